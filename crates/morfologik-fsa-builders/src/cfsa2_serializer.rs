@@ -1,4 +1,468 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Implementacja CFSA2Serializer
 
-// Implementacja CFSA2Serializer
\ No newline at end of file
+use std::io::Write;
+
+use crate::error::Result;
+use crate::fsa_serializer_trait::FsaSerializer;
+use crate::fsa_utils::{collect_states_with_nodes, right_language_counts, StateArc};
+use morfologik_fsa::cfsa2::{
+    CFSA2_MAX_LABEL_MAPPING, CFSA_ARC_IS_FINAL_BIT, CFSA_ARC_IS_LAST_BIT, CFSA_ARC_TARGET_IS_NEXT_BIT,
+};
+use morfologik_fsa::fsa_header::{FsaFlags, FSA_MAGIC, VERSION_CFSA2};
+use morfologik_fsa::fsa_trait::{Fsa, Node};
+
+/// Epsilon (pierwszy) łuk automatu, prowadzący do korzenia.
+const EPSILON_LABEL: u8 = b'^';
+
+/// Początkowy offset stanu przed obliczeniem układu (jak `Integer.MAX_VALUE` w Javie).
+const MAX_OFFSET: usize = i32::MAX as usize;
+
+/// Liczba stanów o największej liczbie łuków wchodzących sprawdzanych przy szukaniu
+/// najlepszego układu: od 25 do 150 co 25 (jak w `CFSA2Serializer.linearize`).
+const FIRST_STATES_PROBE: (usize, usize, usize) = (25, 150, 25);
+
+/// Serializator do formatu CFSA2 opisanego w [`morfologik_fsa::cfsa2::CFSA2`], zgodny
+/// z `CFSA2Serializer.java`.
+///
+/// - 31 najczęstszych etykiet trafia do tablicy indeksów (najczęstsza pod najwyższym
+///   indeksem) i jest zapisywanych w 5 bitach bajtu łuku; pozostałe zajmują dodatkowy bajt.
+/// - Stany z więcej niż dwoma łukami wchodzącymi mogą zostać przeniesione na początek
+///   automatu, by ich adresy (bezwzględne vinty) były krótkie; liczba przenoszonych stanów
+///   jest dobierana próbnie, jak w Javie.
+/// - Pozostałe stany są układane w głąb; łuk do stanu zapisanego zaraz za bieżącym dostaje
+///   `CFSA_ARC_TARGET_IS_NEXT_BIT` i nie zapisuje adresu.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CFSA2Serializer {
+    numbers: bool,
+}
+
+impl CFSA2Serializer {
+    pub fn new() -> Self {
+        CFSA2Serializer { numbers: false }
+    }
+
+    /// Włącza zapis liczby sekwencji dla każdego węzła (flaga NUMBERS).
+    pub fn with_numbers(mut self) -> Self {
+        self.numbers = true;
+        self
+    }
+}
+
+/// Zapisuje `value` jako vint (7 bitów na bajt, od najmłodszych) do `buffer`; zwraca długość.
+fn write_vint(buffer: &mut [u8; 10], mut value: usize) -> usize {
+    let mut length = 0;
+    while value > 0x7F {
+        buffer[length] = 0x80 | (value & 0x7F) as u8;
+        value >>= 7;
+        length += 1;
+    }
+    buffer[length] = value as u8;
+    length + 1
+}
+
+/// Tablica etykiet (`computeLabelsIndex` w Javie): wpis 0 jest nieużywany, a etykiety są
+/// ułożone od najrzadszej do najczęstszej (przy remisie mniejsza etykieta dostaje wyższy
+/// indeks). Zwraca tablicę i indeks każdej etykiety (0 — spoza tablicy).
+fn labels_index(states: &[Vec<StateArc>]) -> (Vec<u8>, [u8; 256]) {
+    let mut frequencies = [0u64; 256];
+    for arc in states.iter().flatten() {
+        frequencies[arc.label as usize] += 1;
+    }
+    let mut labels: Vec<u8> = (0..=255u8).filter(|&l| frequencies[l as usize] > 0).collect();
+    labels.sort_by(|&a, &b| frequencies[b as usize].cmp(&frequencies[a as usize]).then(a.cmp(&b)));
+    labels.truncate(CFSA2_MAX_LABEL_MAPPING);
+
+    let mut mapping = vec![0u8; labels.len() + 1];
+    let mut inverse = [0u8; 256];
+    for (label, index) in labels.into_iter().zip((1..mapping.len()).rev()) {
+        mapping[index] = label;
+        inverse[label as usize] = index as u8;
+    }
+    (mapping, inverse)
+}
+
+/// Liczba łuków wchodzących do każdego stanu osiągalnego z korzenia (łuki do liści pomijane).
+fn inlink_counts(states: &[Vec<StateArc>]) -> Vec<usize> {
+    let mut counts = vec![0usize; states.len()];
+    let mut visited = vec![false; states.len()];
+    let mut stack = vec![0usize];
+    while let Some(state) = stack.pop() {
+        if std::mem::replace(&mut visited[state], true) {
+            continue;
+        }
+        for target in states[state].iter().filter_map(|arc| arc.target) {
+            counts[target] += 1;
+            if !visited[target] {
+                stack.push(target);
+            }
+        }
+    }
+    counts
+}
+
+/// Stany z więcej niż dwoma łukami wchodzącymi, od największej liczby łuków (przy remisie
+/// od największego węzła automatu źródłowego), jak `computeFirstStates` w Javie.
+fn first_states(inlinks: &[usize], nodes: &[Node]) -> Vec<usize> {
+    let mut states: Vec<usize> = (0..inlinks.len()).filter(|&s| inlinks[s] > 2).collect();
+    states.sort_by(|&a, &b| (inlinks[b], nodes[b]).cmp(&(inlinks[a], nodes[a])));
+    states
+}
+
+/// Układ stanów w pliku i ich offsety (odpowiednik pól `linearized` i `offsets` w Javie).
+struct Layout<'a> {
+    states: &'a [Vec<StateArc>],
+    /// Węzły automatu źródłowego (numery stanów w Javie), dla kolejności przy remisach.
+    nodes: &'a [Node],
+    label_index: [u8; 256],
+    counts: Option<Vec<u64>>,
+    linearized: Vec<usize>,
+    offsets: Vec<usize>,
+    scratch: [u8; 10],
+}
+
+impl Layout<'_> {
+    /// Dobiera liczbę stanów przenoszonych na początek i układa automat (`linearize`).
+    fn linearize(&mut self) -> Result<()> {
+        let first = first_states(&inlink_counts(self.states), self.nodes);
+        let serialized_size = self.linearize_and_calculate_offsets(&[])?;
+
+        // Wynik nie jest monotoniczny względem liczby przenoszonych stanów, więc nie ma tu
+        // wyszukiwania binarnego; próbkowany jest tylko początek listy.
+        let (from, to, step) = FIRST_STATES_PROBE;
+        let mut cut_at = 0;
+        let mut cut = from.min(first.len());
+        while cut <= to.min(first.len()) {
+            if self.linearize_and_calculate_offsets(&first[..cut])? >= serialized_size {
+                break;
+            }
+            cut_at = cut;
+            cut += step;
+        }
+        self.linearize_and_calculate_offsets(&first[..cut_at])?;
+        Ok(())
+    }
+
+    /// Układa najpierw stany `first`, potem pozostałe w głąb od korzenia, i powtarza
+    /// obliczanie offsetów aż do ustalenia; zwraca rozmiar danych łuków.
+    fn linearize_and_calculate_offsets(&mut self, first: &[usize]) -> Result<usize> {
+        let mut visited = vec![false; self.states.len()];
+        let mut stack = Vec::new();
+        self.linearized.clear();
+        for &state in first {
+            self.linearize_state(&mut stack, &mut visited, state);
+        }
+        if !self.states[0].is_empty() {
+            stack.push(0);
+        }
+        while let Some(state) = stack.pop() {
+            if !visited[state] {
+                self.linearize_state(&mut stack, &mut visited, state);
+            }
+        }
+
+        self.offsets.fill(MAX_OFFSET);
+        let mut size = 0;
+        loop {
+            match self.emit_nodes(None)? {
+                0 => return Ok(size),
+                changed => size = changed,
+            }
+        }
+    }
+
+    fn linearize_state(&mut self, stack: &mut Vec<usize>, visited: &mut [bool], state: usize) {
+        self.linearized.push(state);
+        visited[state] = true;
+        for target in self.states[state].iter().filter_map(|arc| arc.target) {
+            if !visited[target] {
+                stack.push(target);
+            }
+        }
+    }
+
+    /// Zapisuje (albo dla `None` tylko mierzy) węzeł epsilon i wszystkie stany. Przy
+    /// pomiarze aktualizuje offsety i zwraca rozmiar danych, jeśli któryś się zmienił,
+    /// a 0, jeśli układ jest już ustalony.
+    fn emit_nodes(&mut self, mut writer: Option<&mut dyn Write>) -> Result<usize> {
+        let mut offset = self.emit_node_data(&mut writer, 0)?;
+        let root_offset = if self.states[0].is_empty() { 0 } else { self.offsets[0] };
+        offset += self.emit_arc(&mut writer, CFSA_ARC_IS_LAST_BIT, EPSILON_LABEL, root_offset)?;
+
+        let mut changed = false;
+        for position in 0..self.linearized.len() {
+            let state = self.linearized[position];
+            let next_state = self.linearized.get(position + 1).copied();
+            if writer.is_none() {
+                changed |= self.offsets[state] != offset;
+                self.offsets[state] = offset;
+            }
+            let count = self.counts.as_ref().map_or(0, |counts| counts[state] as usize);
+            offset += self.emit_node_data(&mut writer, count)?;
+
+            let arcs = &self.states[state];
+            for (i, arc) in arcs.iter().enumerate() {
+                let mut target_offset = arc.target.map_or(0, |target| self.offsets[target]);
+                let mut flags = 0;
+                if arc.is_final {
+                    flags |= CFSA_ARC_IS_FINAL_BIT;
+                }
+                if i + 1 == arcs.len() {
+                    flags |= CFSA_ARC_IS_LAST_BIT;
+                }
+                if target_offset != 0 && arc.target == next_state {
+                    flags |= CFSA_ARC_TARGET_IS_NEXT_BIT;
+                    target_offset = 0;
+                }
+                offset += self.emit_arc(&mut writer, flags, arc.label, target_offset)?;
+            }
+        }
+        Ok(if changed { offset } else { 0 })
+    }
+
+    fn emit_arc(&mut self, writer: &mut Option<&mut dyn Write>, flags: u8, label: u8, target_offset: usize) -> Result<usize> {
+        let index = self.label_index[label as usize];
+        let mut head = [flags | index, label];
+        let head = if index > 0 { &mut head[..1] } else { &mut head[..] };
+        let mut length = head.len();
+        if let Some(writer) = writer {
+            writer.write_all(head)?;
+        }
+        if flags & CFSA_ARC_TARGET_IS_NEXT_BIT == 0 {
+            let size = write_vint(&mut self.scratch, target_offset);
+            if let Some(writer) = writer {
+                writer.write_all(&self.scratch[..size])?;
+            }
+            length += size;
+        }
+        Ok(length)
+    }
+
+    fn emit_node_data(&mut self, writer: &mut Option<&mut dyn Write>, count: usize) -> Result<usize> {
+        if self.counts.is_none() {
+            return Ok(0);
+        }
+        let size = write_vint(&mut self.scratch, count);
+        if let Some(writer) = writer {
+            writer.write_all(&self.scratch[..size])?;
+        }
+        Ok(size)
+    }
+}
+
+impl FsaSerializer for CFSA2Serializer {
+    fn serialize(&self, fsa: &dyn Fsa, writer: &mut dyn Write) -> Result<()> {
+        let (states, nodes) = collect_states_with_nodes(fsa)?;
+        let (mapping, label_index) = labels_index(&states);
+        let counts = if self.numbers {
+            Some(right_language_counts(&states)?)
+        } else {
+            None
+        };
+
+        let mut layout = Layout {
+            states: &states,
+            nodes: &nodes,
+            label_index,
+            counts,
+            linearized: Vec::with_capacity(states.len()),
+            offsets: vec![MAX_OFFSET; states.len()],
+            scratch: [0; 10],
+        };
+        layout.linearize()?;
+
+        writer.write_all(&FSA_MAGIC)?;
+        writer.write_all(&[VERSION_CFSA2])?;
+        writer.write_all(&self.get_flags().bits().to_be_bytes())?;
+        writer.write_all(&[mapping.len() as u8])?;
+        writer.write_all(&mapping)?;
+        let size = layout.emit_nodes(Some(writer))?;
+        debug_assert_eq!(size, 0, "offsets changed in the final pass");
+        Ok(())
+    }
+
+    fn get_flags(&self) -> FsaFlags {
+        let flags = FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED;
+        if self.numbers {
+            flags | FsaFlags::NUMBERS
+        } else {
+            flags
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa5_serializer::Fsa5Serializer;
+    use crate::fsa_builder::FsaBuilder;
+    use morfologik_fsa::cfsa2::{CFSA2, CFSA2_LABEL_INDEX_MASK};
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+    use morfologik_fsa::perfect_hash::PerfectHash;
+    use morfologik_fsa::validation::validate;
+    use morfologik_fsa::traversal::{FsaTraversal, MatchKind};
+    use std::io::Cursor;
+
+    fn serialize<S: FsaSerializer>(serializer: &S, input: &[Vec<u8>]) -> Vec<u8> {
+        let fsa = FsaBuilder::build(input.iter()).unwrap();
+        let mut out = Vec::new();
+        serializer.serialize(&fsa, &mut out).unwrap();
+        out
+    }
+
+    fn read(bytes: Vec<u8>) -> CFSA2 {
+        CFSA2::from_reader(&mut Cursor::new(bytes)).unwrap()
+    }
+
+    fn sequences(fsa: &CFSA2) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    fn words(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|s| s.as_bytes().to_vec()).collect()
+    }
+
+    fn corpus() -> Vec<Vec<u8>> {
+        let mut input: Vec<Vec<u8>> = (0..3000u32)
+            .map(|i| format!("słowo{}-{}", i % 97, i * 31 % 1009).into_bytes())
+            .collect();
+        input.sort();
+        input.dedup();
+        input
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = words(&["ala", "alan", "kot", "kota", "kotek", "pies"]);
+        let fsa = read(serialize(&CFSA2Serializer::new(), &input));
+        assert_eq!(sequences(&fsa), input);
+
+        let traversal = FsaTraversal::new(&fsa);
+        assert_eq!(traversal.perfect_match(b"kotek").unwrap().kind, MatchKind::ExactMatch);
+        assert_eq!(traversal.perfect_match(b"kote").unwrap().kind, MatchKind::SequenceIsAPrefix);
+        assert_eq!(traversal.perfect_match(b"mysz").unwrap().kind, MatchKind::NoMatch);
+    }
+
+    #[test]
+    fn test_label_mapping_is_frequency_ranked() {
+        let input = words(&["aab", "ab", "b"]);
+        let fsa = read(serialize(&CFSA2Serializer::new(), &input));
+        // Minimalny automat ma dwa łuki 'a' i trzy łuki 'b' (ostatni stan akceptuje tylko "b");
+        // najczęstsza etykieta dostaje najwyższy indeks.
+        assert_eq!(fsa.label_mapping, vec![0, b'a', b'b']);
+        assert_eq!(fsa.goto_length_info, 3);
+    }
+
+    #[test]
+    fn test_uses_implicit_targets() {
+        let input = words(&["abcdef"]);
+        let bytes = serialize(&CFSA2Serializer::new(), &input);
+        let fsa = read(bytes);
+        // Łuk epsilon (flagi, '^', adres korzenia 3), potem łańcuch stanów: każdy łuk poza
+        // ostatnim wskazuje na stan zaraz za nim, więc nie ma adresów; ostatni prowadzi do liścia.
+        assert_eq!(fsa.arcs_data()[..3], [CFSA_ARC_IS_LAST_BIT, b'^', 3]);
+        assert_eq!(fsa.arcs_data().len(), 3 + 6 + 1);
+        assert!(fsa.arcs_data()[3..8].iter().all(|&b| b & CFSA_ARC_TARGET_IS_NEXT_BIT != 0));
+        assert_eq!(fsa.arcs_data()[8] & !CFSA2_LABEL_INDEX_MASK, CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT);
+        assert_eq!(fsa.arcs_data()[9], 0);
+        assert_eq!(sequences(&fsa), input);
+    }
+
+    #[test]
+    fn test_labels_outside_mapping() {
+        // 40 różnych etykiet: 9 nie mieści się w tablicy i jest zapisywanych jawnie.
+        let input: Vec<Vec<u8>> = (0..40u8).map(|i| vec![b'0' + i, b'x']).collect();
+        let fsa = read(serialize(&CFSA2Serializer::new(), &input));
+        assert_eq!(fsa.label_mapping.len(), CFSA2_MAX_LABEL_MAPPING + 1);
+        assert_eq!(sequences(&fsa), input);
+    }
+
+    #[test]
+    fn test_round_trip_with_numbers() {
+        let input = words(&["a", "ab", "abc", "b", "bc"]);
+        let fsa = read(serialize(&CFSA2Serializer::new().with_numbers(), &input));
+        assert!(fsa.get_flags().contains(FsaFlags::NUMBERS));
+        assert_eq!(sequences(&fsa), input);
+        assert_eq!(fsa.get_right_language_count(fsa.get_root_node()).unwrap(), 5);
+
+        let after_a = fsa.get_end_node(fsa.get_arc(fsa.get_root_node(), b'a').unwrap()).unwrap();
+        assert_eq!(fsa.get_right_language_count(after_a).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_perfect_hash_over_large_automaton() {
+        let input = corpus();
+        let fsa = read(serialize(&CFSA2Serializer::new().with_numbers(), &input));
+        let hash = PerfectHash::new(&fsa).unwrap();
+        assert_eq!(hash.len(), input.len());
+        for (index, sequence) in input.iter().enumerate() {
+            assert_eq!(hash.index_of(sequence).unwrap(), Some(index));
+            assert_eq!(&hash.sequence_at(index).unwrap(), sequence);
+        }
+        assert_eq!(hash.index_of("słowo".as_bytes()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_large_round_trip_is_smaller_than_fsa5() {
+        let input = corpus();
+        let cfsa2 = serialize(&CFSA2Serializer::new(), &input);
+        let fsa5 = serialize(&Fsa5Serializer::new(), &input);
+        assert!(cfsa2.len() < fsa5.len(), "CFSA2 {} vs FSA5 {}", cfsa2.len(), fsa5.len());
+        assert_eq!(sequences(&read(cfsa2)), input);
+    }
+
+    #[test]
+    fn test_serialized_automata_validate() {
+        let input = corpus();
+        for serializer in [CFSA2Serializer::new(), CFSA2Serializer::new().with_numbers()] {
+            let report = validate(&read(serialize(&serializer, &input)));
+            assert!(report.is_valid(), "{:?}", report.issues);
+            assert_eq!(report.sequences, Some(input.len()));
+        }
+    }
+
+    #[test]
+    fn test_empty_automaton() {
+        let fsa = read(serialize(&CFSA2Serializer::new(), &[]));
+        assert!(sequences(&fsa).is_empty());
+    }
+
+    #[test]
+    fn test_write_vint() {
+        let mut buffer = [0u8; 10];
+        assert_eq!(write_vint(&mut buffer, 0), 1);
+        assert_eq!(buffer[0], 0);
+        assert_eq!(write_vint(&mut buffer, 127), 1);
+        assert_eq!(write_vint(&mut buffer, 987), 2);
+        assert_eq!(buffer[..2], [0xdb, 0x07]);
+    }
+
+    /// Słownik polski zbudowany w Javie (CFSA2Serializer).
+    fn polish_dict() -> Vec<u8> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../morfologik-polish/resources/morfologik/stemming/polish/polish.dict");
+        std::fs::read(path).unwrap()
+    }
+
+    /// Odbudowuje polish.dict z jego sekwencji i porównuje wynik z plikiem z Javy bajt po bajcie.
+    ///
+    /// W profilu debug trwa kilka minut, więc jest pomijany; uruchomienie:
+    /// `cargo test --release -p morfologik-fsa-builders -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_rebuilt_polish_dict_is_identical() {
+        let expected = polish_dict();
+        let source = CFSA2::from_bytes(expected.clone()).unwrap();
+        let input = sequences(&source);
+        let rebuilt = serialize(&CFSA2Serializer::new(), &input);
+        assert_eq!(rebuilt.len(), expected.len());
+        assert!(rebuilt == expected, "first difference at byte {}", rebuilt.iter().zip(&expected).take_while(|(a, b)| a == b).count());
+    }
+
+    #[test]
+    fn test_header_matches_java() {
+        let bytes = serialize(&CFSA2Serializer::new(), &words(&["ab"]));
+        assert_eq!(bytes[..5], [b'\\', b'f', b's', b'a', VERSION_CFSA2]);
+        // Flagi FLEXIBLE | STOPBIT | NEXTBIT zapisane big-endian, jak w polish.dict.
+        assert_eq!(bytes[5..7], [0x00, 0x07]);
+        assert_eq!(bytes[7..10], [3, 0, b'b']);
+    }
+}
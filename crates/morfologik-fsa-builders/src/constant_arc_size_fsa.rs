@@ -0,0 +1,144 @@
+// Automat w pamięci o stałym rozmiarze łuku (odpowiednik ConstantArcSizeFSA.java)
+
+use morfologik_fsa::error::{FsaError, Result};
+use morfologik_fsa::fsa_header::FsaFlags;
+use morfologik_fsa::fsa_trait::{ArcOffset, Fsa, Node};
+
+/// Flaga łuku: łuk kończy akceptowaną sekwencję.
+pub const BIT_ARC_FINAL: u8 = 1 << 0;
+/// Flaga łuku: łuk jest ostatnim łukiem wychodzącym z węzła.
+pub const BIT_ARC_LAST: u8 = 1 << 1;
+
+/// Rozmiar pola flag w bajtach.
+pub const FLAGS_SIZE: usize = 1;
+/// Rozmiar etykiety w bajtach.
+pub const LABEL_SIZE: usize = 1;
+/// Rozmiar adresu docelowego w bajtach.
+pub const TARGET_ADDRESS_SIZE: usize = 4;
+/// Całkowity rozmiar łuku w bajtach.
+pub const ARC_SIZE: usize = FLAGS_SIZE + LABEL_SIZE + TARGET_ADDRESS_SIZE;
+
+/// Offset flag w obrębie łuku.
+pub const FLAGS_OFFSET: usize = 0;
+/// Offset etykiety w obrębie łuku.
+pub const LABEL_OFFSET: usize = FLAGS_SIZE;
+/// Offset adresu docelowego w obrębie łuku.
+pub const ADDRESS_OFFSET: usize = LABEL_OFFSET + LABEL_SIZE;
+
+/// Adres stanu końcowego (bez łuków wychodzących).
+///
+/// Pod adresem 0 leży łuk epsilon wskazujący na korzeń, więc żaden
+/// rzeczywisty stan nie może zaczynać się pod tym adresem.
+pub const TERMINAL_STATE: Node = 0;
+
+/// Automat przechowywany w pamięci, w którym każdy łuk zajmuje [`ARC_SIZE`] bajtów.
+///
+/// Jest to wynik działania [`crate::fsa_builder::FsaBuilder`]. Format nie jest
+/// przeznaczony do zapisu na dysk — do tego służą serializatory.
+///
+/// Struktura łuku:
+///
+/// ```text
+/// byte   flags;   // BIT_ARC_FINAL | BIT_ARC_LAST
+/// byte   label;
+/// u32 LE target;  // adres pierwszego łuku węzła docelowego lub TERMINAL_STATE
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConstantArcSizeFsa {
+    data: Vec<u8>,
+    root: Node,
+}
+
+impl ConstantArcSizeFsa {
+    /// Tworzy automat z surowych danych; `epsilon` to adres łuku wskazującego na korzeń.
+    pub(crate) fn new(data: Vec<u8>, epsilon: ArcOffset) -> Self {
+        let root = read_target(&data, epsilon);
+        ConstantArcSizeFsa { data, root }
+    }
+
+    /// Surowe dane łuków automatu.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn check_arc(&self, arc: ArcOffset) -> Result<()> {
+        if arc + ARC_SIZE > self.data.len() {
+            return Err(FsaError::InvalidArcOffset(arc));
+        }
+        Ok(())
+    }
+}
+
+fn read_target(data: &[u8], arc: ArcOffset) -> Node {
+    let start = arc + ADDRESS_OFFSET;
+    let mut bytes = [0u8; TARGET_ADDRESS_SIZE];
+    bytes.copy_from_slice(&data[start..start + TARGET_ADDRESS_SIZE]);
+    u32::from_le_bytes(bytes) as Node
+}
+
+impl Fsa for ConstantArcSizeFsa {
+    fn get_flags(&self) -> FsaFlags {
+        FsaFlags::empty()
+    }
+
+    fn get_root_node(&self) -> Node {
+        self.root
+    }
+
+    fn get_first_arc(&self, node: Node) -> Result<ArcOffset> {
+        if node == TERMINAL_STATE || node + ARC_SIZE > self.data.len() {
+            return Err(FsaError::InvalidNodeOffset(node));
+        }
+        Ok(node)
+    }
+
+    fn first_arc_opt(&self, node: Node) -> Result<Option<ArcOffset>> {
+        if node == TERMINAL_STATE {
+            return Ok(None);
+        }
+        self.get_first_arc(node).map(Some)
+    }
+
+    fn get_next_arc(&self, arc: ArcOffset) -> Result<ArcOffset> {
+        if self.is_arc_terminal(arc)? {
+            return Err(FsaError::NoNextArc(arc));
+        }
+        Ok(arc + ARC_SIZE)
+    }
+
+    fn get_arc(&self, node: Node, label: u8) -> Result<ArcOffset> {
+        if node == TERMINAL_STATE {
+            return Err(FsaError::ArcNotFound { label, node_offset: node });
+        }
+        let mut arc = self.get_first_arc(node)?;
+        loop {
+            if self.get_arc_label(arc)? == label {
+                return Ok(arc);
+            }
+            if self.is_arc_terminal(arc)? {
+                return Err(FsaError::ArcNotFound { label, node_offset: node });
+            }
+            arc += ARC_SIZE;
+        }
+    }
+
+    fn get_end_node(&self, arc: ArcOffset) -> Result<Node> {
+        self.check_arc(arc)?;
+        Ok(read_target(&self.data, arc))
+    }
+
+    fn get_arc_label(&self, arc: ArcOffset) -> Result<u8> {
+        self.check_arc(arc)?;
+        Ok(self.data[arc + LABEL_OFFSET])
+    }
+
+    fn is_arc_final(&self, arc: ArcOffset) -> Result<bool> {
+        self.check_arc(arc)?;
+        Ok(self.data[arc + FLAGS_OFFSET] & BIT_ARC_FINAL != 0)
+    }
+
+    fn is_arc_terminal(&self, arc: ArcOffset) -> Result<bool> {
+        self.check_arc(arc)?;
+        Ok(self.data[arc + FLAGS_OFFSET] & BIT_ARC_LAST != 0)
+    }
+}
@@ -1,4 +1,41 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Błędy specyficzne dla morfologik-fsa-builders
 
-// Błędy specyficzne dla morfologik-fsa-builders
\ No newline at end of file
+use morfologik_fsa::error::FsaError;
+use thiserror::Error;
+
+/// Typ Result używany w tym crate.
+pub type Result<T> = std::result::Result<T, BuilderError>;
+
+/// Enum reprezentujący błędy, które mogą wystąpić podczas budowania i serializacji automatów.
+#[derive(Debug, Error)]
+pub enum BuilderError {
+    /// Błąd wejścia/wyjścia.
+    #[error("Błąd I/O: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Błąd zgłoszony przez automat źródłowy.
+    #[error("Błąd automatu: {0}")]
+    Fsa(#[from] FsaError),
+
+    /// Sekwencje wejściowe nie są posortowane leksykograficznie (bajtowo).
+    #[error("Sekwencje wejściowe nie są posortowane: {current:?} występuje po {previous:?}")]
+    UnsortedInput { previous: Vec<u8>, current: Vec<u8> },
+
+    /// Pusta sekwencja nie może zostać dodana do automatu.
+    #[error("Nie można dodać pustej sekwencji do automatu.")]
+    EmptySequence,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsorted_input_display() {
+        let err = BuilderError::UnsortedInput { previous: b"b".to_vec(), current: b"a".to_vec() };
+        assert_eq!(
+            err.to_string(),
+            "Sekwencje wejściowe nie są posortowane: [97] występuje po [98]"
+        );
+    }
+}
@@ -1,4 +1,424 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Implementacja FSA5Serializer
 
-// Implementacja FSA5Serializer
\ No newline at end of file
+use std::io::Write;
+
+use crate::error::Result;
+use crate::fsa_serializer_trait::FsaSerializer;
+use crate::fsa_utils::{collect_states, right_language_counts, StateArc};
+use morfologik_fsa::fsa5::{ARC_FLAGS_BITS, BIT_ARC_FINAL, BIT_ARC_LAST, BIT_ARC_TARGET_NEXT};
+use morfologik_fsa::fsa_header::{FsaFlags, FSA_MAGIC, VERSION_FSA5};
+use morfologik_fsa::fsa_trait::Fsa;
+
+/// Domyślny bajt wypełnienia (jak w `FSA5Serializer.java`).
+pub const DEFAULT_FILLER: u8 = b'_';
+/// Domyślny separator adnotacji (jak w `FSA5Serializer.java`).
+pub const DEFAULT_ANNOTATION: u8 = b'+';
+
+/// Etykieta łuku węzła epsilon, prowadzącego do korzenia.
+const EPSILON_LABEL: u8 = b'^';
+
+/// Serializator do formatu FSA5 opisanego w [`morfologik_fsa::fsa5::FSA5`], zgodny bajtowo
+/// z `FSA5Serializer.java`.
+///
+/// - Na początku danych zapisywany jest pusty węzeł stanu końcowego i węzeł epsilon z łukiem
+///   do korzenia; stany są układane w głąb od korzenia.
+/// - Ostatni łuk stanu, którego cel jest zapisany zaraz za nim, dostaje `BIT_ARC_TARGET_NEXT`
+///   i nie zapisuje adresu.
+/// - Rozmiar pola adresu (`gtl`) to najmniejsza liczba bajtów, w której mieszczą się
+///   wszystkie adresy; przy włączonych liczbach sekwencji ich rozmiar wynika z liczby
+///   sekwencji całego automatu. Oba trafiają do bajtu `hgtl` nagłówka, obok bajtu
+///   wypełnienia i separatora adnotacji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fsa5Serializer {
+    numbers: bool,
+    filler: u8,
+    annotation: u8,
+}
+
+impl Default for Fsa5Serializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fsa5Serializer {
+    pub fn new() -> Self {
+        Fsa5Serializer {
+            numbers: false,
+            filler: DEFAULT_FILLER,
+            annotation: DEFAULT_ANNOTATION,
+        }
+    }
+
+    /// Włącza zapis liczby sekwencji dla każdego węzła (flaga NUMBERS).
+    pub fn with_numbers(mut self) -> Self {
+        self.numbers = true;
+        self
+    }
+
+    pub fn with_filler(mut self, filler: u8) -> Self {
+        self.filler = filler;
+        self
+    }
+
+    pub fn with_annotation_separator(mut self, annotation: u8) -> Self {
+        self.annotation = annotation;
+        self
+    }
+
+    pub fn filler(&self) -> u8 {
+        self.filler
+    }
+
+    pub fn annotation_separator(&self) -> u8 {
+        self.annotation
+    }
+}
+
+/// Liczba bajtów potrzebna do zapisania `value` (0 dla zera).
+fn bytes_for(mut value: u64) -> u8 {
+    let mut bytes = 0;
+    while value > 0 {
+        bytes += 1;
+        value >>= 8;
+    }
+    bytes
+}
+
+/// Układ stanów w pliku i ich offsety (odpowiednik pól `linearized` i `offsets` w Javie).
+struct Layout<'a> {
+    states: &'a [Vec<StateArc>],
+    counts: Option<Vec<u64>>,
+    node_data_length: u8,
+    linearized: Vec<usize>,
+    /// Offsety stanów z ostatniego pomiaru; 0 dla stanów jeszcze nie umieszczonych.
+    offsets: Vec<usize>,
+}
+
+impl Layout<'_> {
+    /// Układa stany w głąb od korzenia; cele łuków są odkładane na stos w kolejności łuków.
+    fn linearize(&mut self) {
+        let mut visited = vec![false; self.states.len()];
+        let mut stack = Vec::new();
+        if !self.states[0].is_empty() {
+            stack.push(0);
+        }
+        while let Some(state) = stack.pop() {
+            if std::mem::replace(&mut visited[state], true) {
+                continue;
+            }
+            self.linearized.push(state);
+            for target in self.states[state].iter().filter_map(|arc| arc.target) {
+                if !visited[target] {
+                    stack.push(target);
+                }
+            }
+        }
+    }
+
+    /// Zapisuje (albo dla `None` tylko mierzy, aktualizując offsety) wszystkie węzły dla
+    /// danego `gtl`; zwraca `false`, jeśli któryś adres nie mieści się w `gtl` bajtach.
+    fn emit_arcs(&mut self, mut writer: Option<&mut dyn Write>, gtl: u8) -> Result<bool> {
+        // Pusty węzeł stanu końcowego.
+        let mut offset = self.emit_node_data(&mut writer, 0)?;
+        offset += self.emit_arc(&mut writer, gtl, 0, 0, 0)?.unwrap_or(0);
+
+        // Węzeł epsilon z łukiem do korzenia (zapisanego zaraz za nim).
+        offset += self.emit_node_data(&mut writer, 0)?;
+        let flags = if self.linearized.is_empty() { BIT_ARC_LAST } else { BIT_ARC_LAST | BIT_ARC_TARGET_NEXT };
+        offset += self.emit_arc(&mut writer, gtl, flags, EPSILON_LABEL, 0)?.unwrap_or(0);
+
+        for position in 0..self.linearized.len() {
+            let state = self.linearized[position];
+            if writer.is_none() {
+                self.offsets[state] = offset;
+            } else {
+                debug_assert_eq!(self.offsets[state], offset, "offset of state {} changed", state);
+            }
+            let count = self.counts.as_ref().map_or(0, |counts| counts[state]);
+            offset += self.emit_node_data(&mut writer, count)?;
+
+            let arcs = &self.states[state];
+            for (i, arc) in arcs.iter().enumerate() {
+                let mut target_offset = arc.target.map_or(0, |target| self.offsets[target]);
+                let mut flags = 0;
+                if arc.is_final {
+                    flags |= BIT_ARC_FINAL;
+                }
+                if i + 1 == arcs.len() {
+                    flags |= BIT_ARC_LAST;
+                    if target_offset != 0 && arc.target == self.linearized.get(position + 1).copied() {
+                        flags |= BIT_ARC_TARGET_NEXT;
+                        target_offset = 0;
+                    }
+                }
+                match self.emit_arc(&mut writer, gtl, flags, arc.label, target_offset)? {
+                    Some(size) => offset += size,
+                    None => return Ok(false),
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Zapisuje łuk: etykietę i `(target_offset << 3) | flags` na `gtl` bajtach (1 bajt dla
+    /// łuku z `BIT_ARC_TARGET_NEXT`). Zwraca rozmiar łuku albo `None`, jeśli adres się nie mieści.
+    fn emit_arc(&self, writer: &mut Option<&mut dyn Write>, gtl: u8, flags: u8, label: u8, target_offset: usize) -> Result<Option<usize>> {
+        let address_bytes = if flags & BIT_ARC_TARGET_NEXT != 0 { 1 } else { gtl as usize };
+        let value = ((target_offset as u64) << ARC_FLAGS_BITS) | flags as u64;
+        if address_bytes < 8 && value >> (8 * address_bytes) != 0 {
+            return Ok(None);
+        }
+        if let Some(writer) = writer {
+            writer.write_all(&[label])?;
+            writer.write_all(&value.to_le_bytes()[..address_bytes])?;
+        }
+        Ok(Some(1 + address_bytes))
+    }
+
+    fn emit_node_data(&self, writer: &mut Option<&mut dyn Write>, count: u64) -> Result<usize> {
+        let length = self.node_data_length as usize;
+        if let Some(writer) = writer {
+            writer.write_all(&count.to_le_bytes()[..length])?;
+        }
+        Ok(length)
+    }
+}
+
+impl FsaSerializer for Fsa5Serializer {
+    fn serialize(&self, fsa: &dyn Fsa, writer: &mut dyn Write) -> Result<()> {
+        let states = collect_states(fsa)?;
+        let counts = if self.numbers {
+            Some(right_language_counts(&states)?)
+        } else {
+            None
+        };
+        let node_data_length = counts.as_ref().map_or(0, |c| bytes_for(c[0]));
+
+        let mut layout = Layout {
+            states: &states,
+            counts,
+            node_data_length,
+            linearized: Vec::with_capacity(states.len()),
+            offsets: vec![0; states.len()],
+        };
+        layout.linearize();
+
+        // Pierwszy pomiar ustala offsety, drugi sprawdza, czy adresy mieszczą się w gtl bajtach.
+        let mut gtl = 1u8;
+        loop {
+            if !layout.emit_arcs(None, gtl)? {
+                gtl += 1;
+                continue;
+            }
+            if layout.emit_arcs(None, gtl)? {
+                break;
+            }
+            gtl += 1;
+        }
+
+        writer.write_all(&FSA_MAGIC)?;
+        writer.write_all(&[VERSION_FSA5, self.filler, self.annotation, (node_data_length << 4) | gtl])?;
+        layout.emit_arcs(Some(writer), gtl)?;
+        Ok(())
+    }
+
+    fn get_flags(&self) -> FsaFlags {
+        let flags = FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED;
+        if self.numbers {
+            flags | FsaFlags::NUMBERS
+        } else {
+            flags
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa_builder::FsaBuilder;
+    use morfologik_fsa::fsa5::FSA5;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+    use morfologik_fsa::perfect_hash::PerfectHash;
+    use morfologik_fsa::validation::validate;
+    use morfologik_fsa::traversal::{FsaTraversal, MatchKind};
+    use std::io::Cursor;
+
+    fn serialize(serializer: &Fsa5Serializer, input: &[&[u8]]) -> Vec<u8> {
+        let fsa = FsaBuilder::build(input.iter()).unwrap();
+        let mut out = Vec::new();
+        serializer.serialize(&fsa, &mut out).unwrap();
+        out
+    }
+
+    fn read(bytes: Vec<u8>) -> FSA5 {
+        FSA5::from_reader(&mut Cursor::new(bytes)).unwrap()
+    }
+
+    fn sequences(fsa: &FSA5) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    /// Nagłówek FSA5 z domyślnym bajtem wypełnienia i separatorem adnotacji.
+    fn header(hgtl: u8) -> Vec<u8> {
+        let mut bytes = FSA_MAGIC.to_vec();
+        bytes.extend_from_slice(&[VERSION_FSA5, DEFAULT_FILLER, DEFAULT_ANNOTATION, hgtl]);
+        bytes
+    }
+
+    // Oczekiwane bajty poniżej wyprowadzone są ręcznie z `FSA5Serializer.java` (pusty węzeł
+    // stanu końcowego, węzeł epsilon, stany w głąb od korzenia, NEXT tylko na ostatnim łuku).
+
+    #[test]
+    fn test_header_and_simple_layout() {
+        let bytes = serialize(&Fsa5Serializer::new(), &[b"a", b"b"]);
+        let mut expected = header(0x01);
+        expected.extend_from_slice(&[0, 0]);
+        expected.extend_from_slice(&[b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        // Korzeń (adres 4): dwa łuki do stanu końcowego (adres 0).
+        expected.extend_from_slice(&[b'a', BIT_ARC_FINAL]);
+        expected.extend_from_slice(&[b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_explicit_and_next_targets_match_java() {
+        // 'a' nie jest ostatnim łukiem korzenia, więc zapisuje adres stanu {"b"} (8 << 3).
+        let bytes = serialize(&Fsa5Serializer::new(), &[b"ab", b"b"]);
+        let mut expected = header(0x01);
+        expected.extend_from_slice(&[0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        expected.extend_from_slice(&[b'a', 8 << 3, b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        expected.extend_from_slice(&[b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        assert_eq!(bytes, expected);
+
+        // Łańcuch: każdy ostatni łuk prowadzi do stanu zapisanego zaraz za nim.
+        let bytes = serialize(&Fsa5Serializer::new(), &[b"abc"]);
+        let mut expected = header(0x01);
+        expected.extend_from_slice(&[0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        expected.extend_from_slice(&[b'a', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        expected.extend_from_slice(&[b'b', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        expected.extend_from_slice(&[b'c', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_numbers_layout_matches_java() {
+        let bytes = serialize(&Fsa5Serializer::new().with_numbers(), &[b"a", b"ab", b"b"]);
+        let mut expected = header(0x11);
+        expected.extend_from_slice(&[0, 0, 0]);
+        expected.extend_from_slice(&[0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        // Korzeń (adres 6): 3 sekwencje; 'a' prowadzi do stanu {"b"} pod adresem 11.
+        expected.extend_from_slice(&[3, b'a', (11 << 3) | BIT_ARC_FINAL, b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        expected.extend_from_slice(&[1, b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_filler_and_annotation_are_written() {
+        let serializer = Fsa5Serializer::new().with_filler(b'#').with_annotation_separator(b'|');
+        let bytes = serialize(&serializer, &[b"a"]);
+        assert_eq!(bytes[FSA_MAGIC.len()..FSA_MAGIC.len() + 4], [VERSION_FSA5, b'#', b'|', 0x01]);
+        let fsa = read(bytes);
+        assert_eq!((fsa.filler, fsa.annotation), (b'#', b'|'));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input: Vec<&[u8]> = vec![b"ala", b"alan", b"kot", b"kota", b"kotek", b"pies"];
+        let fsa = read(serialize(&Fsa5Serializer::new(), &input));
+        let expected: Vec<Vec<u8>> = input.iter().map(|s| s.to_vec()).collect();
+        assert_eq!(sequences(&fsa), expected);
+
+        let traversal = FsaTraversal::new(&fsa);
+        assert_eq!(traversal.perfect_match(b"kotek").unwrap().kind, MatchKind::ExactMatch);
+        assert_eq!(traversal.perfect_match(b"kote").unwrap().kind, MatchKind::SequenceIsAPrefix);
+        assert_eq!(traversal.perfect_match(b"mysz").unwrap().kind, MatchKind::NoMatch);
+    }
+
+    #[test]
+    fn test_round_trip_with_numbers() {
+        let input: Vec<&[u8]> = vec![b"a", b"ab", b"abc", b"b", b"bc"];
+        let fsa = read(serialize(&Fsa5Serializer::new().with_numbers(), &input));
+        assert!(fsa.get_flags().contains(FsaFlags::NUMBERS));
+        assert_eq!(sequences(&fsa).len(), input.len());
+        assert_eq!(fsa.get_right_language_count(fsa.get_root_node()).unwrap(), 5);
+
+        let after_a = fsa.get_end_node(fsa.get_arc(fsa.get_root_node(), b'a').unwrap()).unwrap();
+        assert_eq!(fsa.get_right_language_count(after_a).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_perfect_hash_is_lexicographic() {
+        let input: Vec<&[u8]> = vec![b"a", b"ab", b"abc", b"b", b"bc"];
+        let fsa = read(serialize(&Fsa5Serializer::new().with_numbers(), &input));
+        let hash = PerfectHash::new(&fsa).unwrap();
+        for (index, sequence) in input.iter().enumerate() {
+            assert_eq!(hash.index_of(sequence).unwrap(), Some(index));
+            assert_eq!(hash.sequence_at(index).unwrap(), sequence.to_vec());
+        }
+        assert_eq!(hash.index_of(b"c").unwrap(), None);
+    }
+
+    #[test]
+    fn test_large_automaton_uses_wider_addresses() {
+        let mut input: Vec<Vec<u8>> = (0..2000u32)
+            .map(|i| format!("{}-{}", i, i * i % 9973).into_bytes())
+            .collect();
+        input.sort();
+        let fsa = FsaBuilder::build(input.iter()).unwrap();
+        let mut out = Vec::new();
+        Fsa5Serializer::new().serialize(&fsa, &mut out).unwrap();
+        let fsa5 = read(out);
+        assert!(fsa5.goto_length >= 2);
+        assert_eq!(sequences(&fsa5), input);
+
+        let mut out = Vec::new();
+        Fsa5Serializer::new().with_numbers().serialize(&fsa, &mut out).unwrap();
+        let report = validate(&read(out));
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.sequences, Some(input.len()));
+    }
+
+    #[test]
+    fn test_empty_automaton() {
+        let bytes = serialize(&Fsa5Serializer::new(), &[]);
+        let mut expected = header(0x01);
+        expected.extend_from_slice(&[0, 0, b'^', BIT_ARC_LAST]);
+        assert_eq!(bytes, expected);
+        assert!(sequences(&read(bytes)).is_empty());
+    }
+
+    #[test]
+    fn test_filler_and_annotation_defaults() {
+        let serializer = Fsa5Serializer::new();
+        assert_eq!(serializer.filler(), DEFAULT_FILLER);
+        assert_eq!(serializer.annotation_separator(), DEFAULT_ANNOTATION);
+        let serializer = serializer.with_filler(b'#').with_annotation_separator(b'|');
+        assert_eq!(serializer.filler(), b'#');
+        assert_eq!(serializer.annotation_separator(), b'|');
+    }
+
+    /// Przepisuje polish.dict (CFSA2 z Javy) do FSA5: wynik musi być poprawny i akceptować
+    /// te same sekwencje w tej samej kolejności.
+    ///
+    /// Trwa długo w trybie debug; uruchamiać przez
+    /// `cargo test --release -p morfologik-fsa-builders -- --ignored polish_dict`.
+    #[test]
+    #[ignore]
+    fn test_polish_dict_survives_fsa5_round_trip() {
+        use morfologik_fsa::cfsa2::CFSA2;
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../morfologik-polish/resources/morfologik/stemming/polish/polish.dict");
+        let original = std::fs::read(path).unwrap();
+        let cfsa2 = CFSA2::from_bytes(&original[..]).unwrap();
+
+        let mut bytes = Vec::new();
+        Fsa5Serializer::new().with_numbers().serialize(&cfsa2, &mut bytes).unwrap();
+        let fsa5 = read(bytes);
+        let report = validate(&fsa5);
+        assert!(report.is_valid(), "{:?}", report.issues);
+
+        let expected = ByteSequenceIterator::new(&cfsa2).map(|s| s.unwrap());
+        assert!(expected.eq(ByteSequenceIterator::new(&fsa5).map(|s| s.unwrap())));
+    }
+}
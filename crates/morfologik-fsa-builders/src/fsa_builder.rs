@@ -1,4 +1,349 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika FSABuilder
 
-// Logika FSABuilder
\ No newline at end of file
+use std::cmp::Ordering;
+
+use crate::constant_arc_size_fsa::{
+    ConstantArcSizeFsa, ARC_SIZE, BIT_ARC_FINAL, BIT_ARC_LAST, FLAGS_OFFSET, TERMINAL_STATE,
+};
+use crate::error::{BuilderError, Result};
+use morfologik_fsa::fsa_trait::Node;
+
+/// Początkowy rozmiar tablicy rejestru stanów (musi być potęgą dwójki).
+const INITIAL_REGISTER_SIZE: usize = 1 << 10;
+
+/// Łuk stanu na aktywnej ścieżce (jeszcze niezamrożonego).
+#[derive(Debug, Clone, Copy)]
+struct PendingArc {
+    label: u8,
+    is_final: bool,
+    /// Adres zamrożonego stanu docelowego. Dla ostatniego łuku stanu
+    /// na aktywnej ścieżce jest uzupełniany dopiero przy zamrażaniu następnika.
+    target: Node,
+}
+
+/// Przyrostowy konstruktor minimalnych automatów acyklicznych.
+///
+/// Implementacja algorytmu Daciuka i in. (odpowiednik `FSABuilder.java`):
+/// sekwencje muszą być dodawane w porządku leksykograficznym (bajtowym),
+/// dzięki czemu wszystkie stany poza aktywną ścieżką (ostatnio dodaną sekwencją)
+/// można od razu zamrozić i zastąpić równoważnym stanem z rejestru.
+///
+/// ```
+/// use morfologik_fsa::iterator::ByteSequenceIterator;
+/// use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+///
+/// let fsa = FsaBuilder::build([&b"kot"[..], b"kota", b"pies"]).unwrap();
+/// let words: Vec<Vec<u8>> = ByteSequenceIterator::new(&fsa).map(|s| s.unwrap()).collect();
+/// assert_eq!(words, vec![b"kot".to_vec(), b"kota".to_vec(), b"pies".to_vec()]);
+/// ```
+#[derive(Debug)]
+pub struct FsaBuilder {
+    /// Zamrożone stany; pod adresem 0 rezerwowane jest miejsce na łuk epsilon.
+    serialized: Vec<u8>,
+    /// Tablica z adresowaniem otwartym: adresy stanów w `serialized` (0 = puste pole).
+    register: Vec<Node>,
+    register_count: usize,
+    /// Stany aktywnej ścieżki; `active[d]` to stan na głębokości `d`.
+    /// Wektory są ponownie używane, więc liczy się tylko `active[..=depth]`.
+    active: Vec<Vec<PendingArc>>,
+    depth: usize,
+    previous: Option<Vec<u8>>,
+    /// Bufor na serializowany stan przed sprawdzeniem w rejestrze.
+    scratch: Vec<u8>,
+}
+
+impl Default for FsaBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsaBuilder {
+    pub fn new() -> Self {
+        FsaBuilder {
+            serialized: vec![0u8; ARC_SIZE],
+            register: vec![0; INITIAL_REGISTER_SIZE],
+            register_count: 0,
+            active: vec![Vec::new()],
+            depth: 0,
+            previous: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Buduje automat z posortowanych sekwencji.
+    pub fn build<I, S>(sequences: I) -> Result<ConstantArcSizeFsa>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        let mut builder = FsaBuilder::new();
+        for sequence in sequences {
+            builder.add(sequence.as_ref())?;
+        }
+        Ok(builder.complete())
+    }
+
+    /// Dodaje sekwencję do automatu.
+    ///
+    /// Sekwencja nie może być mniejsza od poprzednio dodanej
+    /// ([`BuilderError::UnsortedInput`]); powtórzenie ostatniej sekwencji jest ignorowane.
+    pub fn add(&mut self, sequence: &[u8]) -> Result<()> {
+        if sequence.is_empty() {
+            return Err(BuilderError::EmptySequence);
+        }
+
+        let common_prefix = match &self.previous {
+            Some(previous) => match previous.as_slice().cmp(sequence) {
+                Ordering::Greater => {
+                    return Err(BuilderError::UnsortedInput {
+                        previous: previous.clone(),
+                        current: sequence.to_vec(),
+                    });
+                }
+                Ordering::Equal => return Ok(()),
+                Ordering::Less => previous
+                    .iter()
+                    .zip(sequence.iter())
+                    .take_while(|&(a, b)| a == b)
+                    .count(),
+            },
+            None => 0,
+        };
+
+        // Wszystko poniżej wspólnego prefiksu nie zmieni się już nigdy.
+        self.freeze_down_to(common_prefix);
+
+        for (i, &label) in sequence.iter().enumerate().skip(common_prefix) {
+            self.active[i].push(PendingArc {
+                label,
+                is_final: i + 1 == sequence.len(),
+                target: TERMINAL_STATE,
+            });
+            self.depth = i + 1;
+            if self.active.len() <= self.depth {
+                self.active.push(Vec::new());
+            }
+            self.active[self.depth].clear();
+        }
+
+        match &mut self.previous {
+            Some(previous) => {
+                previous.clear();
+                previous.extend_from_slice(sequence);
+            }
+            None => self.previous = Some(sequence.to_vec()),
+        }
+        Ok(())
+    }
+
+    /// Zamraża pozostałe stany aktywnej ścieżki i zwraca gotowy automat.
+    pub fn complete(mut self) -> ConstantArcSizeFsa {
+        self.freeze_down_to(0);
+        let root = self.freeze_state(0);
+
+        // Łuk epsilon pod adresem 0 wskazuje na korzeń.
+        self.serialized[FLAGS_OFFSET] = BIT_ARC_LAST;
+        self.serialized[2..ARC_SIZE].copy_from_slice(&to_address(root).to_le_bytes());
+
+        ConstantArcSizeFsa::new(self.serialized, 0)
+    }
+
+    /// Zamraża stany aktywnej ścieżki głębsze niż `depth`, podpinając je pod rodziców.
+    fn freeze_down_to(&mut self, depth: usize) {
+        while self.depth > depth {
+            let address = self.freeze_state(self.depth);
+            self.depth -= 1;
+            if let Some(arc) = self.active[self.depth].last_mut() {
+                arc.target = address;
+            }
+        }
+    }
+
+    /// Zwraca adres stanu równoważnego `active[depth]`, dodając go do rejestru w razie potrzeby.
+    fn freeze_state(&mut self, depth: usize) -> Node {
+        let arcs = &self.active[depth];
+        if arcs.is_empty() {
+            return TERMINAL_STATE;
+        }
+
+        self.scratch.clear();
+        for (i, arc) in arcs.iter().enumerate() {
+            let mut flags = 0u8;
+            if arc.is_final {
+                flags |= BIT_ARC_FINAL;
+            }
+            if i + 1 == arcs.len() {
+                flags |= BIT_ARC_LAST;
+            }
+            self.scratch.push(flags);
+            self.scratch.push(arc.label);
+            self.scratch.extend_from_slice(&to_address(arc.target).to_le_bytes());
+        }
+
+        let mask = self.register.len() - 1;
+        let mut slot = hash(&self.scratch) & mask;
+        let mut step = 0;
+        loop {
+            let address = self.register[slot];
+            if address == 0 {
+                break;
+            }
+            // Ostatni łuk w `scratch` ma BIT_ARC_LAST, więc zgodność bajtów oznacza równość stanów.
+            if self.serialized.get(address..address + self.scratch.len()) == Some(&self.scratch[..]) {
+                return address;
+            }
+            step += 1;
+            slot = (slot + step) & mask;
+        }
+
+        let address = self.serialized.len();
+        self.serialized.extend_from_slice(&self.scratch);
+        self.register[slot] = address;
+        self.register_count += 1;
+        if self.register_count * 2 > self.register.len() {
+            self.expand_register();
+        }
+        address
+    }
+
+    /// Długość (w bajtach) zamrożonego stanu zaczynającego się pod `address`.
+    fn state_length(&self, address: Node) -> usize {
+        let mut arc = address;
+        while self.serialized[arc + FLAGS_OFFSET] & BIT_ARC_LAST == 0 {
+            arc += ARC_SIZE;
+        }
+        arc + ARC_SIZE - address
+    }
+
+    fn expand_register(&mut self) {
+        let new_size = self.register.len() * 2;
+        let old = std::mem::replace(&mut self.register, vec![0; new_size]);
+        let mask = self.register.len() - 1;
+        for address in old.into_iter().filter(|&a| a != 0) {
+            let length = self.state_length(address);
+            let mut slot = hash(&self.serialized[address..address + length]) & mask;
+            let mut step = 0;
+            while self.register[slot] != 0 {
+                step += 1;
+                slot = (slot + step) & mask;
+            }
+            self.register[slot] = address;
+        }
+    }
+}
+
+fn to_address(node: Node) -> u32 {
+    u32::try_from(node).expect("Automat przekracza maksymalny rozmiar (4 GB) ConstantArcSizeFsa")
+}
+
+/// Skrót FNV-1a serializowanego stanu.
+fn hash(bytes: &[u8]) -> usize {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use morfologik_fsa::fsa_trait::Fsa;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+    use morfologik_fsa::traversal::{FsaTraversal, MatchKind};
+
+    fn sequences(fsa: &ConstantArcSizeFsa) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    fn count_states(fsa: &ConstantArcSizeFsa) -> usize {
+        let mut seen = std::collections::HashSet::new();
+        let mut stack = vec![fsa.get_root_node()];
+        while let Some(node) = stack.pop() {
+            if !seen.insert(node) {
+                continue;
+            }
+            let Some(mut arc) = fsa.first_arc_opt(node).unwrap() else {
+                continue;
+            };
+            loop {
+                stack.push(fsa.get_end_node(arc).unwrap());
+                match fsa.get_next_arc(arc) {
+                    Ok(next) => arc = next,
+                    Err(_) => break,
+                }
+            }
+        }
+        seen.len()
+    }
+
+    #[test]
+    fn test_build_and_iterate_sorted() {
+        let input: Vec<&[u8]> = vec![b"a", b"ab", b"abc", b"ax", b"b", b"kot", b"kota"];
+        let fsa = FsaBuilder::build(input.iter()).unwrap();
+        let expected: Vec<Vec<u8>> = input.iter().map(|s| s.to_vec()).collect();
+        assert_eq!(sequences(&fsa), expected);
+    }
+
+    #[test]
+    fn test_minimal_shared_suffixes() {
+        // "kota", "lota", "mota" mają wspólny sufiks "ota", więc po korzeniu
+        // automat potrzebuje tylko stanów dla "ota", "ta", "a" i stanu końcowego.
+        let fsa = FsaBuilder::build([&b"kota"[..], b"lota", b"mota"]).unwrap();
+        assert_eq!(count_states(&fsa), 5);
+        assert_eq!(sequences(&fsa).len(), 3);
+    }
+
+    #[test]
+    fn test_unsorted_input_is_rejected() {
+        let mut builder = FsaBuilder::new();
+        builder.add(b"kot").unwrap();
+        assert_matches!(
+            builder.add(b"ala"),
+            Err(BuilderError::UnsortedInput { ref previous, ref current })
+                if previous == b"kot" && current == b"ala"
+        );
+    }
+
+    #[test]
+    fn test_duplicates_are_ignored() {
+        let fsa = FsaBuilder::build([&b"ala"[..], b"ala", b"kot"]).unwrap();
+        assert_eq!(sequences(&fsa), vec![b"ala".to_vec(), b"kot".to_vec()]);
+    }
+
+    #[test]
+    fn test_empty_sequence_is_rejected() {
+        let mut builder = FsaBuilder::new();
+        assert_matches!(builder.add(b""), Err(BuilderError::EmptySequence));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let fsa = FsaBuilder::new().complete();
+        assert!(sequences(&fsa).is_empty());
+        let result = FsaTraversal::new(&fsa).perfect_match(b"a").unwrap();
+        assert_eq!(result.kind, MatchKind::NoMatch);
+    }
+
+    #[test]
+    fn test_perfect_match() {
+        let fsa = FsaBuilder::build([&b"dom"[..], b"domek", b"kot"]).unwrap();
+        let traversal = FsaTraversal::new(&fsa);
+        assert_eq!(traversal.perfect_match(b"dom").unwrap().kind, MatchKind::ExactMatch);
+        assert_eq!(traversal.perfect_match(b"domek").unwrap().kind, MatchKind::ExactMatch);
+        assert_eq!(traversal.perfect_match(b"do").unwrap().kind, MatchKind::SequenceIsAPrefix);
+        assert_eq!(traversal.perfect_match(b"domki").unwrap().kind, MatchKind::NoMatch);
+        assert_eq!(traversal.perfect_match(b"kotek").unwrap().kind, MatchKind::NoMatch);
+    }
+
+    #[test]
+    fn test_many_sequences_grow_register() {
+        let mut input: Vec<Vec<u8>> = (0..5000u32).map(|i| format!("{:05}", i * 7).into_bytes()).collect();
+        input.sort();
+        let fsa = FsaBuilder::build(input.iter()).unwrap();
+        assert_eq!(sequences(&fsa), input);
+    }
+}
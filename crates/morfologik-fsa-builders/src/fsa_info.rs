@@ -1,4 +1,114 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Struktura FSAInfo (z budowania)
 
-// Struktura FSAInfo (z budowania)
\ No newline at end of file
+use crate::error::Result;
+use crate::fsa_utils::{collect_states, right_language_counts};
+use morfologik_fsa::fsa_header::FsaFlags;
+use morfologik_fsa::fsa_trait::Fsa;
+
+/// Statystyki struktury automatu (odpowiednik `FSAInfo.java`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsaInfo {
+    /// Liczba stanów osiągalnych z korzenia, które mają łuki wychodzące.
+    pub states: usize,
+    /// Liczba łuków tych stanów.
+    pub arcs: usize,
+    /// Liczba łuków końcowych.
+    pub final_arcs: usize,
+    /// Liczba akceptowanych sekwencji.
+    pub sequences: u64,
+    /// Długość najdłuższej akceptowanej sekwencji.
+    pub max_depth: usize,
+    /// Liczba łuków z każdą etykietą (indeksowana etykietą).
+    pub label_histogram: Vec<u64>,
+    /// Czy automat zapisuje liczby sekwencji w węzłach (flaga NUMBERS).
+    pub has_numbers: bool,
+}
+
+impl FsaInfo {
+    /// Zbiera statystyki, przechodząc wszystkie stany osiągalne z korzenia.
+    ///
+    /// Zwraca błąd, jeśli automat zawiera cykl.
+    pub fn new<F: Fsa + ?Sized>(fsa: &F) -> Result<Self> {
+        let states = collect_states(fsa)?;
+        let counts = right_language_counts(&states)?;
+
+        let mut label_histogram = vec![0u64; 256];
+        let mut final_arcs = 0;
+        // Stany są w porządku topologicznym, więc cele łuków mają już policzoną głębokość.
+        let mut depths = vec![0usize; states.len()];
+        for (state, arcs) in states.iter().enumerate().rev() {
+            for arc in arcs {
+                label_histogram[arc.label as usize] += 1;
+                if arc.is_final {
+                    final_arcs += 1;
+                }
+                let depth = 1 + arc.target.map_or(0, |t| depths[t]);
+                depths[state] = depths[state].max(depth);
+            }
+        }
+
+        Ok(FsaInfo {
+            states: states.iter().filter(|arcs| !arcs.is_empty()).count(),
+            arcs: states.iter().map(Vec::len).sum(),
+            final_arcs,
+            sequences: counts.first().copied().unwrap_or(0),
+            max_depth: depths.first().copied().unwrap_or(0),
+            label_histogram,
+            has_numbers: fsa.get_flags().contains(FsaFlags::NUMBERS),
+        })
+    }
+
+    /// Średnia liczba bajtów na łuk dla automatu zajmującego `size` bajtów.
+    pub fn bytes_per_arc(&self, size: u64) -> f64 {
+        if self.arcs == 0 {
+            0.0
+        } else {
+            size as f64 / self.arcs as f64
+        }
+    }
+
+    /// Etykiety występujące w automacie wraz z liczbą łuków, od najczęstszej.
+    pub fn labels_by_frequency(&self) -> Vec<(u8, u64)> {
+        let mut labels: Vec<(u8, u64)> = self
+            .label_histogram
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(label, &count)| (label as u8, count))
+            .collect();
+        labels.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa_builder::FsaBuilder;
+
+    #[test]
+    fn test_fsa_info() {
+        let fsa = FsaBuilder::build([&b"a"[..], b"ab", b"abc", b"b", b"bc"]).unwrap();
+        let info = FsaInfo::new(&fsa).unwrap();
+        // Korzeń {a, b}, stan po "a" {b}, stan po "ab"/"b" {c}.
+        assert_eq!(info.states, 3);
+        assert_eq!(info.arcs, 4);
+        assert_eq!(info.final_arcs, 4);
+        assert_eq!(info.sequences, 5);
+        assert_eq!(info.max_depth, 3);
+        assert!(!info.has_numbers);
+        assert_eq!(info.labels_by_frequency(), vec![(b'b', 2), (b'a', 1), (b'c', 1)]);
+        assert_eq!(info.bytes_per_arc(10), 2.5);
+    }
+
+    #[test]
+    fn test_fsa_info_empty() {
+        let fsa = FsaBuilder::new().complete();
+        let info = FsaInfo::new(&fsa).unwrap();
+        assert_eq!(info.states, 0);
+        assert_eq!(info.arcs, 0);
+        assert_eq!(info.sequences, 0);
+        assert_eq!(info.max_depth, 0);
+        assert_eq!(info.bytes_per_arc(10), 0.0);
+    }
+}
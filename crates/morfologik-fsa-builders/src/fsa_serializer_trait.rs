@@ -1,4 +1,19 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Trait FSASerializer
 
-// Trait FSASerializer
\ No newline at end of file
+use std::io::Write;
+
+use crate::error::Result;
+use morfologik_fsa::fsa_header::FsaFlags;
+use morfologik_fsa::fsa_trait::Fsa;
+
+/// Serializator automatu do formatu binarnego (odpowiednik `FSASerializer.java`).
+///
+/// Trait jest obiektowy (`dyn FsaSerializer`), aby narzędzia mogły wybierać format
+/// wyjściowy w czasie działania.
+pub trait FsaSerializer {
+    /// Zapisuje automat do podanego strumienia.
+    fn serialize(&self, fsa: &dyn Fsa, writer: &mut dyn Write) -> Result<()>;
+
+    /// Flagi, które zostaną zapisane w nagłówku automatu wynikowego.
+    fn get_flags(&self) -> FsaFlags;
+}
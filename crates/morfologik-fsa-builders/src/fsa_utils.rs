@@ -1,4 +1,189 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Funkcje pomocnicze (np. z FSAUtils.java)
 
-// Funkcje pomocnicze (np. z FSAUtils.java)
\ No newline at end of file
+use std::collections::HashMap;
+
+use crate::error::Result;
+use morfologik_fsa::error::FsaError;
+use morfologik_fsa::fsa_trait::{Fsa, Node};
+
+/// Łuk stanu wczytanego z automatu źródłowego.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateArc {
+    pub label: u8,
+    pub is_final: bool,
+    /// Indeks stanu docelowego w wyniku [`collect_states`] lub `None`, jeśli stan
+    /// docelowy nie ma łuków wychodzących.
+    pub target: Option<usize>,
+}
+
+/// Wczytuje wszystkie stany osiągalne z korzenia automatu.
+///
+/// Stany są zwracane w porządku topologicznym (odwrotny porządek post-order
+/// przejścia w głąb), więc stan o indeksie 0 to korzeń, a w automacie acyklicznym
+/// każdy łuk prowadzi do stanu o większym indeksie. Cel ostatniego łuku stanu jest
+/// odwiedzany jako ostatni, więc — jeśli nie został umieszczony wcześniej — trafia
+/// bezpośrednio za stan źródłowy. Łuki każdego stanu są posortowane rosnąco według etykiet.
+pub fn collect_states<F: Fsa + ?Sized>(fsa: &F) -> Result<Vec<Vec<StateArc>>> {
+    Ok(collect_states_with_nodes(fsa)?.0)
+}
+
+/// Jak [`collect_states`], ale zwraca też węzeł automatu źródłowego dla każdego stanu.
+pub fn collect_states_with_nodes<F: Fsa + ?Sized>(fsa: &F) -> Result<(Vec<Vec<StateArc>>, Vec<Node>)> {
+    // Wczytanie łuków wszystkich osiągalnych stanów (indeksy w kolejności odkrycia).
+    let mut index: HashMap<Node, usize> = HashMap::new();
+    let mut discovered = vec![fsa.get_root_node()];
+    let mut raw: Vec<Vec<(u8, bool, Option<usize>)>> = Vec::new();
+    index.insert(discovered[0], 0);
+
+    while raw.len() < discovered.len() {
+        let node = discovered[raw.len()];
+        let mut arcs = Vec::new();
+        let mut next = fsa.first_arc_opt(node)?;
+        while let Some(arc) = next {
+            let target = fsa.get_end_node(arc)?;
+            let target = fsa.first_arc_opt(target)?.map(|_| {
+                *index.entry(target).or_insert_with(|| {
+                    discovered.push(target);
+                    discovered.len() - 1
+                })
+            });
+            arcs.push((fsa.get_arc_label(arc)?, fsa.is_arc_final(arc)?, target));
+            next = if fsa.is_arc_terminal(arc)? { None } else { Some(fsa.get_next_arc(arc)?) };
+        }
+        arcs.sort_by_key(|&(label, _, _)| label);
+        raw.push(arcs);
+    }
+
+    // Porządek post-order; łuki odwiedzane według etykiet.
+    let mut visited = vec![false; raw.len()];
+    let mut post_order = Vec::with_capacity(raw.len());
+    let mut stack = vec![(0usize, 0usize)];
+    visited[0] = true;
+    while let Some(top) = stack.last_mut() {
+        let (state, next_arc) = *top;
+        if next_arc < raw[state].len() {
+            top.1 += 1;
+            if let Some(target) = raw[state][next_arc].2 {
+                if !visited[target] {
+                    visited[target] = true;
+                    stack.push((target, 0));
+                }
+            }
+        } else {
+            post_order.push(state);
+            stack.pop();
+        }
+    }
+    post_order.reverse();
+
+    let mut position = vec![0usize; raw.len()];
+    for (i, &state) in post_order.iter().enumerate() {
+        position[state] = i;
+    }
+
+    let nodes = post_order.iter().map(|&state| discovered[state]).collect();
+    let states = post_order
+        .into_iter()
+        .map(|state| {
+            std::mem::take(&mut raw[state])
+                .into_iter()
+                .map(|(label, is_final, target)| StateArc {
+                    label,
+                    is_final,
+                    target: target.map(|t| position[t]),
+                })
+                .collect()
+        })
+        .collect();
+    Ok((states, nodes))
+}
+
+/// Oblicza liczbę sekwencji akceptowanych z każdego stanu (rozmiar prawego języka).
+///
+/// Odpowiada `FSAUtils.rightLanguageForAllStates`. Zwraca błąd, jeśli automat zawiera cykl.
+pub fn right_language_counts(states: &[Vec<StateArc>]) -> Result<Vec<u64>> {
+    const UNVISITED: u64 = u64::MAX;
+    const IN_PROGRESS: u64 = u64::MAX - 1;
+
+    let mut counts = vec![UNVISITED; states.len()];
+    for start in 0..states.len() {
+        if counts[start] != UNVISITED {
+            continue;
+        }
+        let mut stack = vec![(start, false)];
+        while let Some((state, expanded)) = stack.pop() {
+            if expanded {
+                let mut count = 0u64;
+                for arc in &states[state] {
+                    if arc.is_final {
+                        count += 1;
+                    }
+                    if let Some(target) = arc.target {
+                        count += counts[target];
+                    }
+                }
+                counts[state] = count;
+                continue;
+            }
+            if counts[state] != UNVISITED {
+                continue;
+            }
+            counts[state] = IN_PROGRESS;
+            stack.push((state, true));
+            for target in states[state].iter().filter_map(|arc| arc.target) {
+                match counts[target] {
+                    IN_PROGRESS => {
+                        return Err(FsaError::CorruptedFsa(format!(
+                            "Automaton contains a cycle through state {}",
+                            target
+                        ))
+                        .into())
+                    }
+                    UNVISITED => stack.push((target, false)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa_builder::FsaBuilder;
+
+    #[test]
+    fn test_collect_states_and_counts() {
+        let fsa = FsaBuilder::build([&b"a"[..], b"ab", b"b"]).unwrap();
+        let states = collect_states(&fsa).unwrap();
+        // Korzeń z łukami 'a' i 'b' oraz stan po "a" z łukiem 'b'.
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].iter().map(|a| a.label).collect::<Vec<_>>(), vec![b'a', b'b']);
+        assert_eq!(states[0][1].target, None);
+
+        let counts = right_language_counts(&states).unwrap();
+        assert_eq!(counts, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_collect_states_topological_order() {
+        let fsa = FsaBuilder::build([&b"abc"[..], b"bc", b"xbc"]).unwrap();
+        let states = collect_states(&fsa).unwrap();
+        for (i, arcs) in states.iter().enumerate() {
+            for arc in arcs {
+                if let Some(target) = arc.target {
+                    assert!(target > i, "arc from {} goes back to {}", i, target);
+                }
+            }
+        }
+        // Cel ostatniego łuku korzenia ('x') leży bezpośrednio za korzeniem.
+        assert_eq!(states[0].last().unwrap().target, Some(1));
+    }
+
+    #[test]
+    fn test_right_language_counts_detects_cycle() {
+        let states = vec![vec![StateArc { label: b'a', is_final: true, target: Some(0) }]];
+        assert!(right_language_counts(&states).is_err());
+    }
+}
@@ -1,8 +1,10 @@
 // Główny plik biblioteki morfologik-fsa-builders
 pub mod error;
+pub mod constant_arc_size_fsa;
 pub mod fsa_builder;
 pub mod fsa_serializer_trait;
 pub mod fsa5_serializer;
 pub mod cfsa2_serializer;
 pub mod fsa_info;
 pub mod fsa_utils;
+pub mod set_operations;
@@ -0,0 +1,221 @@
+// Operacje mnogościowe na językach automatów
+
+use crate::constant_arc_size_fsa::ConstantArcSizeFsa;
+use crate::error::Result;
+use crate::fsa_builder::FsaBuilder;
+use morfologik_fsa::fsa_trait::{Fsa, Node};
+
+/// Operacja na językach (zbiorach sekwencji) dwóch automatów.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    /// Sekwencje należące do któregokolwiek automatu.
+    Union,
+    /// Sekwencje należące do obu automatów.
+    Intersection,
+    /// Sekwencje pierwszego automatu, których nie ma w drugim.
+    Difference,
+}
+
+impl SetOperation {
+    fn accepts(self, first: bool, second: bool) -> bool {
+        match self {
+            SetOperation::Union => first || second,
+            SetOperation::Intersection => first && second,
+            SetOperation::Difference => first && !second,
+        }
+    }
+
+    /// Czy pod parą stanów (brak stanu oznacza pusty język) może leżeć jakaś sekwencja wyniku.
+    fn may_accept(self, first: Option<Node>, second: Option<Node>) -> bool {
+        match self {
+            SetOperation::Union => first.is_some() || second.is_some(),
+            SetOperation::Intersection => first.is_some() && second.is_some(),
+            SetOperation::Difference => first.is_some(),
+        }
+    }
+}
+
+/// Buduje minimalny automat dla sumy języków.
+pub fn union<A: Fsa + ?Sized, B: Fsa + ?Sized>(first: &A, second: &B) -> Result<ConstantArcSizeFsa> {
+    apply(SetOperation::Union, first, second)
+}
+
+/// Buduje minimalny automat dla przecięcia języków.
+pub fn intersection<A: Fsa + ?Sized, B: Fsa + ?Sized>(first: &A, second: &B) -> Result<ConstantArcSizeFsa> {
+    apply(SetOperation::Intersection, first, second)
+}
+
+/// Buduje minimalny automat dla różnicy języków (`first` bez `second`).
+pub fn difference<A: Fsa + ?Sized, B: Fsa + ?Sized>(first: &A, second: &B) -> Result<ConstantArcSizeFsa> {
+    apply(SetOperation::Difference, first, second)
+}
+
+/// Buduje minimalny automat dla wyniku operacji.
+///
+/// Oba automaty przechodzone są jednocześnie w głąb, po łukach w kolejności etykiet,
+/// więc sekwencje wyniku powstają posortowane i trafiają wprost do [`FsaBuilder`] —
+/// w pamięci jest tylko bieżąca ścieżka i budowany automat. Gałęzie, które nie mogą
+/// dać sekwencji wyniku (np. obecne tylko w jednym automacie przy przecięciu), są pomijane.
+/// Przejście nie używa rekurencji, więc długie sekwencje nie przepełnią stosu.
+pub fn apply<A: Fsa + ?Sized, B: Fsa + ?Sized>(operation: SetOperation, first: &A, second: &B) -> Result<ConstantArcSizeFsa> {
+    let mut builder = FsaBuilder::new();
+    let mut path = Vec::new();
+    let mut stack = vec![Frame::new(first, second, Some(first.get_root_node()), Some(second.get_root_node()))?];
+    while let Some(frame) = stack.last_mut() {
+        let Some((label, first_arc, second_arc)) = frame.next_pair() else {
+            // Węzły ramki przejrzane: zdejmujemy etykietę łuku, który do nich prowadził
+            // (dla korzenia ścieżka jest już pusta).
+            stack.pop();
+            path.pop();
+            continue;
+        };
+        let first_target = first_arc.map(|arc| arc.2);
+        let second_target = second_arc.map(|arc| arc.2);
+        if !operation.may_accept(first_target, second_target) {
+            continue;
+        }
+        path.push(label);
+        let is_final = |arc: Option<ArcInfo>| arc.is_some_and(|arc| arc.1);
+        if operation.accepts(is_final(first_arc), is_final(second_arc)) {
+            builder.add(&path)?;
+        }
+        stack.push(Frame::new(first, second, first_target, second_target)?);
+    }
+    Ok(builder.complete())
+}
+
+/// Łuk: etykieta, czy końcowy, stan docelowy.
+type ArcInfo = (u8, bool, Node);
+
+/// Para węzłów na bieżącej ścieżce: ich łuki i pozycje następnych łuków do scalenia.
+struct Frame {
+    first_arcs: Vec<ArcInfo>,
+    second_arcs: Vec<ArcInfo>,
+    i: usize,
+    j: usize,
+}
+
+impl Frame {
+    fn new<A: Fsa + ?Sized, B: Fsa + ?Sized>(first: &A, second: &B, first_node: Option<Node>, second_node: Option<Node>) -> Result<Self> {
+        Ok(Frame { first_arcs: read_arcs(first, first_node)?, second_arcs: read_arcs(second, second_node)?, i: 0, j: 0 })
+    }
+
+    /// Następna etykieta w scaleniu łuków obu węzłów i łuki z nią (brak, jeśli węzeł jej nie ma).
+    fn next_pair(&mut self) -> Option<(u8, Option<ArcInfo>, Option<ArcInfo>)> {
+        let first_arc = self.first_arcs.get(self.i).copied();
+        let second_arc = self.second_arcs.get(self.j).copied();
+        match (first_arc, second_arc) {
+            (Some(a), Some(b)) if a.0 == b.0 => {
+                self.i += 1;
+                self.j += 1;
+                Some((a.0, first_arc, second_arc))
+            }
+            (Some(a), Some(b)) if a.0 < b.0 => {
+                self.i += 1;
+                Some((a.0, first_arc, None))
+            }
+            (Some(a), None) => {
+                self.i += 1;
+                Some((a.0, first_arc, None))
+            }
+            (_, Some(b)) => {
+                self.j += 1;
+                Some((b.0, None, second_arc))
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+/// Łuki węzła posortowane według etykiet (pusta lista dla braku węzła lub węzła bez łuków).
+fn read_arcs<F: Fsa + ?Sized>(fsa: &F, node: Option<Node>) -> Result<Vec<ArcInfo>> {
+    let Some(node) = node else {
+        return Ok(Vec::new());
+    };
+    let mut arcs = Vec::new();
+    let Some(mut arc) = fsa.first_arc_opt(node)? else {
+        return Ok(arcs);
+    };
+    loop {
+        arcs.push((fsa.get_arc_label(arc)?, fsa.is_arc_final(arc)?, fsa.get_end_node(arc)?));
+        if fsa.is_arc_terminal(arc)? {
+            break;
+        }
+        arc = fsa.get_next_arc(arc)?;
+    }
+    arcs.sort_by_key(|arc| arc.0);
+    Ok(arcs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa5_serializer::Fsa5Serializer;
+    use crate::fsa_serializer_trait::FsaSerializer;
+    use morfologik_fsa::fsa5::FSA5;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+
+    fn sequences<F: Fsa + ?Sized>(fsa: &F) -> Vec<String> {
+        ByteSequenceIterator::new(fsa).map(|s| String::from_utf8(s.unwrap()).unwrap()).collect()
+    }
+
+    fn fsa(words: &[&str]) -> ConstantArcSizeFsa {
+        FsaBuilder::build(words.iter()).unwrap()
+    }
+
+    #[test]
+    fn test_operations() {
+        let lexicon = fsa(&["ala", "kot", "kota", "pies"]);
+        let other = fsa(&["kot", "kotek", "mysz", "pies"]);
+
+        assert_eq!(
+            sequences(&union(&lexicon, &other).unwrap()),
+            ["ala", "kot", "kota", "kotek", "mysz", "pies"]
+        );
+        assert_eq!(sequences(&intersection(&lexicon, &other).unwrap()), ["kot", "pies"]);
+        assert_eq!(sequences(&difference(&lexicon, &other).unwrap()), ["ala", "kota"]);
+        assert_eq!(sequences(&difference(&other, &lexicon).unwrap()), ["kotek", "mysz"]);
+    }
+
+    #[test]
+    fn test_empty_operands() {
+        let lexicon = fsa(&["ala", "kot"]);
+        let empty = FsaBuilder::new().complete();
+        assert_eq!(sequences(&union(&empty, &lexicon).unwrap()), ["ala", "kot"]);
+        assert!(sequences(&intersection(&lexicon, &empty).unwrap()).is_empty());
+        assert!(sequences(&difference(&lexicon, &lexicon).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_long_sequence_does_not_overflow_stack() {
+        let long = "a".repeat(200_000);
+        let lexicon = fsa(&[long.as_str()]);
+        let other = fsa(&["b"]);
+        let merged = union(&lexicon, &other).unwrap();
+        let all: Vec<Vec<u8>> = ByteSequenceIterator::new(&merged).map(|s| s.unwrap()).collect();
+        assert_eq!(all, [long.into_bytes(), b"b".to_vec()]);
+        assert!(sequences(&intersection(&lexicon, &other).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_result_is_minimal_across_formats() {
+        let words: Vec<String> = (0..500).map(|i| format!("słowo{}", i)).collect();
+        let (even, odd): (Vec<&String>, Vec<&String>) = words.iter().partition(|w| w.len() % 2 == 0);
+        let mut even: Vec<&String> = even;
+        even.sort();
+        let mut odd: Vec<&String> = odd;
+        odd.sort();
+
+        // Operand zapisany i odczytany jako FSA5, drugi prosto z budowania.
+        let mut bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&FsaBuilder::build(even.iter()).unwrap(), &mut bytes).unwrap();
+        let first = FSA5::from_bytes(bytes).unwrap();
+        let second = FsaBuilder::build(odd.iter()).unwrap();
+
+        let merged = union(&first, &second).unwrap();
+        let mut all: Vec<&String> = words.iter().collect();
+        all.sort();
+        let direct = FsaBuilder::build(all.iter()).unwrap();
+        assert_eq!(merged.data(), direct.data());
+    }
+}
@@ -0,0 +1,174 @@
+// Automat jednego z wbudowanych formatów z wywołaniami bez tablicy metod wirtualnych
+
+use crate::cfsa2::CFSA2;
+use crate::error::Result;
+use crate::fsa5::FSA5;
+use crate::fsa_header::FsaFlags;
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+use crate::loader::{BoxedFsa, FsaBytes};
+use crate::validated::ValidatedFsa;
+
+/// Automat FSA5, CFSA2 albo zdekodowany w pamięci, wybierany w czasie działania.
+///
+/// Metody [`Fsa`] rozgałęziają się po wariancie i wywołują implementację wprost, więc
+/// kompilator może ją wstawić w pętlę przejścia po słowie; `Box<dyn Fsa>` wymaga przy
+/// każdym łuku dereferencji i wywołania wirtualnego. Automaty innych formatów (np.
+/// dodanych przez [`FsaRegistry::register`](crate::loader::FsaRegistry::register))
+/// trafiają do wariantu [`AnyFsa::Other`] i działają jak dotąd, przez trait.
+#[derive(Debug)]
+pub enum AnyFsa {
+    Fsa5(FSA5<FsaBytes>),
+    Cfsa2(CFSA2<FsaBytes>),
+    /// Automat zdekodowany do pamięci, np. zbudowany przez `FsaBuilder` i przepisany
+    /// przez [`ValidatedFsa::new`].
+    Validated(ValidatedFsa),
+    Other(BoxedFsa),
+}
+
+/// Wywołuje metodę na automacie z aktywnego wariantu.
+macro_rules! dispatch {
+    ($any:expr, $fsa:ident => $call:expr) => {
+        match $any {
+            AnyFsa::Fsa5($fsa) => $call,
+            AnyFsa::Cfsa2($fsa) => $call,
+            AnyFsa::Validated($fsa) => $call,
+            AnyFsa::Other($fsa) => $call,
+        }
+    };
+}
+
+impl AnyFsa {
+    /// Automat jako obiekt traitu (dla wariantu [`AnyFsa::Other`] bez dodatkowego pudełka).
+    pub fn into_boxed(self) -> BoxedFsa {
+        match self {
+            AnyFsa::Other(fsa) => fsa,
+            fsa => Box::new(fsa),
+        }
+    }
+}
+
+impl From<FSA5<FsaBytes>> for AnyFsa {
+    fn from(fsa: FSA5<FsaBytes>) -> Self {
+        AnyFsa::Fsa5(fsa)
+    }
+}
+
+impl From<CFSA2<FsaBytes>> for AnyFsa {
+    fn from(fsa: CFSA2<FsaBytes>) -> Self {
+        AnyFsa::Cfsa2(fsa)
+    }
+}
+
+impl From<ValidatedFsa> for AnyFsa {
+    fn from(fsa: ValidatedFsa) -> Self {
+        AnyFsa::Validated(fsa)
+    }
+}
+
+impl From<BoxedFsa> for AnyFsa {
+    fn from(fsa: BoxedFsa) -> Self {
+        AnyFsa::Other(fsa)
+    }
+}
+
+impl Fsa for AnyFsa {
+    #[inline]
+    fn get_flags(&self) -> FsaFlags {
+        dispatch!(self, fsa => fsa.get_flags())
+    }
+
+    #[inline]
+    fn get_root_node(&self) -> Node {
+        dispatch!(self, fsa => fsa.get_root_node())
+    }
+
+    #[inline]
+    fn get_first_arc(&self, node: Node) -> Result<ArcOffset> {
+        dispatch!(self, fsa => fsa.get_first_arc(node))
+    }
+
+    #[inline]
+    fn first_arc_opt(&self, node: Node) -> Result<Option<ArcOffset>> {
+        dispatch!(self, fsa => fsa.first_arc_opt(node))
+    }
+
+    #[inline]
+    fn get_next_arc(&self, arc: ArcOffset) -> Result<ArcOffset> {
+        dispatch!(self, fsa => fsa.get_next_arc(arc))
+    }
+
+    #[inline]
+    fn get_arc(&self, node: Node, label: u8) -> Result<ArcOffset> {
+        dispatch!(self, fsa => fsa.get_arc(node, label))
+    }
+
+    #[inline]
+    fn get_end_node(&self, arc: ArcOffset) -> Result<Node> {
+        dispatch!(self, fsa => fsa.get_end_node(arc))
+    }
+
+    #[inline]
+    fn get_arc_label(&self, arc: ArcOffset) -> Result<u8> {
+        dispatch!(self, fsa => fsa.get_arc_label(arc))
+    }
+
+    #[inline]
+    fn is_arc_final(&self, arc: ArcOffset) -> Result<bool> {
+        dispatch!(self, fsa => fsa.is_arc_final(arc))
+    }
+
+    #[inline]
+    fn is_arc_terminal(&self, arc: ArcOffset) -> Result<bool> {
+        dispatch!(self, fsa => fsa.is_arc_terminal(arc))
+    }
+
+    fn get_right_language_count(&self, node: Node) -> Result<usize> {
+        dispatch!(self, fsa => fsa.get_right_language_count(node))
+    }
+
+    fn get_data_size(&self) -> Option<usize> {
+        dispatch!(self, fsa => fsa.get_data_size())
+    }
+
+    fn get_reserved_size(&self) -> usize {
+        dispatch!(self, fsa => fsa.get_reserved_size())
+    }
+
+    fn get_arc_size(&self, arc: ArcOffset) -> Result<usize> {
+        dispatch!(self, fsa => fsa.get_arc_size(arc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iterator::ByteSequenceIterator;
+    use crate::loader::FsaRegistry;
+    use crate::test_utils::trie_bytes;
+    use crate::validation::validate;
+
+    fn sequences(fsa: &dyn Fsa) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_variants_behave_like_wrapped_automaton() {
+        let bytes = trie_bytes(&[b"ala", b"as", b"kot"]);
+        let fsa5 = FSA5::from_bytes(FsaBytes::new(bytes.clone())).unwrap();
+        let expected = sequences(&fsa5);
+        let validated = ValidatedFsa::new(&fsa5).unwrap();
+
+        let variants = [
+            AnyFsa::from(fsa5),
+            AnyFsa::from(validated),
+            AnyFsa::from(FsaRegistry::new().from_bytes(bytes.clone()).unwrap()),
+        ];
+        for fsa in &variants {
+            assert_eq!(sequences(fsa), expected);
+            assert!(validate(fsa).is_valid());
+            let arc = fsa.get_arc(fsa.get_root_node(), b'k').unwrap();
+            assert_eq!(fsa.get_arc_label(arc).unwrap(), b'k');
+        }
+        assert!(matches!(FsaRegistry::new().load(bytes).unwrap(), AnyFsa::Fsa5(_)));
+    }
+}
@@ -0,0 +1,402 @@
+// Odczyt automatów w starszym formacie CFSA (wersja 0xC5)
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use crate::error::{FsaError, Result};
+use crate::fsa_header::{FsaFlags, FsaHeader, VERSION_CFSA};
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+use crate::jump_tables::{JumpTableOptions, JumpTables};
+
+pub const CFSA_ARC_IS_FINAL_BIT: u8 = 1 << 0;
+pub const CFSA_ARC_IS_LAST_BIT: u8 = 1 << 1;
+pub const CFSA_ARC_TARGET_IS_NEXT_BIT: u8 = 1 << 2;
+
+/// Rozmiar tablicy etykiet zapisanej w nagłówku (indeks 0 jest nieużywany).
+pub const CFSA_LABEL_MAPPING_SIZE: usize = 1 << 5;
+
+const CFSA_LABEL_MASK: u8 = 0xf8;
+const CFSA_LABEL_SHIFT: u8 = 3;
+
+/// Automat w formacie CFSA (wersja 0xC5, `CFSA.java`), poprzedniku
+/// [`crate::cfsa2::CFSA2`]; obsługiwany tylko odczyt słowników ze starszych wydań.
+///
+/// ```text
+/// ---- FSA_MAGIC, VERSION_CFSA ----
+/// byte             filler            // bajt wypełnienia
+/// byte             annotation        // separator adnotacji
+/// byte             hgtl              // (node_data_length << 4) | gtl
+/// byte[32]         label_mapping     // etykiety dla indeksów 1..32 (wpis 0 nieużywany)
+/// byte[]           arcs
+/// ```
+///
+/// Pierwszy bajt łuku zawiera flagi w 3 najmłodszych bitach. Łuk z
+/// `CFSA_ARC_TARGET_IS_NEXT_BIT` nie zapisuje adresu (węzeł docelowy zaczyna się zaraz za
+/// nim): jeśli starsze 5 bitów jest niezerowe, to indeks etykiety w tablicy i łuk zajmuje
+/// 1 bajt, inaczej etykieta jest w drugim bajcie. Pozostałe łuki zajmują `1 + gtl` bajtów:
+/// `[adres0|flagi][etykieta][adres1..]`, gdzie adres (little-endian, bez bajtu etykiety)
+/// to `(target << 3) | flagi`.
+///
+/// Jak w FSA5 pod adresem 0 leży pusty węzeł stanu końcowego, a za nim węzeł epsilon
+/// z łukiem do korzenia; przy niezerowym `node_data_length` (flaga NUMBERS) każdy węzeł
+/// zaczyna się od liczby sekwencji.
+#[derive(Debug)]
+pub struct CFSA<S = Vec<u8>> {
+    pub version: u8,
+    pub flags: FsaFlags,
+    /// Bajt wypełnienia z nagłówka.
+    pub filler: u8,
+    /// Separator adnotacji z nagłówka.
+    pub annotation: u8,
+    /// Rozmiar pola adresu łuku w bajtach (razem z bajtem flag, bez etykiety).
+    pub goto_length: u8,
+    /// Rozmiar liczby sekwencji przed łukami węzła w bajtach (0 bez flagi NUMBERS).
+    pub node_data_length: u8,
+    /// Etykiety dla indeksów z bajtu łuku (`CFSA_LABEL_MAPPING_SIZE` wpisów).
+    pub label_mapping: Vec<u8>,
+    /// Adres (offset) korzenia automatu w danych łuków.
+    pub root_node_offset: usize,
+    /// Bajty automatu; dane łuków zaczynają się od `arcs_start`.
+    data: S,
+    arcs_start: usize,
+    /// Indeksy łuków węzłów o dużej liczbie łuków (patrz [`CFSA::with_jump_tables`]).
+    jump_tables: Option<JumpTables>,
+}
+
+fn map_io_error(e: std::io::Error) -> FsaError {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        FsaError::UnexpectedEof
+    } else {
+        FsaError::Io(e)
+    }
+}
+
+impl CFSA {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path).map_err(FsaError::Io)?;
+        let mut reader = BufReader::new(file);
+        Self::from_reader(&mut reader)
+    }
+
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(map_io_error)?;
+        Self::from_bytes(data)
+    }
+}
+
+impl<S: AsRef<[u8]>> CFSA<S> {
+    /// Tworzy automat nad bajtami całego pliku CFSA (z nagłówkiem) bez kopiowania łuków.
+    pub fn from_bytes(data: S) -> Result<Self> {
+        let mut reader = Cursor::new(data.as_ref());
+        let header = FsaHeader::read(&mut reader)?;
+        if header.version != VERSION_CFSA {
+            return Err(FsaError::UnsupportedVersion(header.version));
+        }
+
+        let mut fields = [0u8; 3];
+        reader.read_exact(&mut fields).map_err(map_io_error)?;
+        let [filler, annotation, hgtl] = fields;
+        let node_data_length = (hgtl >> 4) & 0x0f;
+        let goto_length = hgtl & 0x0f;
+        if goto_length == 0 || goto_length as usize > std::mem::size_of::<usize>() {
+            return Err(FsaError::CorruptedFsa(format!("Invalid CFSA address size: {}", goto_length)));
+        }
+        let mut label_mapping = vec![0u8; CFSA_LABEL_MAPPING_SIZE];
+        reader.read_exact(&mut label_mapping).map_err(map_io_error)?;
+
+        let mut flags = FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED;
+        if node_data_length != 0 {
+            flags |= FsaFlags::NUMBERS;
+        }
+
+        let arcs_start = reader.position() as usize;
+        let mut fsa = CFSA {
+            version: header.version,
+            flags,
+            filler,
+            annotation,
+            goto_length,
+            node_data_length,
+            label_mapping,
+            root_node_offset: 0,
+            data,
+            arcs_start,
+            jump_tables: None,
+        };
+        // Pomija pusty węzeł stanu końcowego i idzie łukiem węzła epsilon.
+        let epsilon = fsa.skip_arc(fsa.node_first_arc(0))?;
+        fsa.root_node_offset = fsa.destination(fsa.node_first_arc(epsilon))?;
+        Ok(fsa)
+    }
+
+    /// Surowe dane łuków automatu (bez nagłówka i tablicy etykiet).
+    #[inline(always)]
+    pub fn arcs_data(&self) -> &[u8] {
+        &self.data.as_ref()[self.arcs_start..]
+    }
+
+    /// Pierwszy łuk węzła (za liczbą sekwencji), bez sprawdzania zakresu.
+    #[inline(always)]
+    fn node_first_arc(&self, node: Node) -> ArcOffset {
+        node.saturating_add(self.node_data_length as usize)
+    }
+
+    /// Pierwszy bajt łuku (flagi w młodszych bitach).
+    #[inline(always)]
+    fn arc_byte(&self, arc_offset: ArcOffset) -> Result<u8> {
+        self.arcs_data().get(arc_offset).copied().ok_or(FsaError::InvalidArcOffset(arc_offset))
+    }
+
+    /// Offset za łukiem; sprawdza, czy cały łuk mieści się w danych.
+    #[inline]
+    fn skip_arc(&self, arc_offset: ArcOffset) -> Result<usize> {
+        let byte = self.arc_byte(arc_offset)?;
+        let size = if byte & CFSA_ARC_TARGET_IS_NEXT_BIT == 0 {
+            1 + self.goto_length as usize
+        } else if byte & CFSA_LABEL_MASK != 0 {
+            1
+        } else {
+            2
+        };
+        let end = arc_offset + size;
+        if end > self.arcs_data().len() {
+            return Err(FsaError::InvalidArcOffset(arc_offset));
+        }
+        Ok(end)
+    }
+
+    /// Adres węzła docelowego łuku (0 dla łuku bez dalszych przejść).
+    #[inline]
+    fn destination(&self, arc_offset: ArcOffset) -> Result<Node> {
+        let end = self.skip_arc(arc_offset)?;
+        let byte = self.arc_byte(arc_offset)?;
+        if byte & CFSA_ARC_TARGET_IS_NEXT_BIT != 0 {
+            return Ok(end);
+        }
+        // Bajt etykiety (arc_offset + 1) rozdziela najmłodszy bajt adresu od pozostałych.
+        let high = &self.arcs_data()[arc_offset + 2..end];
+        let address = high.iter().rev().fold(0usize, |value, &b| (value << 8) | b as usize);
+        Ok(((address << 8) | byte as usize) >> CFSA_LABEL_SHIFT)
+    }
+}
+
+impl<S: AsRef<[u8]> + Debug + Send + Sync> CFSA<S> {
+    /// Buduje indeksy łuków dla węzłów o dużej liczbie łuków; `get_arc` korzysta z nich
+    /// automatycznie, a pozostałe metody działają bez zmian.
+    pub fn with_jump_tables(mut self, options: JumpTableOptions) -> Result<Self> {
+        self.jump_tables = None;
+        self.jump_tables = Some(JumpTables::build(&self, options)?);
+        Ok(self)
+    }
+
+    pub fn jump_tables(&self) -> Option<&JumpTables> {
+        self.jump_tables.as_ref()
+    }
+}
+
+impl<S: AsRef<[u8]> + Debug + Send + Sync> Fsa for CFSA<S> {
+    fn get_flags(&self) -> FsaFlags {
+        self.flags
+    }
+
+    fn get_root_node(&self) -> Node {
+        self.root_node_offset
+    }
+
+    fn get_first_arc(&self, node_offset: Node) -> Result<ArcOffset> {
+        self.first_arc_opt(node_offset)?.ok_or(FsaError::InvalidNodeOffset(node_offset))
+    }
+
+    fn first_arc_opt(&self, node_offset: Node) -> Result<Option<ArcOffset>> {
+        // Węzeł 0 (pusty węzeł stanu końcowego) jest celem łuków bez dalszych przejść.
+        if node_offset == 0 {
+            return Ok(None);
+        }
+        let first_arc = self.node_first_arc(node_offset);
+        if first_arc >= self.arcs_data().len() {
+            return Err(FsaError::InvalidNodeOffset(node_offset));
+        }
+        Ok(Some(first_arc))
+    }
+
+    fn get_next_arc(&self, current_arc_offset: ArcOffset) -> Result<ArcOffset> {
+        if self.arc_byte(current_arc_offset)? & CFSA_ARC_IS_LAST_BIT != 0 {
+            return Err(FsaError::NoNextArc(current_arc_offset));
+        }
+        let next_arc_offset = self.skip_arc(current_arc_offset)?;
+        if next_arc_offset >= self.arcs_data().len() {
+            return Err(FsaError::InvalidArcOffset(current_arc_offset));
+        }
+        Ok(next_arc_offset)
+    }
+
+    fn get_arc(&self, node_offset: Node, label: u8) -> Result<ArcOffset> {
+        if let Some(found) = self.jump_tables.as_ref().and_then(|tables| tables.lookup(node_offset, label)) {
+            return found;
+        }
+        let Some(mut arc_offset) = self.first_arc_opt(node_offset)? else {
+            return Err(FsaError::ArcNotFound { label, node_offset });
+        };
+        loop {
+            if self.get_arc_label(arc_offset)? == label {
+                return Ok(arc_offset);
+            }
+            if self.arc_byte(arc_offset)? & CFSA_ARC_IS_LAST_BIT != 0 {
+                return Err(FsaError::ArcNotFound { label, node_offset });
+            }
+            arc_offset = self.get_next_arc(arc_offset)?;
+        }
+    }
+
+    fn get_end_node(&self, arc_offset: ArcOffset) -> Result<Node> {
+        self.destination(arc_offset)
+    }
+
+    fn get_arc_label(&self, arc_offset: ArcOffset) -> Result<u8> {
+        let byte = self.arc_byte(arc_offset)?;
+        if byte & CFSA_ARC_TARGET_IS_NEXT_BIT != 0 && byte & CFSA_LABEL_MASK != 0 {
+            return Ok(self.label_mapping[(byte >> CFSA_LABEL_SHIFT) as usize]);
+        }
+        self.arcs_data().get(arc_offset + 1).copied().ok_or(FsaError::InvalidArcOffset(arc_offset))
+    }
+
+    fn is_arc_final(&self, arc_offset: ArcOffset) -> Result<bool> {
+        Ok(self.arc_byte(arc_offset)? & CFSA_ARC_IS_FINAL_BIT != 0)
+    }
+
+    fn is_arc_terminal(&self, arc_offset: ArcOffset) -> Result<bool> {
+        Ok(self.arc_byte(arc_offset)? & CFSA_ARC_IS_LAST_BIT != 0)
+    }
+
+    fn get_right_language_count(&self, node: Node) -> Result<usize> {
+        if !self.flags.contains(FsaFlags::NUMBERS) {
+            return Err(FsaError::MissingNumbers);
+        }
+        let count = node
+            .checked_add(self.node_data_length as usize)
+            .and_then(|end| self.arcs_data().get(node..end))
+            .ok_or(FsaError::InvalidNodeOffset(node))?;
+        Ok(count.iter().rev().fold(0usize, |value, &b| (value << 8) | b as usize))
+    }
+
+    fn get_data_size(&self) -> Option<usize> {
+        Some(self.arcs_data().len())
+    }
+
+    fn get_reserved_size(&self) -> usize {
+        // Pusty węzeł stanu końcowego i węzeł epsilon z łukiem do korzenia.
+        self.skip_arc(self.node_first_arc(0))
+            .and_then(|epsilon| self.skip_arc(self.node_first_arc(epsilon)))
+            .unwrap_or(0)
+    }
+
+    fn get_arc_size(&self, arc_offset: ArcOffset) -> Result<usize> {
+        Ok(self.skip_arc(arc_offset)? - arc_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa_header::FSA_MAGIC;
+    use crate::iterator::ByteSequenceIterator;
+    use crate::validation::validate;
+    use assert_matches::assert_matches;
+
+    const FINAL: u8 = CFSA_ARC_IS_FINAL_BIT;
+    const LAST: u8 = CFSA_ARC_IS_LAST_BIT;
+    const NEXT: u8 = CFSA_ARC_TARGET_IS_NEXT_BIT;
+
+    // Brak plików 0xC5 zapisanych przez Javę: poniższe dane są złożone ręcznie według
+    // `CFSA.java`.
+    fn cfsa_bytes(hgtl: u8, labels: &[u8], arcs: &[u8]) -> Vec<u8> {
+        let mut data = FSA_MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION_CFSA, b'_', b'+', hgtl]);
+        let mut mapping = [0u8; CFSA_LABEL_MAPPING_SIZE];
+        mapping[1..=labels.len()].copy_from_slice(labels);
+        data.extend_from_slice(&mapping);
+        data.extend_from_slice(arcs);
+        data
+    }
+
+    fn sequences(fsa: &CFSA) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_explicit_targets() {
+        // "ab", "ac", "b" (gtl = 1): pusty węzeł, epsilon '^' (NEXT, etykieta wprost),
+        // korzeń pod adresem 4, stan po 'a' pod adresem 8 (8 << 3 = 64).
+        let arcs = [
+            0, 0,
+            LAST | NEXT, b'^',
+            64, b'a', FINAL | LAST, b'b',
+            FINAL, b'b', FINAL | LAST, b'c',
+        ];
+        let fsa = CFSA::from_bytes(cfsa_bytes(0x01, &[], &arcs)).unwrap();
+        assert_eq!((fsa.filler, fsa.annotation, fsa.goto_length), (b'_', b'+', 1));
+        assert_eq!(fsa.get_root_node(), 4);
+        assert_eq!(fsa.get_reserved_size(), 4);
+        assert_eq!(sequences(&fsa), [b"ab".to_vec(), b"ac".to_vec(), b"b".to_vec()]);
+        assert_eq!(fsa.get_end_node(fsa.get_arc(4, b'a').unwrap()).unwrap(), 8);
+        assert_eq!(fsa.first_arc_opt(0).unwrap(), None);
+        assert_matches!(fsa.get_arc(8, b'a'), Err(FsaError::ArcNotFound { label: b'a', node_offset: 8 }));
+        assert!(validate(&fsa).is_valid());
+    }
+
+    #[test]
+    fn test_next_arcs_with_compressed_labels() {
+        // "xyqz": 'x' i 'y' z tablicy (indeksy 1 i 2) w jednym bajcie, 'q' spoza tablicy
+        // w dwóch, ostatni łuk do stanu końcowego.
+        let arcs = [
+            0, 0,
+            LAST | NEXT, b'^',
+            (1 << 3) | LAST | NEXT,
+            (2 << 3) | LAST | NEXT,
+            LAST | NEXT, b'q',
+            FINAL | LAST, b'z',
+        ];
+        let fsa = CFSA::from_bytes(cfsa_bytes(0x01, b"xy", &arcs)).unwrap();
+        assert_eq!(sequences(&fsa), [b"xyqz".to_vec()]);
+        assert_eq!(fsa.get_arc_label(4).unwrap(), b'x');
+        assert_eq!(fsa.get_end_node(4).unwrap(), 5);
+        assert_eq!([4, 5, 6, 8].map(|arc| fsa.get_arc_size(arc).unwrap()), [1, 1, 2, 2]);
+        assert!(validate(&fsa).is_valid());
+    }
+
+    #[test]
+    fn test_wide_addresses_skip_label_byte() {
+        // gtl = 2: adres 40 << 3 = 0x140 zapisany jako [0x40 | flagi][etykieta][0x01].
+        let mut arcs = vec![0, 0, 0, LAST | NEXT, b'^', 0x40 | LAST, b'a', 0x01];
+        arcs.resize(40, 0);
+        arcs.extend_from_slice(&[FINAL | LAST, b'b', 0]);
+        let fsa = CFSA::from_bytes(cfsa_bytes(0x02, &[], &arcs)).unwrap();
+        assert_eq!(fsa.get_end_node(fsa.get_arc(5, b'a').unwrap()).unwrap(), 40);
+        assert_eq!(sequences(&fsa), [b"ab".to_vec()]);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let arcs = [0, 0, 0, 0, LAST | NEXT, b'^', 2, FINAL, b'a', FINAL | LAST, b'b'];
+        let fsa = CFSA::from_bytes(cfsa_bytes(0x11, &[], &arcs)).unwrap();
+        assert!(fsa.get_flags().contains(FsaFlags::NUMBERS));
+        assert_eq!(fsa.get_root_node(), 6);
+        assert_eq!(fsa.get_right_language_count(6).unwrap(), 2);
+        assert_eq!(sequences(&fsa), [b"a".to_vec(), b"b".to_vec()]);
+        assert!(validate(&fsa).is_valid());
+    }
+
+    #[test]
+    fn test_rejects_other_versions_and_truncated_header() {
+        let mut data = cfsa_bytes(0x01, &[], &[0, 0, LAST, b'^']);
+        data[FSA_MAGIC.len()] = crate::fsa_header::VERSION_CFSA2;
+        assert_matches!(CFSA::from_bytes(data), Err(FsaError::UnsupportedVersion(_)));
+
+        let mut data = cfsa_bytes(0x01, &[], &[]);
+        data.truncate(data.len() - 1);
+        assert_matches!(CFSA::from_bytes(data), Err(FsaError::UnexpectedEof));
+    }
+}
@@ -1,6 +1,6 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Odczyt automatów w formacie CFSA2 (wersja 0xC6), zgodnym z CFSA2.java
 
+use std::fmt::Debug;
 use std::fs::File;
 use std::io::{self, BufReader, Cursor, Read, Seek};
 use std::path::Path;
@@ -8,23 +8,55 @@ use std::path::Path;
 use crate::error::{FsaError, Result};
 use crate::fsa_header::{FsaFlags, FsaHeader, VERSION_CFSA2};
 use crate::fsa_trait::{ArcOffset, Fsa, Node};
-
-const CFSA_ARC_IS_FINAL_BIT: u8 = 1 << 0; 
-const CFSA_ARC_IS_LAST_BIT: u8 = 1 << 1;  
-const CFSA_ARC_TARGET_IS_NEXT_BIT: u8 = 1 << 2; 
-
-const CFSA2_LABEL_MASK: u8 = 0xf8; 
-const CFSA2_LABEL_SHIFT: u8 = 3;
-const CFSA2_FLAGS_MASK: u8 = 0x07; 
-
-
+use crate::jump_tables::{JumpTableOptions, JumpTables};
+
+pub const CFSA_ARC_IS_FINAL_BIT: u8 = 1 << 5; 
+pub const CFSA_ARC_IS_LAST_BIT: u8 = 1 << 6;  
+pub const CFSA_ARC_TARGET_IS_NEXT_BIT: u8 = 1 << 7; 
+
+/// Młodsze bity bajtu łuku: indeks etykiety w tablicy etykiet (0 — etykieta zapisana jawnie).
+pub const CFSA2_LABEL_INDEX_MASK: u8 = 0x1f;
+pub const CFSA2_FLAGS_MASK: u8 = 0xe0; 
+
+/// Maksymalna liczba etykiet w tablicy indeksów (indeksy 1..=31 w 5 bitach łuku).
+pub const CFSA2_MAX_LABEL_MAPPING: usize = 31;
+
+/// Implementacja automatu w wersji CFSA2 (układ z CFSA2.java).
+///
+/// ```text
+/// ---- FSA_MAGIC, VERSION_CFSA2 ----
+/// u16 BE           flags             // FsaFlags
+/// byte             label_count
+/// byte[label_count] label_mapping    // etykiety dla indeksów 1..label_count (wpis 0 nieużywany)
+/// byte[]           arcs
+/// ```
+///
+/// Łuk zaczyna się bajtem `[TARGET_NEXT:1][LAST:1][FINAL:1][indeks etykiety:5]`. Indeks 0
+/// oznacza, że etykieta jest zapisana w następnym bajcie. Dalej następuje bezwzględny adres
+/// węzła docelowego jako vint — chyba że ustawiono `CFSA_ARC_TARGET_IS_NEXT_BIT`: wtedy
+/// adresu nie ma, a węzeł docelowy zaczyna się zaraz za ostatnim łukiem bieżącego węzła.
+///
+/// Pod adresem 0 leży węzeł epsilon z jednym łukiem (`'^'`) prowadzącym do korzenia; adres
+/// docelowy 0 oznacza więc łuk bez dalszych przejść, a węzeł 0 jest dla [`Fsa`] węzłem bez
+/// łuków. Z flagą NUMBERS każdy węzeł poprzedza vint z liczbą sekwencji akceptowanych z tego
+/// węzła.
+///
+/// Podobnie jak [`crate::fsa5::FSA5`], automat czyta łuki wprost z bajtów typu `S`
+/// (patrz [`CFSA2::from_bytes`]).
 #[derive(Debug)]
-pub struct CFSA2 {
+pub struct CFSA2<S = Vec<u8>> {
     pub version: u8,
     pub flags: FsaFlags,
+    /// Liczba wpisów w `label_mapping` (bajt za flagami).
     pub goto_length_info: u8,
-    pub arcs_data: Vec<u8>,
+    /// Etykiety odpowiadające indeksom `1..goto_length_info`; wpis 0 jest nieużywany.
+    pub label_mapping: Vec<u8>,
+    /// Bajty automatu; dane łuków zaczynają się od `arcs_start`.
+    data: S,
+    arcs_start: usize,
     pub root_node_offset: usize,
+    /// Indeksy łuków węzłów o dużej liczbie łuków (patrz [`CFSA2::with_jump_tables`]).
+    jump_tables: Option<JumpTables>,
 }
 
 impl CFSA2 {
@@ -35,53 +67,130 @@ impl CFSA2 {
     }
 
     pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Self> {
-        let header = FsaHeader::read(reader)?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(map_io_error)?;
+        Self::from_bytes(data)
+    }
+}
+
+impl<S: AsRef<[u8]>> CFSA2<S> {
+    /// Tworzy automat nad bajtami całego pliku CFSA2 (z nagłówkiem) bez kopiowania łuków.
+    pub fn from_bytes(data: S) -> Result<Self> {
+        let mut reader = Cursor::new(data.as_ref());
+        let header = FsaHeader::read(&mut reader)?;
         if header.version != VERSION_CFSA2 {
             return Err(FsaError::UnsupportedVersion(header.version));
         }
 
         let mut file_flags_buffer = [0u8; 2]; 
         reader.read_exact(&mut file_flags_buffer).map_err(map_io_error)?;
-        
-        let automaton_flags_short = u16::from_le_bytes(file_flags_buffer);
-        let flags = FsaFlags::from_bits_truncate(automaton_flags_short & 0x00FF); 
-        let goto_length_info = ((automaton_flags_short & 0xFF00) >> 8) as u8; 
+        let flags = FsaFlags::from_bits_truncate(u16::from_be_bytes(file_flags_buffer));
 
-        let root_node_offset = if flags.contains(FsaFlags::NUMBERS) {
-            let (offset, _bytes_read) = read_vint(reader)?;
-            offset
-        } else {
-            0 
-        };
+        let mut label_count = [0u8; 1];
+        reader.read_exact(&mut label_count).map_err(map_io_error)?;
+        let goto_length_info = label_count[0];
+        if goto_length_info as usize > CFSA2_MAX_LABEL_MAPPING + 1 {
+            return Err(FsaError::CorruptedFsa(format!(
+                "Label mapping too long: {} entries (max {})",
+                goto_length_info, CFSA2_MAX_LABEL_MAPPING + 1
+            )));
+        }
+        let mut label_mapping = vec![0u8; goto_length_info as usize];
+        reader.read_exact(&mut label_mapping).map_err(map_io_error)?;
 
-        let mut arcs_data = Vec::new();
-        reader.read_to_end(&mut arcs_data).map_err(map_io_error)?;
+        let arcs_start = reader.position() as usize;
 
-        Ok(CFSA2 {
+        let mut fsa = CFSA2 {
             version: header.version,
             flags,
             goto_length_info,
-            arcs_data,
-            root_node_offset,
-        })
+            label_mapping,
+            data,
+            arcs_start,
+            root_node_offset: 0,
+            jump_tables: None,
+        };
+        // Korzeń to cel łuku węzła epsilon.
+        fsa.root_node_offset = fsa.end_node(fsa.skip_node_data(0)?)?;
+        Ok(fsa)
+    }
+
+    /// Surowe dane łuków automatu (bez nagłówka i tablicy etykiet).
+    #[inline(always)]
+    pub fn arcs_data(&self) -> &[u8] {
+        &self.data.as_ref()[self.arcs_start..]
+    }
+
+    /// Dekoduje początek łuku: (etykieta, flagi, offset za etykietą).
+    #[inline]
+    fn read_arc_head(&self, arc_offset: ArcOffset) -> Result<(u8, u8, usize)> {
+        let label_and_flags = *self.arcs_data().get(arc_offset).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
+        let arc_flags = label_and_flags & CFSA2_FLAGS_MASK;
+        let index = label_and_flags & CFSA2_LABEL_INDEX_MASK;
+
+        if index == 0 {
+            let label = *self.arcs_data().get(arc_offset + 1).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
+            return Ok((label, arc_flags, arc_offset + 2));
+        }
+        let label = *self.label_mapping.get(index as usize).ok_or_else(|| {
+            FsaError::CorruptedFsa(format!("Label index {} out of mapping range at arc {}", index, arc_offset))
+        })?;
+        Ok((label, arc_flags, arc_offset + 1))
     }
 
     #[inline]
     fn read_vint_at(&self, offset: usize) -> Result<(usize, usize)> {
-        if offset >= self.arcs_data.len() {
-            if self.arcs_data.is_empty() && offset == 0 { 
-                return Err(FsaError::CorruptedFsa(format!(
-                    "VInt read offset 0 on empty arcs_data"
-                )));
-            }
+        if offset >= self.arcs_data().len() {
             return Err(FsaError::CorruptedFsa(format!(
                 "VInt read offset {} out of bounds (len: {})",
-                offset, self.arcs_data.len()
+                offset, self.arcs_data().len()
             )));
         }
-        let mut cursor = Cursor::new(&self.arcs_data[offset..]);
+        let mut cursor = Cursor::new(&self.arcs_data()[offset..]);
         read_vint(&mut cursor)
     }
+
+    /// Pierwszy łuk węzła: z flagą NUMBERS za liczbą sekwencji (jak `getFirstArc` w Javie).
+    #[inline]
+    fn skip_node_data(&self, node_offset: Node) -> Result<ArcOffset> {
+        if node_offset >= self.arcs_data().len() {
+            return Err(FsaError::InvalidNodeOffset(node_offset));
+        }
+        if self.flags.contains(FsaFlags::NUMBERS) {
+            let (_count, count_bytes) = self.read_vint_at(node_offset)?;
+            return Ok(node_offset + count_bytes);
+        }
+        Ok(node_offset)
+    }
+
+    /// Offset za łukiem: za etykietą i — jeśli cel nie leży za węzłem — za adresem celu.
+    #[inline]
+    fn skip_arc(&self, arc_offset: ArcOffset) -> Result<usize> {
+        let (_label, arc_flags, after_label) = self.read_arc_head(arc_offset)?;
+        if (arc_flags & CFSA_ARC_TARGET_IS_NEXT_BIT) != 0 {
+            return Ok(after_label);
+        }
+        let (_target, target_bytes) = self.read_vint_at(after_label)?;
+        Ok(after_label + target_bytes)
+    }
+
+    /// Węzeł docelowy łuku (jak `getDestinationNodeOffset` w Javie); 0 dla łuku bez celu.
+    #[inline]
+    fn end_node(&self, arc_offset: ArcOffset) -> Result<Node> {
+        let (_label, arc_flags, after_label) = self.read_arc_head(arc_offset)?;
+        if (arc_flags & CFSA_ARC_TARGET_IS_NEXT_BIT) == 0 {
+            return Ok(self.read_vint_at(after_label)?.0);
+        }
+
+        // Cel leży zaraz za ostatnim łukiem bieżącego węzła.
+        let mut arc = arc_offset;
+        let mut flags = arc_flags;
+        while (flags & CFSA_ARC_IS_LAST_BIT) == 0 {
+            arc = self.skip_arc(arc)?;
+            flags = self.read_arc_head(arc)?.1;
+        }
+        self.skip_arc(arc)
+    }
 }
 
 /// Pomocnicza funkcja do odczytu VInt z Readera.
@@ -123,7 +232,21 @@ fn map_io_error(e: std::io::Error) -> FsaError {
 }
 
 
-impl Fsa for CFSA2 {
+impl<S: AsRef<[u8]> + Debug + Send + Sync> CFSA2<S> {
+    /// Buduje indeksy łuków dla węzłów o dużej liczbie łuków; `get_arc` korzysta z nich
+    /// automatycznie, a pozostałe metody działają bez zmian.
+    pub fn with_jump_tables(mut self, options: JumpTableOptions) -> Result<Self> {
+        self.jump_tables = None;
+        self.jump_tables = Some(JumpTables::build(&self, options)?);
+        Ok(self)
+    }
+
+    pub fn jump_tables(&self) -> Option<&JumpTables> {
+        self.jump_tables.as_ref()
+    }
+}
+
+impl<S: AsRef<[u8]> + Debug + Send + Sync> Fsa for CFSA2<S> {
     fn get_flags(&self) -> FsaFlags {
         self.flags
     }
@@ -133,117 +256,67 @@ impl Fsa for CFSA2 {
     }
 
     fn get_first_arc(&self, node_offset: Node) -> Result<ArcOffset> {
-        if node_offset >= self.arcs_data.len() {
-            if self.arcs_data.is_empty() && node_offset == 0 {
-                 return Err(FsaError::InvalidNodeOffset(node_offset));
-            }
+        self.first_arc_opt(node_offset)?.ok_or(FsaError::InvalidNodeOffset(node_offset))
+    }
+
+    fn first_arc_opt(&self, node_offset: Node) -> Result<Option<ArcOffset>> {
+        // Węzeł 0 (epsilon) jest celem łuków bez dalszych przejść, jak w Javie.
+        if node_offset == 0 {
+            return Ok(None);
+        }
+        let first_arc = self.skip_node_data(node_offset)?;
+        if first_arc >= self.arcs_data().len() {
             return Err(FsaError::InvalidNodeOffset(node_offset));
         }
-        Ok(node_offset)
+        Ok(Some(first_arc))
     }
 
     fn get_next_arc(&self, current_arc_offset: ArcOffset) -> Result<ArcOffset> {
-        if current_arc_offset >= self.arcs_data.len() {
-            return Err(FsaError::InvalidArcOffset(current_arc_offset));
-        }
-
-        let label_and_flags = self.arcs_data[current_arc_offset];
-        let arc_flags = label_and_flags & CFSA2_FLAGS_MASK;
-
+        let arc_flags = self.read_arc_head(current_arc_offset)?.1;
         if (arc_flags & CFSA_ARC_IS_LAST_BIT) != 0 {
             return Err(FsaError::NoNextArc(current_arc_offset));
         }
 
-        let (_target_addr, target_addr_bytes) = self.read_vint_at(
-            current_arc_offset.checked_add(1)
-            .ok_or_else(|| FsaError::CorruptedFsa(format!("Offset overflow for VInt read at arc {}", current_arc_offset)))?
-        )?;
-        
-        let next_arc_offset = current_arc_offset
-            .checked_add(1) 
-            .ok_or_else(|| FsaError::CorruptedFsa(format!("Offset overflow calculating next arc (step 1) for arc {}", current_arc_offset)))?
-            .checked_add(target_addr_bytes) 
-            .ok_or_else(|| FsaError::CorruptedFsa(format!("Offset overflow calculating next arc (step 2) for arc {}", current_arc_offset)))?;
-
-        if next_arc_offset >= self.arcs_data.len() {
+        let next_arc_offset = self.skip_arc(current_arc_offset)?;
+        if next_arc_offset >= self.arcs_data().len() {
             return Err(FsaError::CorruptedFsa(format!(
                 "Calculated next arc offset {} points beyond or at end of data (len: {}) for arc {}",
-                next_arc_offset, self.arcs_data.len(), current_arc_offset
+                next_arc_offset, self.arcs_data().len(), current_arc_offset
             )));
         }
         Ok(next_arc_offset)
     }
 
     fn get_arc(&self, node_offset: Node, label: u8) -> Result<ArcOffset> {
-        if node_offset >= self.arcs_data.len() && !(self.arcs_data.is_empty() && node_offset == 0) {
-            return Err(FsaError::InvalidNodeOffset(node_offset));
-        }
-        if self.arcs_data.is_empty() && node_offset == 0 { 
-             return Err(FsaError::ArcNotFound { label, node_offset });
+        if let Some(found) = self.jump_tables.as_ref().and_then(|tables| tables.lookup(node_offset, label)) {
+            return found;
         }
-
-        let mut current_arc_res = self.get_first_arc(node_offset);
-
-        while let Ok(arc_offset) = current_arc_res {
-            if arc_offset >= self.arcs_data.len() {
-                return Err(FsaError::CorruptedFsa(format!("Arc offset {} out of bounds while searching for label {}", arc_offset, label)));
-            }
-            
-            let label_and_flags = self.arcs_data[arc_offset];
-            let arc_label = (label_and_flags & CFSA2_LABEL_MASK) >> CFSA2_LABEL_SHIFT;
-            let arc_flags = label_and_flags & CFSA2_FLAGS_MASK;
-
+        let Some(mut arc_offset) = self.first_arc_opt(node_offset)? else {
+            return Err(FsaError::ArcNotFound { label, node_offset });
+        };
+        loop {
+            let (arc_label, arc_flags, _after_label) = self.read_arc_head(arc_offset)?;
             if arc_label == label {
                 return Ok(arc_offset);
             }
-
             if (arc_flags & CFSA_ARC_IS_LAST_BIT) != 0 {
-                break; 
+                return Err(FsaError::ArcNotFound { label, node_offset });
             }
-            current_arc_res = self.get_next_arc(arc_offset);
+            arc_offset = self.skip_arc(arc_offset)?;
         }
-        Err(FsaError::ArcNotFound { label, node_offset })
     }
 
     fn get_end_node(&self, arc_offset: ArcOffset) -> Result<Node> {
-        if arc_offset.checked_add(1).map_or(true, |vint_start| vint_start > self.arcs_data.len()) {
-             return Err(FsaError::InvalidArcOffset(arc_offset));
-        }
-        if arc_offset >= self.arcs_data.len() {
-            return Err(FsaError::InvalidArcOffset(arc_offset));
-        }
-
-        let label_and_flags = self.arcs_data[arc_offset];
-        let arc_flags = label_and_flags & CFSA2_FLAGS_MASK;
-
-        let (target_address_val, bytes_read_for_vint) = self.read_vint_at(arc_offset + 1)?;
-
-        if (arc_flags & CFSA_ARC_TARGET_IS_NEXT_BIT) != 0 {
-            Ok(target_address_val)
-        } else {
-            let offset_after_vint = arc_offset
-                .checked_add(1) 
-                .ok_or_else(|| FsaError::CorruptedFsa(format!("End node offset overflow (step 1) for arc {}", arc_offset)))?
-                .checked_add(bytes_read_for_vint) 
-                .ok_or_else(|| FsaError::CorruptedFsa(format!("End node offset overflow (step 2) for arc {}", arc_offset)))?;
-            
-            offset_after_vint.checked_add(target_address_val)
-                .ok_or_else(|| FsaError::CorruptedFsa(format!("End node offset overflow (step 3) for arc {}", arc_offset)))
-        }
+        self.end_node(arc_offset)
     }
 
     fn get_arc_label(&self, arc_offset: ArcOffset) -> Result<u8> {
-        if arc_offset < self.arcs_data.len() {
-            let label_and_flags = self.arcs_data[arc_offset];
-            Ok((label_and_flags & CFSA2_LABEL_MASK) >> CFSA2_LABEL_SHIFT)
-        } else {
-            Err(FsaError::InvalidArcOffset(arc_offset))
-        }
+        Ok(self.read_arc_head(arc_offset)?.0)
     }
 
     fn is_arc_final(&self, arc_offset: ArcOffset) -> Result<bool> {
-        if arc_offset < self.arcs_data.len() {
-            let label_and_flags = self.arcs_data[arc_offset];
+        if arc_offset < self.arcs_data().len() {
+            let label_and_flags = self.arcs_data()[arc_offset];
             Ok((label_and_flags & CFSA_ARC_IS_FINAL_BIT) != 0)
         } else {
             Err(FsaError::InvalidArcOffset(arc_offset))
@@ -251,13 +324,33 @@ impl Fsa for CFSA2 {
     }
 
     fn is_arc_terminal(&self, arc_offset: ArcOffset) -> Result<bool> {
-        if arc_offset < self.arcs_data.len() {
-            let label_and_flags = self.arcs_data[arc_offset];
+        if arc_offset < self.arcs_data().len() {
+            let label_and_flags = self.arcs_data()[arc_offset];
             Ok((label_and_flags & CFSA_ARC_IS_LAST_BIT) != 0)
         } else {
             Err(FsaError::InvalidArcOffset(arc_offset))
         }
     }
+
+    fn get_right_language_count(&self, node: Node) -> Result<usize> {
+        if !self.flags.contains(FsaFlags::NUMBERS) {
+            return Err(FsaError::MissingNumbers);
+        }
+        Ok(self.read_vint_at(node)?.0)
+    }
+
+    fn get_data_size(&self) -> Option<usize> {
+        Some(self.arcs_data().len())
+    }
+
+    fn get_reserved_size(&self) -> usize {
+        // Węzeł epsilon: (licznik przy NUMBERS i) jeden łuk do korzenia.
+        self.skip_node_data(0).and_then(|arc| self.skip_arc(arc)).unwrap_or(0)
+    }
+
+    fn get_arc_size(&self, arc_offset: ArcOffset) -> Result<usize> {
+        Ok(self.skip_arc(arc_offset)? - arc_offset)
+    }
 }
 
 
@@ -326,24 +419,32 @@ mod tests {
     }
 
 
-    fn create_cfsa2_ab_data() -> Vec<u8> {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
+    /// Nagłówek CFSA2 z flagami (BE) i tablicą etykiet (wpis 0 nieużywany).
+    fn header(flags: FsaFlags, labels: &[u8]) -> Vec<u8> {
+        let mut data = FSA_MAGIC.to_vec();
         data.push(VERSION_CFSA2);
-        let gtl_info = 0u8; 
-        let automaton_flags_short: u16 = (FsaFlags::empty().bits() & 0x00FF) | ((gtl_info & 0xFF) as u16) << 8;
-        data.extend_from_slice(&automaton_flags_short.to_le_bytes());
+        data.extend_from_slice(&flags.bits().to_be_bytes());
+        data.push(labels.len() as u8 + 1);
+        data.push(0);
+        data.extend_from_slice(labels);
+        data
+    }
 
-        let label_a = 1u8; 
-        data.push((label_a << CFSA2_LABEL_SHIFT) | CFSA_ARC_IS_FINAL_BIT); 
-        data.extend_from_slice(&vint_to_bytes(0)); 
+    /// Węzeł epsilon (offset 0) z łukiem `'^'` do korzenia.
+    fn epsilon(root: usize) -> Vec<u8> {
+        let mut arcs = vec![CFSA_ARC_IS_LAST_BIT, b'^'];
+        arcs.extend_from_slice(&vint_to_bytes(root));
+        arcs
+    }
 
-        let label_b = 2u8; 
-        data.push((label_b << CFSA2_LABEL_SHIFT) | CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT); 
-        data.extend_from_slice(&vint_to_bytes(0)); 
+    fn create_cfsa2_ab_data() -> Vec<u8> {
+        let mut data = header(FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED, b"ab");
+        data.extend_from_slice(&epsilon(3));
+        // Korzeń (offset 3): 'a' i 'b' z tablicy etykiet, oba końcowe i bez dalszych przejść.
+        data.extend_from_slice(&[CFSA_ARC_IS_FINAL_BIT | 1, 0]);
+        data.extend_from_slice(&[CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT | 2, 0]);
         data
     }
-    
 
     #[test]
     fn test_cfsa2_from_reader_simple_ab() {
@@ -352,13 +453,25 @@ mod tests {
         let fsa = CFSA2::from_reader(&mut cursor).expect("Failed to read CFSA2");
 
         assert_eq!(fsa.version, VERSION_CFSA2);
-        assert_eq!(fsa.flags, FsaFlags::empty());
-        assert_eq!(fsa.goto_length_info, 0); 
-        assert_eq!(fsa.root_node_offset, 0);
+        assert_eq!(fsa.flags, FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED);
+        assert_eq!(fsa.goto_length_info, 3); 
+        assert_eq!(fsa.label_mapping, vec![0, b'a', b'b']);
+        assert_eq!(fsa.root_node_offset, 3);
 
-        let header_len = 4 + 1 + 2; 
+        let header_len = 4 + 1 + 2 + 1 + 3; 
         let expected_arcs_data = &fsa_data[header_len..];
-        assert_eq!(fsa.arcs_data, expected_arcs_data);
+        assert_eq!(fsa.arcs_data(), expected_arcs_data);
+    }
+
+    #[test]
+    fn test_cfsa2_from_bytes_borrows_data() {
+        let fsa_data = create_cfsa2_ab_data();
+        let fsa = CFSA2::from_bytes(fsa_data.as_slice()).unwrap();
+
+        let header_len = 4 + 1 + 2 + 1 + 3;
+        assert_eq!(fsa.arcs_data().as_ptr(), fsa_data[header_len..].as_ptr());
+        assert_eq!(fsa.get_arc(3, b'b').unwrap(), 5);
+        assert_matches!(CFSA2::from_bytes(&fsa_data[..6]), Err(FsaError::UnexpectedEof));
     }
 
     #[test]
@@ -368,48 +481,65 @@ mod tests {
         let fsa = CFSA2::from_reader(&mut cursor).unwrap();
         let root = fsa.get_root_node(); 
 
-        let arc_a = fsa.get_arc(root, 1).unwrap(); 
-        assert_eq!(arc_a, 0); 
-        assert_eq!(fsa.get_arc_label(arc_a).unwrap(), 1);
+        let arc_a = fsa.get_arc(root, b'a').unwrap(); 
+        assert_eq!(arc_a, 3); 
+        assert_eq!(fsa.get_arc_label(arc_a).unwrap(), b'a');
         assert!(fsa.is_arc_final(arc_a).unwrap());
         assert!(!fsa.is_arc_terminal(arc_a).unwrap()); 
-        assert_eq!(fsa.get_end_node(arc_a).unwrap(), 2);
+        assert_eq!(fsa.get_end_node(arc_a).unwrap(), 0);
+        assert_matches!(fsa.get_first_arc(0), Err(FsaError::InvalidNodeOffset(0)));
 
         let arc_b = fsa.get_next_arc(arc_a).unwrap();
-        assert_eq!(arc_b, 2); 
-        assert_eq!(fsa.get_arc_label(arc_b).unwrap(), 2);
+        assert_eq!(arc_b, 5); 
+        assert_eq!(fsa.get_arc_label(arc_b).unwrap(), b'b');
         assert!(fsa.is_arc_final(arc_b).unwrap());
         assert!(fsa.is_arc_terminal(arc_b).unwrap()); 
-        assert_eq!(fsa.get_end_node(arc_b).unwrap(), 4);
-
-
-        let arc_b_direct = fsa.get_arc(root, 2).unwrap();
-        assert_eq!(arc_b_direct, 2);
+        assert_eq!(fsa.get_end_node(arc_b).unwrap(), 0);
+        assert_eq!(fsa.get_arc_size(arc_b).unwrap(), 2);
 
-        assert_matches!(fsa.get_next_arc(arc_b), Err(FsaError::NoNextArc(2)));
-        assert_matches!(fsa.get_arc(root, 3), Err(FsaError::ArcNotFound { label: 3, node_offset: 0 }));
+        assert_matches!(fsa.get_next_arc(arc_b), Err(FsaError::NoNextArc(5)));
+        assert_matches!(fsa.get_arc(root, b'c'), Err(FsaError::ArcNotFound { label: b'c', node_offset: 3 }));
     }
 
     #[test]
     fn test_cfsa2_get_end_node_target_is_next() {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_CFSA2);
-        let gtl_info = 0u8;
-        let automaton_flags_short: u16 = (FsaFlags::empty().bits() & 0x00FF) | ((gtl_info & 0xFF) as u16) << 8;
-        data.extend_from_slice(&automaton_flags_short.to_le_bytes());
-
-        let label = 0u8;
-        let target_node_addr = 218; 
-        data.push((label << CFSA2_LABEL_SHIFT) | CFSA_ARC_TARGET_IS_NEXT_BIT | CFSA_ARC_IS_LAST_BIT);
-        data.extend_from_slice(&vint_to_bytes(target_node_addr)); 
+        let mut data = header(FsaFlags::FLEXIBLE, b"ab");
+        data.extend_from_slice(&epsilon(3));
+        // Łuk z TARGET_IS_NEXT nie ma adresu: węzeł docelowy zaczyna się za ostatnim
+        // łukiem węzła ('b'), a nie za samym łukiem.
+        data.push(CFSA_ARC_TARGET_IS_NEXT_BIT | 1);
+        data.extend_from_slice(&[CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT | 2, 0]);
+        // Węzeł 6: etykieta spoza tablicy zapisana jawnie.
+        data.extend_from_slice(&[CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT, b'z', 0]);
         
-        let mut cursor = Cursor::new(data);
-        let fsa = CFSA2::from_reader(&mut cursor).unwrap();
+        let fsa = CFSA2::from_reader(&mut Cursor::new(data)).unwrap();
         let root = fsa.get_root_node();
-        let arc = fsa.get_first_arc(root).unwrap();
+        let arc_a = fsa.get_first_arc(root).unwrap();
+        assert_eq!(fsa.get_arc_size(arc_a).unwrap(), 1);
+        assert_eq!(fsa.get_end_node(arc_a).unwrap(), 6);
+
+        let arc_z = fsa.get_arc(6, b'z').unwrap();
+        assert_eq!(fsa.get_arc_label(arc_z).unwrap(), b'z');
+        assert_eq!(fsa.get_arc_size(arc_z).unwrap(), 3);
+        assert_eq!(fsa.get_end_node(arc_z).unwrap(), 0);
+        assert_matches!(fsa.get_arc(root, b'z'), Err(FsaError::ArcNotFound { .. }));
+    }
 
-        assert_eq!(fsa.get_end_node(arc).unwrap(), target_node_addr);
+    #[test]
+    fn test_cfsa2_numbers_and_invalid_label_index() {
+        let mut data = header(FsaFlags::FLEXIBLE | FsaFlags::NUMBERS, b"a");
+        // Węzeł epsilon i korzeń (offset 4) poprzedzone liczbą sekwencji.
+        data.extend_from_slice(&[0, CFSA_ARC_IS_LAST_BIT, b'^', 4]);
+        data.extend_from_slice(&[1, CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT | 1, 0]);
+        let fsa = CFSA2::from_bytes(data.clone()).unwrap();
+        assert_eq!(fsa.get_root_node(), 4);
+        assert_eq!(fsa.get_first_arc(4).unwrap(), 5);
+        assert_eq!(fsa.get_right_language_count(4).unwrap(), 1);
+
+        // Indeks 3 wykracza poza dwuelementową tablicę etykiet.
+        let last = data.len() - 2;
+        data[last] = CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT | 3;
+        let fsa = CFSA2::from_bytes(data).unwrap();
+        assert_matches!(fsa.get_arc_label(5), Err(FsaError::CorruptedFsa(_)));
     }
 }
-
@@ -0,0 +1,143 @@
+// Uzupełnianie przedrostków (autouzupełnianie)
+
+use crate::error::Result;
+use crate::fsa_trait::Fsa;
+use crate::iterator::SequenceCursor;
+use crate::traversal::{FsaTraversal, MatchKind};
+
+/// Opcje zapytania o uzupełnienia przedrostka.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompletionOptions {
+    /// Maksymalna liczba zwracanych uzupełnień (`None` oznacza brak ograniczenia).
+    pub limit: Option<usize>,
+    /// Liczba pomijanych pierwszych uzupełnień (np. przy stronicowaniu).
+    pub skip: usize,
+    /// Separator słownika: sekwencje ucinane są przed pierwszym łukiem z tą etykietą,
+    /// więc zwracane są same formy fleksyjne, bez zakodowanego lematu i tagu.
+    pub separator: Option<u8>,
+}
+
+/// Leniwy iterator po sekwencjach automatu zaczynających się od danego przedrostka.
+///
+/// Zwraca pełne sekwencje (z przedrostkiem) w kolejności łuków, czyli leksykograficznej
+/// dla automatów zbudowanych z posortowanego wejścia. Z separatorem każda forma
+/// zwracana jest raz, w kolejności sekwencji `forma + separator`.
+#[derive(Debug)]
+pub struct Completions<'a, T: Fsa + ?Sized> {
+    prefix: Vec<u8>,
+    prefix_pending: bool,
+    suffixes: Option<SequenceCursor<'a, T>>,
+    skip: usize,
+    remaining: Option<usize>,
+}
+
+impl<'a, T: Fsa + ?Sized> Completions<'a, T> {
+    /// Dopasowuje przedrostek przez [`FsaTraversal::perfect_match`] i przygotowuje iterator
+    /// po jego uzupełnieniach.
+    pub fn new(fsa: &'a T, prefix: &[u8], options: CompletionOptions) -> Result<Self> {
+        let mut completions = Completions {
+            prefix: prefix.to_vec(),
+            prefix_pending: false,
+            suffixes: None,
+            skip: options.skip,
+            remaining: options.limit,
+        };
+
+        let node = if prefix.is_empty() {
+            fsa.get_root_node()
+        } else {
+            let matched = FsaTraversal::new(fsa).perfect_match(prefix)?;
+            match matched.kind {
+                MatchKind::ExactMatch => {
+                    // Z separatorem sam przedrostek zwraca iterator (łuk separatora).
+                    completions.prefix_pending = options.separator.is_none();
+                    matched.node
+                }
+                MatchKind::SequenceIsAPrefix => matched.node,
+                MatchKind::NoMatch | MatchKind::AutomatonIsAPrefix => return Ok(completions),
+            }
+        };
+
+        let suffixes = SequenceCursor::from_node(fsa, node);
+        completions.suffixes = Some(match options.separator {
+            Some(separator) => suffixes.stop_at(separator),
+            None => suffixes,
+        });
+        Ok(completions)
+    }
+
+    fn next_completion(&mut self) -> Option<Result<Vec<u8>>> {
+        if self.prefix_pending {
+            self.prefix_pending = false;
+            return Some(Ok(self.prefix.clone()));
+        }
+        let suffix = self.suffixes.as_mut()?.advance()?;
+        Some(suffix.map(|suffix| [self.prefix.as_slice(), suffix].concat()))
+    }
+}
+
+impl<'a, T: Fsa + ?Sized> Iterator for Completions<'a, T> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        loop {
+            let completion = self.next_completion()?;
+            if completion.is_ok() && self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            if let Some(remaining) = self.remaining.as_mut() {
+                *remaining -= 1;
+            }
+            return Some(completion);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa5::FSA5;
+    use crate::test_utils::trie_fsa;
+
+    fn complete(fsa: &FSA5, prefix: &[u8], options: CompletionOptions) -> Vec<String> {
+        Completions::new(fsa, prefix, options)
+            .unwrap()
+            .map(|c| String::from_utf8(c.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_completions_in_order() {
+        let fsa = trie_fsa(&[b"ala", b"alan", b"ale", b"kot", b"kota"]);
+        let all = CompletionOptions::default();
+        assert_eq!(complete(&fsa, b"al", all), ["ala", "alan", "ale"]);
+        assert_eq!(complete(&fsa, b"ala", all), ["ala", "alan"]);
+        assert_eq!(complete(&fsa, b"", all), ["ala", "alan", "ale", "kot", "kota"]);
+        assert!(complete(&fsa, b"pies", all).is_empty());
+        assert!(complete(&fsa, b"kotek", all).is_empty());
+    }
+
+    #[test]
+    fn test_limit_and_skip() {
+        let fsa = trie_fsa(&[b"ala", b"alan", b"ale", b"kot", b"kota"]);
+        let page = |skip, limit| CompletionOptions { limit: Some(limit), skip, separator: None };
+        assert_eq!(complete(&fsa, b"", page(0, 2)), ["ala", "alan"]);
+        assert_eq!(complete(&fsa, b"", page(2, 2)), ["ale", "kot"]);
+        assert_eq!(complete(&fsa, b"", page(4, 2)), ["kota"]);
+        assert!(complete(&fsa, b"al", page(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_separator_completes_forms_only() {
+        let fsa = trie_fsa(&[b"kot+A", b"kot+B", b"kota+C", b"kotek+D", b"pies+E"]);
+        let forms = CompletionOptions { separator: Some(b'+'), ..Default::default() };
+        assert_eq!(complete(&fsa, b"kot", forms), ["kot", "kota", "kotek"]);
+        assert_eq!(complete(&fsa, b"", forms), ["kot", "kota", "kotek", "pies"]);
+        let forms = CompletionOptions { limit: Some(1), skip: 1, ..forms };
+        assert_eq!(complete(&fsa, b"ko", forms), ["kota"]);
+    }
+}
@@ -48,4 +48,16 @@ pub enum FsaError {
     /// Bieżący łuk jest ostatnim łukiem wychodzącym z węzła i nie ma następnego.
     #[error("Brak następnego łuku po łuku o offsecie {0} (jest ostatni).")]
     NoNextArc(usize),
+
+    /// Automat nie zapisuje liczby sekwencji w węzłach (brak flagi NUMBERS).
+    #[error("Automat nie zawiera liczby sekwencji w węzłach (brak flagi NUMBERS).")]
+    MissingNumbers,
+
+    /// Numer sekwencji wykracza poza liczbę sekwencji automatu.
+    #[error("Numer sekwencji {index} poza zakresem (liczba sekwencji: {count}).")]
+    IndexOutOfRange { index: usize, count: usize },
+
+    /// Implementacja automatu nie udostępnia żądanej informacji.
+    #[error("Operacja nieobsługiwana przez ten automat: {0}")]
+    Unsupported(&'static str),
 }
@@ -1,16 +1,16 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Odczyt automatów w formacie FSA5 (wersja 5), zgodnym z FSA5.java
 
 use std::fs::File;
-use std::io::{self, BufReader, Read, Seek};
+use std::fmt::Debug;
+use std::io::{self, BufReader, Cursor, Read, Seek};
 use std::path::Path;
 
-
 use crate::error::{FsaError, Result};
 use crate::fsa_header::{FsaFlags, FsaHeader, VERSION_FSA5};
 use crate::fsa_trait::{ArcOffset, Fsa, Node};
+use crate::jump_tables::{JumpTableOptions, JumpTables};
 
-/// Implementacja automatu w wersji FSA5.
+/// Implementacja automatu w wersji FSA5 (układ z FSA5.java).
 ///
 /// Format FSA5 jest następujący:
 ///
@@ -23,54 +23,67 @@ use crate::fsa_trait::{ArcOffset, Fsa, Node};
 ///
 /// ---- HEADER ----
 /// byte             FSA_VERSION = 5;
-/// byte             gtl;        // node data address size (target, label)
-/// byte             n_size;     // node data address size (next)
-///
-/// ---- FLAGS ----
-/// unsigned short   flags;      // automaton flags (see FSAFlags)
+/// byte             filler;       // bajt wypełnienia (domyślnie '_')
+/// byte             annotation;   // separator adnotacji (domyślnie '+')
+/// byte             hgtl;         // (node_data_length << 4) | gtl
 ///
 /// ---- DATA ----
-/// byte[]           arcs;       // arcs data
+/// byte[]           arcs;         // arcs data
 /// ```
 ///
 /// Szczegóły dotyczące struktury łuków (arcs):
-/// Każdy łuk (arc) w `arcs` ma następującą strukturę:
 /// - `label` (1 bajt): etykieta łuku.
-/// - `flags` (1 bajt): flagi łuku:
+/// - `gtl` bajtów little-endian z wartością `(target << 3) | flags`, gdzie flagi to:
 ///     - `BIT_ARC_FINAL` (0x01): czy łuk jest końcowy.
 ///     - `BIT_ARC_LAST` (0x02): czy łuk jest ostatnim z danego węzła.
-///     - `BIT_ARC_NEXT` (0x04): czy łuk ma zakodowany adres następnego łuku (jeśli nie, to jest on bezpośrednio po nim).
-///     - `BIT_ARC_TARGET_NEXT` (0x08): jeśli ustawione, adres docelowy jest adresem następnego *węzła*. W tej implementacji (zgodnie z FSA5.java) adresy docelowe są zawsze absolutne.
-/// - `target_address` (zmienna długość, `gtl` bajtów): adres węzła docelowego.
-/// - `next_arc_address` (zmienna długość, `n_size` bajtów, opcjonalnie): adres następnego łuku, jeśli flaga `BIT_ARC_NEXT` jest ustawiona.
+///     - `BIT_ARC_TARGET_NEXT` (0x04): węzeł docelowy zaczyna się zaraz za łukiem; łuk
+///       zajmuje wtedy tylko 2 bajty (etykieta i flagi) i nie zapisuje adresu.
+///
+/// Pod adresem 0 leży pusty węzeł oznaczający stan końcowy (adres docelowy 0 oznacza łuk
+/// bez dalszych przejść, a węzeł 0 jest dla [`Fsa`] węzłem bez łuków), za nim węzeł
+/// epsilon z jednym łukiem (`'^'`) prowadzącym do korzenia.
+///
+/// Niezerowe `node_data_length` oznacza flagę `NUMBERS`: każdy węzeł zaczyna się wtedy od
+/// liczby sekwencji akceptowanych z tego węzła (`node_data_length` bajtów, little-endian),
+/// po której następują jego łuki. Flagi automatu nie są zapisywane w pliku; jak w Javie
+/// przyjmowane są `FLEXIBLE`, `STOPBIT` i `NEXTBIT` (oraz ewentualnie `NUMBERS`).
 ///
-/// Adresy mogą być względne lub bezwzględne w zależności od implementacji (w FSA5 są to offsety).
+/// Automat czyta łuki bezpośrednio z bajtów typu `S`: domyślnie z własnego `Vec<u8>`,
+/// ale też z pożyczonego `&[u8]`, współdzielonego `Arc<[u8]>` czy pamięci mapowanej
+/// z pliku (patrz [`FSA5::from_bytes`]).
 #[derive(Debug)]
-pub struct FSA5 {
+pub struct FSA5<S = Vec<u8>> {
     /// Wersja automatu (powinna być VERSION_FSA5).
     pub version: u8,
     /// Flagi automatu.
     pub flags: FsaFlags,
-    /// Rozmiar adresu dla (target, label) w bajtach.
-    /// W FSA5.java to `gtl = gotoLength`.
+    /// Bajt wypełnienia z nagłówka.
+    pub filler: u8,
+    /// Separator adnotacji z nagłówka.
+    pub annotation: u8,
+    /// Rozmiar pola adresu i flag łuku w bajtach.
+    /// W FSA5.java to `gtl`.
     pub goto_length: u8,
-    /// Rozmiar adresu dla następnego łuku w bajtach.
-    /// W FSA5.java to `n_size = nodeDataLength`.
+    /// Rozmiar liczby sekwencji przed łukami węzła w bajtach (0 bez flagi NUMBERS).
+    /// W FSA5.java to `nodeDataLength`.
     pub node_data_length: u8,
-    /// Surowe dane łuków automatu.
-    pub arcs_data: Vec<u8>,
-    /// Adres (offset) korzenia automatu w `arcs_data`.
+    /// Bajty automatu; dane łuków zaczynają się od `arcs_start`.
+    data: S,
+    arcs_start: usize,
+    /// Adres (offset) korzenia automatu w danych łuków.
     /// W FSA5.java to `rootNodeOffset`.
     pub root_node_offset: usize,
+    /// Indeksy łuków węzłów o dużej liczbie łuków (patrz [`FSA5::with_jump_tables`]).
+    jump_tables: Option<JumpTables>,
 }
 
 // Flagi bitowe dla pojedynczego łuku w FSA5
 pub const BIT_ARC_FINAL: u8 = 0x01;
 pub const BIT_ARC_LAST: u8 = 0x02;
-pub const BIT_ARC_NEXT: u8 = 0x04;
-pub const BIT_ARC_TARGET_NEXT: u8 = 0x08;
-#[allow(dead_code)]
-pub const BIT_TARGET_RELATIVE: u8 = 0x10;
+pub const BIT_ARC_TARGET_NEXT: u8 = 0x04;
+
+/// Liczba bitów flag w młodszej części pola adresu łuku.
+pub const ARC_FLAGS_BITS: u32 = 3;
 
 /// Mapuje błąd `std::io::Error` na `FsaError`, specyficznie obsługując `UnexpectedEof`.
 fn map_io_error(e: std::io::Error) -> FsaError {
@@ -91,63 +104,101 @@ impl FSA5 {
 
     /// Wczytuje automat FSA5 z podanego czytnika (readera).
     pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Self> {
-        let header = FsaHeader::read(reader)?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(map_io_error)?;
+        Self::from_bytes(data)
+    }
+}
+
+impl<S: AsRef<[u8]>> FSA5<S> {
+    /// Tworzy automat nad bajtami całego pliku FSA5 (z nagłówkiem) bez kopiowania łuków.
+    pub fn from_bytes(data: S) -> Result<Self> {
+        let mut reader = Cursor::new(data.as_ref());
+        let header = FsaHeader::read(&mut reader)?;
         if header.version != VERSION_FSA5 {
             return Err(FsaError::UnsupportedVersion(header.version));
         }
 
-        let mut gtl_buffer = [0u8; 1];
-        reader.read_exact(&mut gtl_buffer).map_err(map_io_error)?;
-        let goto_length = gtl_buffer[0];
-
-        let mut n_size_buffer = [0u8; 1];
-        reader.read_exact(&mut n_size_buffer).map_err(map_io_error)?;
-        let node_data_length = n_size_buffer[0];
-
-        let mut flags_buffer = [0u8; 2];
-        reader.read_exact(&mut flags_buffer).map_err(map_io_error)?;
-        let flags_val = u16::from_le_bytes(flags_buffer);
-        let flags = FsaFlags::from_bits_truncate(flags_val);
-
-        let root_node_offset = if flags.contains(FsaFlags::NUMBERS) {
-            if goto_length == 0 {
-                return Err(FsaError::CorruptedFsa("goto_length is 0 with NUMBERS flag, cannot read root_node_offset".to_string()));
-            }
-            let mut root_offset_buffer = vec![0u8; goto_length as usize];
-            reader.read_exact(&mut root_offset_buffer).map_err(map_io_error)?;
-            read_address(&root_offset_buffer, goto_length)?
-        } else {
-            0
-        };
+        let mut fields = [0u8; 3];
+        reader.read_exact(&mut fields).map_err(map_io_error)?;
+        let [filler, annotation, hgtl] = fields;
+        let node_data_length = (hgtl >> 4) & 0x0f;
+        let goto_length = hgtl & 0x0f;
+        if goto_length == 0 {
+            return Err(FsaError::CorruptedFsa("goto_length is 0".to_string()));
+        }
 
-        let mut arcs_data = Vec::new();
-        reader.read_to_end(&mut arcs_data).map_err(map_io_error)?;
+        let mut flags = FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED;
+        if node_data_length != 0 {
+            flags |= FsaFlags::NUMBERS;
+        }
 
-        Ok(FSA5 {
+        let arcs_start = reader.position() as usize;
+        let mut fsa = FSA5 {
             version: header.version,
             flags,
+            filler,
+            annotation,
             goto_length,
             node_data_length,
-            arcs_data,
-            root_node_offset,
-        })
+            data,
+            arcs_start,
+            root_node_offset: 0,
+            jump_tables: None,
+        };
+        // Pomija pusty węzeł stanu końcowego i idzie łukiem węzła epsilon.
+        let epsilon = fsa.skip_arc(fsa.node_first_arc(0))?;
+        fsa.root_node_offset = fsa.destination(fsa.node_first_arc(epsilon))?;
+        Ok(fsa)
+    }
+
+    /// Surowe dane łuków automatu (bez nagłówka).
+    #[inline(always)]
+    pub fn arcs_data(&self) -> &[u8] {
+        &self.data.as_ref()[self.arcs_start..]
+    }
+
+    /// Pierwszy łuk węzła (za liczbą sekwencji), bez sprawdzania zakresu.
+    #[inline(always)]
+    fn node_first_arc(&self, node: Node) -> ArcOffset {
+        node.saturating_add(self.node_data_length as usize)
+    }
+
+    /// Flagi łuku (młodsze bity bajtu za etykietą).
+    #[inline(always)]
+    fn arc_flags(&self, arc_offset: ArcOffset) -> Result<u8> {
+        let flags = *self.arcs_data().get(arc_offset.saturating_add(1)).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
+        Ok(flags)
+    }
+
+    /// Offset za łukiem: 2 bajty dla łuku z `BIT_ARC_TARGET_NEXT`, inaczej `1 + gtl`.
+    #[inline(always)]
+    fn skip_arc(&self, arc_offset: ArcOffset) -> Result<usize> {
+        let size = if self.arc_flags(arc_offset)? & BIT_ARC_TARGET_NEXT != 0 { 2 } else { 1 + self.goto_length as usize };
+        Ok(arc_offset + size)
+    }
+
+    /// Adres węzła docelowego łuku (0 dla łuku bez dalszych przejść).
+    #[inline(always)]
+    fn destination(&self, arc_offset: ArcOffset) -> Result<Node> {
+        if self.arc_flags(arc_offset)? & BIT_ARC_TARGET_NEXT != 0 {
+            return self.skip_arc(arc_offset);
+        }
+        Ok(self.read_address_at(arc_offset + 1, self.goto_length)? >> ARC_FLAGS_BITS)
     }
 
     /// Pomocnicza funkcja do odczytu adresu o zmiennej długości z `arcs_data`.
     #[inline(always)]
     fn read_address_at(&self, offset: usize, length: u8) -> Result<usize> {
-        if length == 0 {
-            return Ok(0);
-        }
         let end_offset = offset.checked_add(length as usize).ok_or_else(|| FsaError::CorruptedFsa(format!("Address offset overflow: {} + {}", offset, length)))?;
 
-        if end_offset > self.arcs_data.len() {
+        if end_offset > self.arcs_data().len() {
             return Err(FsaError::CorruptedFsa(format!(
                 "Attempt to read address beyond arcs_data bounds: offset {}, length {}, data_len {}",
-                offset, length, self.arcs_data.len()
+                offset, length, self.arcs_data().len()
             )));
         }
-        read_address(&self.arcs_data[offset..end_offset], length)
+        read_address(&self.arcs_data()[offset..end_offset], length)
     }
 }
 
@@ -166,14 +217,28 @@ fn read_address(buffer: &[u8], length: u8) -> Result<usize> {
     }
 
     let mut address: usize = 0;
-    for i in 0..(length as usize) {
-        address |= (buffer[i] as usize) << (i * 8);
+    for (i, &byte) in buffer.iter().take(length as usize).enumerate() {
+        address |= (byte as usize) << (i * 8);
     }
     Ok(address)
 }
 
 
-impl Fsa for FSA5 {
+impl<S: AsRef<[u8]> + Debug + Send + Sync> FSA5<S> {
+    /// Buduje indeksy łuków dla węzłów o dużej liczbie łuków; `get_arc` korzysta z nich
+    /// automatycznie, a pozostałe metody działają bez zmian.
+    pub fn with_jump_tables(mut self, options: JumpTableOptions) -> Result<Self> {
+        self.jump_tables = None;
+        self.jump_tables = Some(JumpTables::build(&self, options)?);
+        Ok(self)
+    }
+
+    pub fn jump_tables(&self) -> Option<&JumpTables> {
+        self.jump_tables.as_ref()
+    }
+}
+
+impl<S: AsRef<[u8]> + Debug + Send + Sync> Fsa for FSA5<S> {
     fn get_flags(&self) -> FsaFlags {
         self.flags
     }
@@ -183,110 +248,90 @@ impl Fsa for FSA5 {
     }
 
     fn get_first_arc(&self, node_offset: Node) -> Result<ArcOffset> {
-        if node_offset >= self.arcs_data.len() && self.arcs_data.is_empty() && node_offset == 0 {
-            return Err(FsaError::InvalidNodeOffset(node_offset));
+        self.first_arc_opt(node_offset)?.ok_or(FsaError::InvalidNodeOffset(node_offset))
+    }
+
+    fn first_arc_opt(&self, node_offset: Node) -> Result<Option<ArcOffset>> {
+        // Węzeł 0 (pusty węzeł stanu końcowego) jest celem łuków bez dalszych przejść.
+        if node_offset == 0 {
+            return Ok(None);
         }
-        if node_offset >= self.arcs_data.len() {
+        let first_arc = self.node_first_arc(node_offset);
+        if first_arc >= self.arcs_data().len() {
             return Err(FsaError::InvalidNodeOffset(node_offset));
         }
-        Ok(node_offset)
+        Ok(Some(first_arc))
     }
 
     fn get_next_arc(&self, current_arc_offset: ArcOffset) -> Result<ArcOffset> {
-        let arc_flags_byte_offset = current_arc_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(current_arc_offset))?;
-        if arc_flags_byte_offset >= self.arcs_data.len() {
-            return Err(FsaError::InvalidArcOffset(current_arc_offset));
-        }
-        let arc_flags = self.arcs_data[arc_flags_byte_offset];
-
-        if (arc_flags & BIT_ARC_LAST) != 0 {
+        if self.arc_flags(current_arc_offset)? & BIT_ARC_LAST != 0 {
             return Err(FsaError::NoNextArc(current_arc_offset));
         }
-
-        if (arc_flags & BIT_ARC_NEXT) != 0 {
-            let next_arc_address_offset_start = arc_flags_byte_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(current_arc_offset))?
-                                                .checked_add(self.goto_length as usize).ok_or(FsaError::InvalidArcOffset(current_arc_offset))?;
-            self.read_address_at(next_arc_address_offset_start, self.node_data_length)
-        } else {
-            let next_arc_offset = arc_flags_byte_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(current_arc_offset))?
-                                  .checked_add(self.goto_length as usize).ok_or(FsaError::InvalidArcOffset(current_arc_offset))?;
-            if next_arc_offset > self.arcs_data.len() {
-                 return Err(FsaError::CorruptedFsa(format!("Calculated next arc offset {} is out of bounds {}", next_arc_offset, self.arcs_data.len())));
-            }
-            Ok(next_arc_offset)
+        let next_arc_offset = self.skip_arc(current_arc_offset)?;
+        if next_arc_offset >= self.arcs_data().len() {
+            return Err(FsaError::CorruptedFsa(format!(
+                "Calculated next arc offset {} is out of bounds {}",
+                next_arc_offset,
+                self.arcs_data().len()
+            )));
         }
+        Ok(next_arc_offset)
     }
 
     fn get_arc(&self, node_offset: Node, label: u8) -> Result<ArcOffset> {
-        if node_offset >= self.arcs_data.len() && !(self.arcs_data.is_empty() && node_offset == 0) {
-            return Err(FsaError::InvalidNodeOffset(node_offset));
+        if let Some(found) = self.jump_tables.as_ref().and_then(|tables| tables.lookup(node_offset, label)) {
+            return found;
         }
-        if self.arcs_data.is_empty() && node_offset == 0 {
-             return Err(FsaError::ArcNotFound { label, node_offset });
-        }
-
-
-        let mut current_arc_result = self.get_first_arc(node_offset);
-
-        while let Ok(arc_offset) = current_arc_result {
-            if arc_offset >= self.arcs_data.len() {
-                return Err(FsaError::CorruptedFsa(format!("ArcOffset offset {} out of bounds while searching for label {}", arc_offset, label)));
-            }
-            let arc_label = self.arcs_data[arc_offset];
-
-            if arc_label == label {
+        let Some(mut arc_offset) = self.first_arc_opt(node_offset)? else {
+            return Err(FsaError::ArcNotFound { label, node_offset });
+        };
+        loop {
+            if self.get_arc_label(arc_offset)? == label {
                 return Ok(arc_offset);
             }
-            if arc_label > label {
-                break;
-            }
-
-            let arc_flags_byte_offset = arc_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
-            if arc_flags_byte_offset >= self.arcs_data.len() {
-                 return Err(FsaError::CorruptedFsa(format!("ArcOffset flags offset {} out of bounds for arc {}", arc_flags_byte_offset, arc_offset)));
-            }
-            let arc_flags = self.arcs_data[arc_flags_byte_offset];
-            if (arc_flags & BIT_ARC_LAST) != 0 {
-                break;
+            if self.arc_flags(arc_offset)? & BIT_ARC_LAST != 0 {
+                return Err(FsaError::ArcNotFound { label, node_offset });
             }
-            current_arc_result = self.get_next_arc(arc_offset);
+            arc_offset = self.get_next_arc(arc_offset)?;
         }
-        Err(FsaError::ArcNotFound { label, node_offset })
     }
 
     fn get_end_node(&self, arc_offset: ArcOffset) -> Result<Node> {
-        let target_address_start_offset = arc_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(arc_offset))?
-                                          .checked_add(1).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
-
-        let target_node_raw = self.read_address_at(target_address_start_offset, self.goto_length)?;
-
-        Ok(target_node_raw)
+        self.destination(arc_offset)
     }
 
     fn get_arc_label(&self, arc_offset: ArcOffset) -> Result<u8> {
-        if arc_offset < self.arcs_data.len() {
-            Ok(self.arcs_data[arc_offset])
-        } else {
-            Err(FsaError::InvalidArcOffset(arc_offset))
-        }
+        self.arcs_data().get(arc_offset).copied().ok_or(FsaError::InvalidArcOffset(arc_offset))
     }
 
     fn is_arc_final(&self, arc_offset: ArcOffset) -> Result<bool> {
-        let arc_flags_offset = arc_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
-        if arc_flags_offset < self.arcs_data.len() {
-            Ok((self.arcs_data[arc_flags_offset] & BIT_ARC_FINAL) != 0)
-        } else {
-            Err(FsaError::InvalidArcOffset(arc_offset))
-        }
+        Ok(self.arc_flags(arc_offset)? & BIT_ARC_FINAL != 0)
     }
 
     fn is_arc_terminal(&self, arc_offset: ArcOffset) -> Result<bool> {
-        let arc_flags_offset = arc_offset.checked_add(1).ok_or(FsaError::InvalidArcOffset(arc_offset))?;
-        if arc_flags_offset < self.arcs_data.len() {
-            Ok((self.arcs_data[arc_flags_offset] & BIT_ARC_LAST) != 0)
-        } else {
-            Err(FsaError::InvalidArcOffset(arc_offset))
+        Ok(self.arc_flags(arc_offset)? & BIT_ARC_LAST != 0)
+    }
+
+    fn get_right_language_count(&self, node: Node) -> Result<usize> {
+        if !self.flags.contains(FsaFlags::NUMBERS) {
+            return Err(FsaError::MissingNumbers);
         }
+        self.read_address_at(node, self.node_data_length)
+    }
+
+    fn get_data_size(&self) -> Option<usize> {
+        Some(self.arcs_data().len())
+    }
+
+    fn get_reserved_size(&self) -> usize {
+        // Pusty węzeł stanu końcowego i węzeł epsilon z łukiem do korzenia.
+        self.skip_arc(self.node_first_arc(0))
+            .and_then(|epsilon| self.skip_arc(self.node_first_arc(epsilon)))
+            .unwrap_or(0)
+    }
+
+    fn get_arc_size(&self, arc_offset: ArcOffset) -> Result<usize> {
+        Ok(self.skip_arc(arc_offset)? - arc_offset)
     }
 }
 
@@ -298,21 +343,30 @@ mod tests {
     use crate::fsa_header::FSA_MAGIC;
     use assert_matches::assert_matches;
 
+    const HEADER_LEN: usize = 4 + 1 + 3;
+
+    fn header(hgtl: u8) -> Vec<u8> {
+        let mut data = FSA_MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION_FSA5, b'_', b'+', hgtl]);
+        data
+    }
+
+    /// Automat {"a", "b"} w układzie z `FSA5Serializer.java` (gtl = 1).
     fn create_test_fsa5_ab_data() -> Vec<u8> {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_FSA5);
-        data.push(1); 
-        data.push(1); 
-        data.extend_from_slice(&0u16.to_le_bytes()); 
-
-        data.push(b'a');
-        data.push(BIT_ARC_FINAL); // Używamy stałej z tego modułu (fsa5.rs)
-        data.push(0x00);
-
-        data.push(b'b');
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); // Używamy stałych z tego modułu
-        data.push(0x00);
+        let mut data = header(0x01);
+        data.extend_from_slice(&[0, 0]); // pusty węzeł stanu końcowego
+        data.extend_from_slice(&[b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]); // epsilon -> korzeń (4)
+        data.extend_from_slice(&[b'a', BIT_ARC_FINAL]);
+        data.extend_from_slice(&[b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        data
+    }
+
+    /// Automat {"a", "b"} z liczbami sekwencji (jeden bajt przed łukami każdego węzła).
+    fn create_test_fsa5_with_numbers_data() -> Vec<u8> {
+        let mut data = header(0x11);
+        data.extend_from_slice(&[0, 0, 0]);
+        data.extend_from_slice(&[0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        data.extend_from_slice(&[2, b'a', BIT_ARC_FINAL, b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
         data
     }
 
@@ -323,22 +377,27 @@ mod tests {
         let fsa = FSA5::from_reader(&mut cursor).expect("Failed to read FSA5");
 
         assert_eq!(fsa.version, VERSION_FSA5);
-        assert_eq!(fsa.flags, FsaFlags::empty());
+        assert_eq!(fsa.flags, FsaFlags::FLEXIBLE | FsaFlags::_STOPBIT_DEPRECATED | FsaFlags::_NEXTBIT_DEPRECATED);
+        assert_eq!(fsa.filler, b'_');
+        assert_eq!(fsa.annotation, b'+');
         assert_eq!(fsa.goto_length, 1);
-        assert_eq!(fsa.node_data_length, 1);
-        assert_eq!(fsa.root_node_offset, 0);
-
-        let header_len = 4 + 1 + 1 + 1 + 2;
-        let expected_arcs_data = &fsa_file_bytes[header_len..];
-        assert_eq!(fsa.arcs_data, expected_arcs_data);
+        assert_eq!(fsa.node_data_length, 0);
+        assert_eq!(fsa.root_node_offset, 4);
+        assert_eq!(fsa.get_reserved_size(), 4);
+        assert_eq!(fsa.arcs_data(), &fsa_file_bytes[HEADER_LEN..]);
     }
 
     #[test]
-    fn test_fsa5_get_root_node() {
+    fn test_fsa5_from_bytes_borrows_data() {
         let fsa_file_bytes = create_test_fsa5_ab_data();
-        let mut cursor = Cursor::new(fsa_file_bytes);
-        let fsa = FSA5::from_reader(&mut cursor).unwrap();
-        assert_eq!(fsa.get_root_node(), 0);
+        let fsa = FSA5::from_bytes(&fsa_file_bytes[..]).unwrap();
+
+        assert_eq!(fsa.arcs_data().as_ptr(), fsa_file_bytes[HEADER_LEN..].as_ptr());
+        assert_eq!(fsa.get_arc_label(fsa.get_arc(4, b'b').unwrap()).unwrap(), b'b');
+
+        let shared: std::sync::Arc<[u8]> = fsa_file_bytes.into();
+        let fsa = FSA5::from_bytes(shared).unwrap();
+        assert!(fsa.is_arc_final(fsa.get_first_arc(4).unwrap()).unwrap());
     }
 
     #[test]
@@ -349,10 +408,22 @@ mod tests {
 
         let root_node = fsa.get_root_node();
         let first_arc = fsa.get_first_arc(root_node).unwrap();
-        assert_eq!(first_arc, 0);
+        assert_eq!(first_arc, 4);
         assert_eq!(fsa.get_arc_label(first_arc).unwrap(), b'a');
     }
 
+    #[test]
+    fn test_fsa5_first_arc_opt_tells_leaf_from_invalid_offset() {
+        let fsa = FSA5::from_bytes(create_test_fsa5_ab_data()).unwrap();
+        let end = fsa.arcs_data().len();
+
+        assert_eq!(fsa.first_arc_opt(fsa.get_root_node()).unwrap(), Some(4));
+        assert_eq!(fsa.first_arc_opt(0).unwrap(), None);
+        assert_matches!(fsa.get_arc(0, b'a'), Err(FsaError::ArcNotFound { label: b'a', .. }));
+        assert_matches!(fsa.first_arc_opt(end), Err(FsaError::InvalidNodeOffset(_)));
+        assert_matches!(fsa.get_arc(end + 3, b'a'), Err(FsaError::InvalidNodeOffset(_)));
+    }
+
     #[test]
     fn test_fsa5_get_next_arc() {
         let fsa_file_bytes = create_test_fsa5_ab_data();
@@ -363,12 +434,12 @@ mod tests {
         let arc_a = fsa.get_first_arc(root_node).unwrap();
         
         let arc_b = fsa.get_next_arc(arc_a).unwrap();
-        assert_eq!(arc_b, 3);
+        assert_eq!(arc_b, 6);
         assert_eq!(fsa.get_arc_label(arc_b).unwrap(), b'b');
 
         assert!(fsa.is_arc_terminal(arc_b).unwrap());
         let next_after_b_result = fsa.get_next_arc(arc_b);
-        assert_matches!(next_after_b_result, Err(FsaError::NoNextArc(3)));
+        assert_matches!(next_after_b_result, Err(FsaError::NoNextArc(6)));
     }
 
      #[test]
@@ -380,14 +451,14 @@ mod tests {
 
         let arc_a = fsa.get_arc(root, b'a').unwrap();
         assert_eq!(fsa.get_arc_label(arc_a).unwrap(), b'a');
-        assert_eq!(arc_a, 0);
+        assert_eq!(arc_a, 4);
 
         let arc_b = fsa.get_arc(root, b'b').unwrap();
         assert_eq!(fsa.get_arc_label(arc_b).unwrap(), b'b');
-        assert_eq!(arc_b, 3);
+        assert_eq!(arc_b, 6);
 
         let result_c = fsa.get_arc(root, b'c');
-        assert_matches!(result_c, Err(FsaError::ArcNotFound { label: b'c', node_offset: 0 }));
+        assert_matches!(result_c, Err(FsaError::ArcNotFound { label: b'c', node_offset: 4 }));
     }
 
     #[test]
@@ -401,6 +472,7 @@ mod tests {
         assert!(fsa.is_arc_final(arc_a).unwrap());
         assert!(!fsa.is_arc_terminal(arc_a).unwrap());
         assert_eq!(fsa.get_end_node(arc_a).unwrap(), 0);
+        assert_eq!(fsa.get_arc_size(arc_a).unwrap(), 2);
 
         let arc_b = fsa.get_arc(root, b'b').unwrap();
         assert!(fsa.is_arc_final(arc_b).unwrap());
@@ -408,66 +480,66 @@ mod tests {
         assert_eq!(fsa.get_end_node(arc_b).unwrap(), 0);
     }
 
-    fn create_test_fsa5_with_numbers_data(root_offset_val: usize, goto_len: u8) -> Vec<u8> {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_FSA5);
-        data.push(goto_len);
-        data.push(1);
-        data.extend_from_slice(&FsaFlags::NUMBERS.bits().to_le_bytes());
-
-        let mut root_offset_bytes = vec![0u8; goto_len as usize];
-        if goto_len > 0 {
-            for i in 0..(goto_len as usize) {
-                root_offset_bytes[i] = ((root_offset_val >> (i * 8)) & 0xFF) as u8;
-            }
-        }
-        data.extend_from_slice(&root_offset_bytes);
-        
-        if root_offset_val > 0 && root_offset_val < 255 && goto_len > 0 {
-            for _ in 0..=root_offset_val {
-                 data.push(0);
-            }
-        }
-        data.push(b'x'); data.push(BIT_ARC_FINAL | BIT_ARC_LAST); data.push(0);
-
-
-        data
+    #[test]
+    fn test_fsa5_explicit_and_next_targets() {
+        // {"ab", "b"}: 'a' -> węzeł {'b'} pod adresem 8 (zapisany jawnie, 8 << 3 = 64),
+        // ostatni łuk korzenia prowadzi do liścia.
+        let mut data = header(0x01);
+        data.extend_from_slice(&[0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        data.extend_from_slice(&[b'a', 8 << ARC_FLAGS_BITS, b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        data.extend_from_slice(&[b'b', BIT_ARC_FINAL | BIT_ARC_LAST]);
+        let fsa = FSA5::from_bytes(data).unwrap();
+
+        let arc_a = fsa.get_arc(fsa.get_root_node(), b'a').unwrap();
+        assert!(!fsa.is_arc_final(arc_a).unwrap());
+        assert_eq!(fsa.get_end_node(arc_a).unwrap(), 8);
+        let sequences: Vec<Vec<u8>> = crate::iterator::ByteSequenceIterator::new(&fsa).map(|s| s.unwrap()).collect();
+        assert_eq!(sequences, [b"ab".to_vec(), b"b".to_vec()]);
+        assert!(crate::validation::validate(&fsa).is_valid());
     }
 
     #[test]
     fn test_fsa5_from_reader_with_numbers_flag() {
-        let root_offset_val = 0x0A;
-        let goto_len = 1;
-        let fsa_file_bytes = create_test_fsa5_with_numbers_data(root_offset_val, goto_len);
+        let fsa_file_bytes = create_test_fsa5_with_numbers_data();
         let mut cursor = Cursor::new(fsa_file_bytes.clone());
         let fsa = FSA5::from_reader(&mut cursor).expect("Failed to read FSA5 with NUMBERS");
 
         assert_eq!(fsa.version, VERSION_FSA5);
         assert!(fsa.flags.contains(FsaFlags::NUMBERS));
-        assert_eq!(fsa.goto_length, goto_len);
-        assert_eq!(fsa.root_node_offset, root_offset_val);
-        assert_eq!(fsa.get_root_node(), root_offset_val);
-
-        let header_and_root_offset_len = 4 + 1 + 1 + 1 + 2 + (goto_len as usize);
-        let expected_arcs_data = &fsa_file_bytes[header_and_root_offset_len..];
-        assert_eq!(fsa.arcs_data, expected_arcs_data);
+        assert_eq!(fsa.goto_length, 1);
+        assert_eq!(fsa.node_data_length, 1);
+        assert_eq!(fsa.get_root_node(), 6);
+        assert_eq!(fsa.arcs_data(), &fsa_file_bytes[HEADER_LEN..]);
     }
     
     #[test]
-    fn test_fsa5_from_reader_with_numbers_flag_gtl_zero() {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_FSA5);
-        data.push(0); 
-        data.push(1); 
-        data.extend_from_slice(&FsaFlags::NUMBERS.bits().to_le_bytes());
+    fn test_fsa5_numbers_node_data_precedes_arcs() {
+        let fsa = FSA5::from_bytes(create_test_fsa5_with_numbers_data()).unwrap();
+        assert_eq!(fsa.get_first_arc(6).unwrap(), 7);
+        assert_eq!(fsa.get_right_language_count(6).unwrap(), 2);
+        assert_eq!(fsa.get_arc(6, b'b').unwrap(), 9);
+        assert_eq!(fsa.get_reserved_size(), 6);
+    }
 
-        let mut cursor = Cursor::new(data);
+    #[test]
+    fn test_fsa5_without_numbers_has_no_counts() {
+        let fsa = FSA5::from_bytes(create_test_fsa5_ab_data()).unwrap();
+        assert_matches!(fsa.get_right_language_count(4), Err(FsaError::MissingNumbers));
+    }
+
+    #[test]
+    fn test_fsa5_from_reader_gtl_zero() {
+        let mut cursor = Cursor::new(header(0x10));
         let result = FSA5::from_reader(&mut cursor);
         assert_matches!(result, Err(FsaError::CorruptedFsa(_)));
     }
 
+    #[test]
+    fn test_fsa5_truncated_header() {
+        let mut data = header(0x01);
+        data.truncate(HEADER_LEN - 1);
+        assert_matches!(FSA5::from_bytes(data), Err(FsaError::UnexpectedEof));
+    }
 
     #[test]
     fn test_read_address_helper() {
@@ -480,39 +552,21 @@ mod tests {
     }
 
     #[test]
-    fn test_get_first_arc_on_empty_fsa_data_valid_root() {
-        let mut fsa_file_bytes = Vec::new();
-        fsa_file_bytes.extend_from_slice(&FSA_MAGIC);
-        fsa_file_bytes.push(VERSION_FSA5);
-        fsa_file_bytes.push(1);
-        fsa_file_bytes.push(1);
-        fsa_file_bytes.extend_from_slice(&FsaFlags::empty().bits().to_le_bytes());
-
-        let mut cursor = Cursor::new(fsa_file_bytes);
-        let fsa = FSA5::from_reader(&mut cursor).unwrap();
+    fn test_empty_fsa() {
+        // Pusty automat: łuk epsilon bez NEXT prowadzi do węzła 0.
+        let mut data = header(0x01);
+        data.extend_from_slice(&[0, 0, b'^', BIT_ARC_LAST]);
+        let fsa = FSA5::from_bytes(data).unwrap();
         assert_eq!(fsa.root_node_offset, 0);
-        assert!(fsa.arcs_data.is_empty());
-
-        let result = fsa.get_first_arc(fsa.get_root_node());
-        assert_matches!(result, Err(FsaError::InvalidNodeOffset(0)));
-    }
-
-    #[test]
-    fn test_get_arc_on_empty_fsa_data() {
-        let mut fsa_file_bytes = Vec::new();
-        fsa_file_bytes.extend_from_slice(&FSA_MAGIC);
-        fsa_file_bytes.push(VERSION_FSA5);
-        fsa_file_bytes.push(1); 
-        fsa_file_bytes.push(1); 
-        fsa_file_bytes.extend_from_slice(&FsaFlags::empty().bits().to_le_bytes());
-
-        let mut cursor = Cursor::new(fsa_file_bytes);
-        let fsa = FSA5::from_reader(&mut cursor).unwrap();
-        assert_eq!(fsa.root_node_offset, 0);
-        assert!(fsa.arcs_data.is_empty());
 
+        assert_matches!(fsa.get_first_arc(fsa.get_root_node()), Err(FsaError::InvalidNodeOffset(0)));
         let result = fsa.get_arc(fsa.get_root_node(), b'a');
         assert_matches!(result, Err(FsaError::ArcNotFound { label: b'a', node_offset: 0 }));
+        assert_eq!(crate::iterator::ByteSequenceIterator::new(&fsa).count(), 0);
     }
 
+    #[test]
+    fn test_fsa5_without_epsilon_node_is_corrupted() {
+        assert_matches!(FSA5::from_bytes(header(0x01)), Err(FsaError::InvalidArcOffset(_)));
+    }
 }
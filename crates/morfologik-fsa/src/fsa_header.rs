@@ -12,19 +12,22 @@ pub const VERSION_FSA5: u8 = 5;
 /// Wersja dla formatu CFSA2 (zgodnie z kodem Morfologik Java CFSA2.java).
 pub const VERSION_CFSA2: u8 = 0xC6; // 198 dziesiętnie
 
-// Możemy usunąć lub zakomentować _VERSION_CFSA_DEPRECATED_JAVA, jeśli nie jest używana.
-// pub const _VERSION_CFSA_DEPRECATED_JAVA: u8 = 3; 
+/// Wersja dla starszego formatu CFSA (poprzednika CFSA2), obsługiwanego tylko do odczytu.
+pub const VERSION_CFSA: u8 = 0xC5;
 
 bitflags! {
-    /// Flagi opisujące atrybuty automatu FSA.
+    /// Flagi opisujące atrybuty automatu FSA (wartości bitów jak w FSAFlags.java; CFSA2
+    /// zapisuje je w nagłówku jako u16 big-endian).
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct FsaFlags: u16 {
         const FLEXIBLE = 0b0000_0000_0000_0001;
-        const NUMBERS = 0b0000_0000_0000_0010;
-        // Poniższe flagi są przestarzałe w Javie
+        // Poniższe flagi są przestarzałe w Javie i nie zmieniają układu łuków; FSA5 i CFSA2
+        // zawsze zgłaszają _STOPBIT_DEPRECATED i _NEXTBIT_DEPRECATED, jak w Javie.
+        const _STOPBIT_DEPRECATED = 0b0000_0000_0000_0010;
         const _NEXTBIT_DEPRECATED = 0b0000_0000_0000_0100;
-        const _STOPBIT_DEPRECATED = 0b0000_0000_0000_1000;
-        const _TAILBIT_DEPRECATED = 0b0000_0000_0001_0000;
+        const _TAILBIT_DEPRECATED = 0b0000_0000_0000_1000;
+        const NUMBERS = 0b0000_0001_0000_0000;
+        const SEPARATORS = 0b0000_0010_0000_0000;
     }
 }
 
@@ -35,7 +38,17 @@ pub struct FsaHeader {
 }
 
 impl FsaHeader {
+    /// Czyta nagłówek i sprawdza, czy wersja jest obsługiwana przez tę bibliotekę.
     pub fn read<R: io::Read>(reader: &mut R) -> Result<Self> {
+        let header = Self::read_any_version(reader)?;
+        match header.version {
+            VERSION_FSA5 | VERSION_CFSA | VERSION_CFSA2 => Ok(header),
+            version => Err(FsaError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Czyta nagłówek bez sprawdzania wersji (np. dla formatów z [`FsaRegistry`](crate::loader::FsaRegistry)).
+    pub fn read_any_version<R: io::Read>(reader: &mut R) -> Result<Self> {
         let mut magic_buffer = [0u8; 4];
         reader.read_exact(&mut magic_buffer).map_err(|e| {
             if e.kind() == io::ErrorKind::UnexpectedEof {
@@ -57,13 +70,7 @@ impl FsaHeader {
                 FsaError::Io(e)
             }
         })?;
-        let version = version_buffer[0];
-
-        // Akceptujemy teraz poprawną wersję CFSA2
-        match version {
-            VERSION_FSA5 | VERSION_CFSA2 /* | _VERSION_CFSA_DEPRECATED_JAVA */ => Ok(FsaHeader { version }),
-            _ => Err(FsaError::UnsupportedVersion(version)),
-        }
+        Ok(FsaHeader { version: version_buffer[0] })
     }
 
     pub fn get_version(&self) -> u8 {
@@ -100,6 +107,13 @@ mod tests {
 
     // Usunięto test dla VERSION_CFSA_LEGACY, ponieważ jest to teraz VERSION_CFSA2
 
+    #[test]
+    fn test_read_fsa_header_legacy_cfsa() {
+        let mut data: Vec<u8> = FSA_MAGIC.to_vec();
+        data.push(VERSION_CFSA);
+        assert_eq!(FsaHeader::read(&mut Cursor::new(data)).unwrap().version, VERSION_CFSA);
+    }
+
     #[test]
     fn test_read_fsa_header_invalid_magic() {
         let data = vec![b'a', b'b', b'c', b'd', VERSION_FSA5];
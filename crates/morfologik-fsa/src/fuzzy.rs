@@ -0,0 +1,251 @@
+// Wyszukiwanie przybliżone (automat Levenshteina)
+
+use crate::error::Result;
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+
+/// Jednostka, na której liczone są edycje.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditUnit {
+    /// Pojedynczy bajt.
+    #[default]
+    Byte,
+    /// Znak UTF-8; niepoprawna sekwencja bajtów o długości wynikającej z pierwszego
+    /// bajtu liczona jest jako jedna jednostka.
+    Char,
+}
+
+/// Parametry wyszukiwania przybliżonego.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzyOptions {
+    /// Maksymalna liczba edycji (wstawień, usunięć, zamian).
+    pub max_edits: usize,
+    /// Czy zamiana dwóch sąsiednich jednostek liczy się jako jedna edycja.
+    pub transpositions: bool,
+    pub unit: EditUnit,
+}
+
+impl Default for FuzzyOptions {
+    fn default() -> Self {
+        FuzzyOptions { max_edits: 1, transpositions: false, unit: EditUnit::Byte }
+    }
+}
+
+/// Sekwencja automatu odległa od zapytania o `distance` edycji.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub sequence: Vec<u8>,
+    pub distance: usize,
+}
+
+/// Wyszukiwanie sekwencji automatu różniących się od zapytania o co najwyżej
+/// [`FuzzyOptions::max_edits`] edycji.
+///
+/// Automat przechodzony jest w głąb równolegle z automatem Levenshteina zapytania,
+/// którego stanem jest wiersz macierzy odległości dla bieżącej ścieżki. Gałęzie,
+/// w których każda pozycja wiersza przekracza limit, są odcinane.
+#[derive(Debug)]
+pub struct FuzzySearch<'a, F: Fsa + ?Sized> {
+    fsa: &'a F,
+    options: FuzzyOptions,
+}
+
+impl<'a, F: Fsa + ?Sized> FuzzySearch<'a, F> {
+    pub fn new(fsa: &'a F, options: FuzzyOptions) -> Self {
+        FuzzySearch { fsa, options }
+    }
+
+    /// Zwraca dopasowania posortowane według odległości, a przy równej odległości
+    /// według sekwencji.
+    pub fn find(&self, query: &[u8]) -> Result<Vec<FuzzyMatch>> {
+        let query = units(query, self.options.unit);
+        let mut state = SearchState {
+            rows: vec![(0..=query.len()).collect()],
+            units: Vec::new(),
+            path: Vec::new(),
+            matches: Vec::new(),
+            query,
+        };
+        self.visit(self.fsa.get_root_node(), 0, &mut state)?;
+        let mut matches = state.matches;
+        matches.sort_by(|a, b| a.distance.cmp(&b.distance).then_with(|| a.sequence.cmp(&b.sequence)));
+        Ok(matches)
+    }
+
+    /// Odwiedza łuki węzła; `unit_start` to pozycja w ścieżce, od której zaczyna się
+    /// niedokończony znak UTF-8.
+    fn visit(&self, node: Node, unit_start: usize, state: &mut SearchState) -> Result<()> {
+        let Some(mut arc) = self.fsa.first_arc_opt(node)? else {
+            return Ok(());
+        };
+        loop {
+            self.visit_arc(arc, unit_start, state)?;
+            if self.fsa.is_arc_terminal(arc)? {
+                return Ok(());
+            }
+            arc = self.fsa.get_next_arc(arc)?;
+        }
+    }
+
+    fn visit_arc(&self, arc: ArcOffset, unit_start: usize, state: &mut SearchState) -> Result<()> {
+        state.path.push(self.fsa.get_arc_label(arc)?);
+        let unit = match self.options.unit {
+            EditUnit::Byte => state.path.last().map(|&b| u32::from(b)),
+            EditUnit::Char => complete_char(&state.path[unit_start..]),
+        };
+
+        match unit {
+            Some(unit) => {
+                let row = state.next_row(unit, self.options.transpositions);
+                let distance = row[row.len() - 1];
+                if distance <= self.options.max_edits && self.fsa.is_arc_final(arc)? {
+                    state.matches.push(FuzzyMatch { sequence: state.path.clone(), distance });
+                }
+                if row.iter().min().is_some_and(|&min| min <= self.options.max_edits) {
+                    state.rows.push(row);
+                    state.units.push(unit);
+                    let next_start = state.path.len();
+                    self.visit(self.fsa.get_end_node(arc)?, next_start, state)?;
+                    state.rows.pop();
+                    state.units.pop();
+                }
+            }
+            // Znak nie jest jeszcze kompletny: stan automatu Levenshteina się nie zmienia.
+            None => self.visit(self.fsa.get_end_node(arc)?, unit_start, state)?,
+        }
+
+        state.path.pop();
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct SearchState {
+    query: Vec<u32>,
+    /// Wiersze macierzy odległości dla kolejnych jednostek ścieżki (pierwszy dla pustej).
+    rows: Vec<Vec<usize>>,
+    units: Vec<u32>,
+    path: Vec<u8>,
+    matches: Vec<FuzzyMatch>,
+}
+
+impl SearchState {
+    fn next_row(&self, unit: u32, transpositions: bool) -> Vec<usize> {
+        let query = &self.query;
+        let previous = &self.rows[self.rows.len() - 1];
+        let mut row = Vec::with_capacity(query.len() + 1);
+        row.push(previous[0] + 1);
+        for j in 1..=query.len() {
+            let substitution = previous[j - 1] + usize::from(query[j - 1] != unit);
+            let mut distance = substitution.min(previous[j] + 1).min(row[j - 1] + 1);
+            if transpositions && j > 1 && self.rows.len() > 1 {
+                let last_unit = self.units[self.units.len() - 1];
+                if unit == query[j - 2] && last_unit == query[j - 1] {
+                    distance = distance.min(self.rows[self.rows.len() - 2][j - 2] + 1);
+                }
+            }
+            row.push(distance);
+        }
+        row
+    }
+}
+
+/// Niepoprawne bajty UTF-8 mapowane są poza zakres znaków Unicode.
+const INVALID_UTF8_BASE: u32 = 0x110000;
+
+/// Długość znaku UTF-8 według pierwszego bajtu (1 dla bajtów niepoprawnych).
+pub(crate) fn utf8_len(lead: u8) -> usize {
+    match lead {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+/// Zwraca jednostkę, jeśli bajty tworzą kompletny znak (lub niepoprawną sekwencję).
+fn complete_char(bytes: &[u8]) -> Option<u32> {
+    if bytes.len() < utf8_len(bytes[0]) {
+        return None;
+    }
+    Some(match std::str::from_utf8(bytes) {
+        Ok(text) => text.chars().next().map_or(INVALID_UTF8_BASE, u32::from),
+        Err(_) => INVALID_UTF8_BASE + u32::from(bytes[0]),
+    })
+}
+
+fn units(bytes: &[u8], unit: EditUnit) -> Vec<u32> {
+    match unit {
+        EditUnit::Byte => bytes.iter().map(|&b| u32::from(b)).collect(),
+        EditUnit::Char => {
+            let mut units = Vec::new();
+            let mut start = 0;
+            while start < bytes.len() {
+                let end = (start + utf8_len(bytes[start])).min(bytes.len());
+                units.push(complete_char(&bytes[start..end]).unwrap_or(INVALID_UTF8_BASE + u32::from(bytes[start])));
+                start = end;
+            }
+            units
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::trie_fsa;
+
+    fn find(fsa: &crate::fsa5::FSA5, query: &str, options: FuzzyOptions) -> Vec<(String, usize)> {
+        FuzzySearch::new(fsa, options)
+            .find(query.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|m| (String::from_utf8(m.sequence).unwrap(), m.distance))
+            .collect()
+    }
+
+    fn owned(expected: &[(&str, usize)]) -> Vec<(String, usize)> {
+        expected.iter().map(|&(s, d)| (s.to_string(), d)).collect()
+    }
+
+    #[test]
+    fn test_byte_edits_ordered_by_distance() {
+        let fsa = trie_fsa(&[b"kit", b"kot", b"kota", b"koty", b"pies"]);
+        let options = FuzzyOptions { max_edits: 2, ..Default::default() };
+        assert_eq!(
+            find(&fsa, "kota", options),
+            owned(&[("kota", 0), ("kot", 1), ("koty", 1), ("kit", 2)])
+        );
+        assert_eq!(find(&fsa, "pies", FuzzyOptions { max_edits: 0, ..options }), owned(&[("pies", 0)]));
+    }
+
+    #[test]
+    fn test_transpositions() {
+        let fsa = trie_fsa(&[b"kota", b"okta"]);
+        let plain = FuzzyOptions { max_edits: 1, ..Default::default() };
+        assert_eq!(find(&fsa, "okta", plain), owned(&[("okta", 0)]));
+        let swapped = FuzzyOptions { transpositions: true, ..plain };
+        assert_eq!(find(&fsa, "okta", swapped), owned(&[("okta", 0), ("kota", 1)]));
+    }
+
+    #[test]
+    fn test_char_edits() {
+        let words = ["kot", "kąt", "łąka"];
+        let mut sequences: Vec<&[u8]> = words.iter().map(|w| w.as_bytes()).collect();
+        sequences.sort();
+        let fsa = trie_fsa(&sequences);
+
+        // "ą" to dwa bajty, więc bajtowo "kąt" jest o dwie edycje od "kot".
+        let bytes = FuzzyOptions { max_edits: 1, ..Default::default() };
+        assert_eq!(find(&fsa, "kot", bytes), owned(&[("kot", 0)]));
+        let chars = FuzzyOptions { unit: EditUnit::Char, ..bytes };
+        assert_eq!(find(&fsa, "kot", chars), owned(&[("kot", 0), ("kąt", 1)]));
+        assert_eq!(find(&fsa, "laka", FuzzyOptions { max_edits: 2, ..chars }), owned(&[("łąka", 2)]));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(units("aż".as_bytes(), EditUnit::Char), vec![u32::from('a'), u32::from('ż')]);
+        assert_eq!(units(b"a\xffb", EditUnit::Char), vec![0x61, INVALID_UTF8_BASE + 0xff, 0x62]);
+        assert_eq!(units(b"\xc5", EditUnit::Char), vec![INVALID_UTF8_BASE + 0xc5]);
+    }
+}
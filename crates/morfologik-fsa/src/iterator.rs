@@ -1,59 +1,191 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Przegląd sekwencji automatu: iterator, kursor bez alokacji i wizytator
 
 use crate::fsa_trait::{ArcOffset, Fsa, Node}; 
 use crate::error::{Result as FsaResult, FsaError}; 
 
-/// Iterator po sekwencjach bajtów (słowach) w automacie FSA.
+/// Kursor po sekwencjach bajtów automatu, udostępniający bieżącą sekwencję jako
+/// pożyczony wycinek wewnętrznego bufora.
+///
+/// W przeciwieństwie do [`ByteSequenceIterator`] nie alokuje nowego wektora dla każdej
+/// sekwencji, więc nadaje się do pełnych przeglądów dużych automatów. Kolejność, ucinanie
+/// na etykiecie i zakresy działają tak samo jak w iteratorze.
 #[derive(Debug)]
-pub struct ByteSequenceIterator<'a, T: Fsa + ?Sized + 'a> {
+pub struct SequenceCursor<'a, T: Fsa + ?Sized + 'a> {
     fsa: &'a T,
     // Stack stores: (node_id, option_of_next_arc_offset_from_this_node_to_try)
     stack: Vec<(Node, Option<ArcOffset>)>, 
     current_sequence: Vec<u8>,
-    #[allow(dead_code)]
-    start_node: Option<Node>, // For potential future use if iterator needs to restart from a specific sequence
+    /// Etykieta ostatnio zwróconej sekwencji do zdjęcia z bufora przy następnym kroku.
+    pending_pop: bool,
+    start_node: Option<Node>,
+    stop_label: Option<u8>,
+    /// Dolne ograniczenie ustawione przez `seek`/`seek_after` (klucz, czy włącznie).
+    lower_bound: Option<(Vec<u8>, bool)>,
+    /// Górne ograniczenie (wyłącznie) ustawione przez `until`.
+    upper_bound: Option<Vec<u8>>,
 }
 
-impl<'a, T: Fsa + ?Sized> ByteSequenceIterator<'a, T> {
+impl<'a, T: Fsa + ?Sized> SequenceCursor<'a, T> {
     pub fn new(fsa: &'a T) -> Self {
         Self::from_node(fsa, fsa.get_root_node())
     }
 
     pub fn from_node(fsa: &'a T, start_node_id: Node) -> Self {
-        let mut iterator = ByteSequenceIterator {
+        let mut cursor = SequenceCursor {
             fsa,
             stack: Vec::new(),
             current_sequence: Vec::new(),
+            pending_pop: false,
             start_node: Some(start_node_id),
+            stop_label: None,
+            lower_bound: None,
+            upper_bound: None,
         };
         
         // Initialize stack with the start node and its first arc (if any)
-        match fsa.get_first_arc(start_node_id) {
-            Ok(first_arc) => {
-                iterator.stack.push((start_node_id, Some(first_arc)));
+        // If start_node_id is a leaf (or invalid), the stack remains empty,
+        // and the cursor will correctly produce no items.
+        if let Ok(Some(first_arc)) = fsa.first_arc_opt(start_node_id) {
+            cursor.stack.push((start_node_id, Some(first_arc)));
+        }
+        cursor
+    }
+
+    /// Ucina sekwencje na łukach z etykietą `label`: zamiast schodzić głębiej, kursor
+    /// zwraca sekwencję sprzed tej etykiety (np. formę fleksyjną przed separatorem słownika).
+    pub fn stop_at(mut self, label: u8) -> Self {
+        self.stop_label = Some(label);
+        self
+    }
+
+    /// Kończy przegląd przed pierwszą sekwencją `>= upper` (górne ograniczenie wyłącznie).
+    pub fn until(mut self, upper: &[u8]) -> Self {
+        self.upper_bound = Some(upper.to_vec());
+        self
+    }
+
+    /// Ustawia kursor na pierwszej sekwencji `>= key`.
+    ///
+    /// Zamiast przeglądać wcześniejsze sekwencje, schodzi od węzła startowego po łukach
+    /// klucza, więc koszt zależy od długości klucza, a nie od liczby pominiętych sekwencji.
+    /// Klucze i kolejność dotyczą sekwencji zwracanych przez kursor (od węzła startowego)
+    /// i zakładają łuki węzłów posortowane według etykiet, jak w automatach z serializatorów.
+    pub fn seek(&mut self, key: &[u8]) -> FsaResult<()> {
+        self.position_at(key)?;
+        self.lower_bound = Some((key.to_vec(), true));
+        Ok(())
+    }
+
+    /// Ustawia kursor na pierwszej sekwencji `> key`, np. aby wznowić stronicowanie
+    /// po ostatniej zwróconej sekwencji.
+    pub fn seek_after(&mut self, key: &[u8]) -> FsaResult<()> {
+        self.position_at(key)?;
+        self.lower_bound = Some((key.to_vec(), false));
+        Ok(())
+    }
+
+    /// Przechodzi do następnej sekwencji i zwraca ją jako wycinek ważny do kolejnego kroku;
+    /// `None` po ostatniej sekwencji.
+    pub fn advance(&mut self) -> Option<FsaResult<&[u8]>> {
+        loop {
+            if let Err(e) = self.find_next_sequence()? {
+                return Some(Err(e));
+            }
+            let sequence = self.current_sequence.as_slice();
+            if let Some((lower, inclusive)) = &self.lower_bound {
+                if sequence < lower.as_slice() || (!inclusive && sequence == lower.as_slice()) {
+                    continue;
+                }
+                // Kolejne sekwencje są większe, więc ograniczenie nie jest już potrzebne.
+                self.lower_bound = None;
+            }
+            if self.upper_bound.as_ref().is_some_and(|upper| self.current_sequence >= *upper) {
+                self.stack.clear();
+                self.current_sequence.clear();
+                self.pending_pop = false;
+                return None;
+            }
+            return Some(Ok(&self.current_sequence));
+        }
+    }
+
+    /// Wywołuje `visitor` dla każdej pozostałej sekwencji; pierwszy błąd (kursora lub
+    /// zwrócony przez `visitor`) przerywa przegląd.
+    pub fn try_for_each<E, F>(&mut self, mut visitor: F) -> Result<(), E>
+    where
+        E: From<FsaError>,
+        F: FnMut(&[u8]) -> Result<(), E>,
+    {
+        while let Some(sequence) = self.advance() {
+            visitor(sequence?)?;
+        }
+        Ok(())
+    }
+
+    /// Odbudowuje stos tak, jakby przegląd doszedł do ścieżki klucza: w każdym węźle
+    /// następnym łukiem jest pierwszy łuk z etykietą `>=` kolejnego bajtu klucza.
+    fn position_at(&mut self, key: &[u8]) -> FsaResult<()> {
+        self.stack.clear();
+        self.current_sequence.clear();
+        self.pending_pop = false;
+        let Some(mut node) = self.start_node else {
+            return Ok(());
+        };
+        for (i, &byte) in key.iter().enumerate() {
+            let Some(first_arc) = self.fsa.first_arc_opt(node)? else {
+                return Ok(());
+            };
+            let mut arc = Some(first_arc);
+            while let Some(current) = arc {
+                if self.fsa.get_arc_label(current)? >= byte {
+                    break;
+                }
+                arc = if self.fsa.is_arc_terminal(current)? { None } else { Some(self.fsa.get_next_arc(current)?) };
+            }
+            let Some(current) = arc else {
+                self.stack.push((node, None));
+                return Ok(());
+            };
+            // Łuk z większą etykietą albo ostatni bajt klucza: dalej zwykły przegląd.
+            if self.fsa.get_arc_label(current)? > byte || i + 1 == key.len() {
+                self.stack.push((node, Some(current)));
+                return Ok(());
             }
-            Err(_) => {
-                // If start_node_id is invalid or has no arcs, stack remains empty,
-                // and the iterator will correctly produce no items.
+            let next_sibling = if self.fsa.is_arc_terminal(current)? { None } else { Some(self.fsa.get_next_arc(current)?) };
+            self.stack.push((node, next_sibling));
+            let target = self.fsa.get_end_node(current)?;
+            if self.fsa.first_arc_opt(target)?.is_none() {
+                // Klucz jest dłuższy niż ścieżka: dalej są tylko następne łuki rodzeństwa.
+                return Ok(());
             }
+            self.current_sequence.push(byte);
+            node = target;
         }
-        iterator
+        // Pusty klucz lub cały klucz przeszedł po łukach: sekwencje od tego węzła są większe.
+        if let Some(first_arc) = self.fsa.first_arc_opt(node)? {
+            self.stack.push((node, Some(first_arc)));
+        }
+        Ok(())
     }
 
-    fn find_next_sequence(&mut self) -> Option<FsaResult<Vec<u8>>> {
+    /// Przechodzi do następnej sekwencji; po `Some(Ok(()))` jest ona w `current_sequence`.
+    fn find_next_sequence(&mut self) -> Option<FsaResult<()>> {
+        // The previous sequence ended at a leaf: its label stayed in the buffer until now.
+        if std::mem::take(&mut self.pending_pop) {
+            self.current_sequence.pop();
+        }
         loop {
             // Peek at the top of the stack to see which node and arc to process.
             // We use `last_mut` to be able to update the `Option<ArcOffset>` (next sibling).
-            let (current_node_on_stack, next_arc_to_try_ref) = match self.stack.last_mut() {
-                Some(top) => (top.0, &mut top.1), // top.0 is Node, top.1 is Option<ArcOffset>
+            let next_arc_to_try_ref = match self.stack.last_mut() {
+                Some(top) => &mut top.1, // top.1 is Option<ArcOffset>
                 None => return None, // Stack is empty, DFS is complete.
             };
 
             let arc_to_process_opt = next_arc_to_try_ref.take(); // Take the arc, leaving None for this node's turn
 
             if let Some(arc) = arc_to_process_opt {
-                // We are processing 'arc' from 'current_node_on_stack'.
+                // We are processing 'arc' from the node on top of the stack.
                 // Before going deeper, put its *next sibling* back onto the stack for later.
                 if let Ok(false) = self.fsa.is_arc_terminal(arc) { // If current arc is not the last from its node
                     if let Ok(next_sibling_arc) = self.fsa.get_next_arc(arc) {
@@ -69,13 +201,14 @@ impl<'a, T: Fsa + ?Sized> ByteSequenceIterator<'a, T> {
                 let label = match self.fsa.get_arc_label(arc) {
                     Ok(l) => l,
                     Err(e) => {
-                        // Error getting label, current path is corrupted.
-                        // Pop the current_sequence label that was *about* to be added or was from parent.
-                        // This state is tricky; best to return error.
+                        // Error getting label, current path is corrupted; best to return error.
                         // The stack will be popped in the next iteration if this was the only arc.
                         return Some(Err(e));
                     }
                 };
+                if self.stop_label == Some(label) {
+                    return Some(Ok(()));
+                }
                 self.current_sequence.push(label);
 
                 let is_final = match self.fsa.is_arc_final(arc) {
@@ -83,45 +216,34 @@ impl<'a, T: Fsa + ?Sized> ByteSequenceIterator<'a, T> {
                     Err(e) => { self.current_sequence.pop(); return Some(Err(e)); }
                 };
 
-                let mut sequence_yielded_for_this_arc: Option<FsaResult<Vec<u8>>> = None;
-                if is_final {
-                    sequence_yielded_for_this_arc = Some(Ok(self.current_sequence.clone()));
-                }
-
                 // Try to go deeper: push children of the current arc's target node to stack.
-                let mut pushed_children = false;
-                match self.fsa.get_end_node(arc) {
-                    Ok(target_node) => {
-                        match self.fsa.get_first_arc(target_node) {
-                            Ok(first_child_arc) => {
-                                self.stack.push((target_node, Some(first_child_arc)));
-                                pushed_children = true;
-                            }
-                            Err(FsaError::InvalidNodeOffset(_)) => { /* Target is a leaf, no children to push */ }
-                            Err(e) => { // Error getting children
-                                self.current_sequence.pop(); // Backtrack label
-                                return sequence_yielded_for_this_arc.or(Some(Err(e)));
-                            }
-                        }
+                let children = self.fsa.get_end_node(arc).and_then(|target_node| {
+                    // A leaf target has no children to push.
+                    Ok(self.fsa.first_arc_opt(target_node)?.map(|first_child_arc| (target_node, first_child_arc)))
+                });
+                let pushed_children = match children {
+                    Ok(Some((target_node, first_child_arc))) => {
+                        self.stack.push((target_node, Some(first_child_arc)));
+                        true
                     }
-                    Err(e) => { // Error getting end node
+                    Ok(None) => false,
+                    Err(e) => {
+                        // The target is neither a leaf nor a valid node: the automaton is corrupt.
                         self.current_sequence.pop(); // Backtrack label
-                        return sequence_yielded_for_this_arc.or(Some(Err(e)));
+                        return Some(Err(e));
                     }
-                }
+                };
 
-                if sequence_yielded_for_this_arc.is_some() {
-                    // If we yielded a sequence, and we are *not* going deeper from this arc's target,
-                    // we need to pop the current label because we are done with this path.
+                if is_final {
+                    // If we are *not* going deeper from this arc's target, the label is popped
+                    // before the next step (the caller borrows the buffer until then).
                     // If we *are* going deeper, the pop will happen when that deeper path is exhausted.
-                    if !pushed_children {
-                        self.current_sequence.pop();
-                    }
-                    return sequence_yielded_for_this_arc;
+                    self.pending_pop = !pushed_children;
+                    return Some(Ok(()));
                 }
 
                 // If not final, and we didn't push children (it's a non-final leaf for this path)
-                if !is_final && !pushed_children {
+                if !pushed_children {
                     self.current_sequence.pop(); // Backtrack this label
                 }
                 // Continue the loop: if children were pushed, they'll be processed.
@@ -137,7 +259,45 @@ impl<'a, T: Fsa + ?Sized> ByteSequenceIterator<'a, T> {
                 }
             }
         }
-        None // Stack is empty
+    }
+}
+
+/// Iterator po sekwencjach bajtów (słowach) w automacie FSA.
+///
+/// Każda sekwencja zwracana jest jako nowy `Vec<u8>`; przy pełnych przeglądach tańszy jest
+/// [`SequenceCursor`] albo [`for_each_sequence`].
+#[derive(Debug)]
+pub struct ByteSequenceIterator<'a, T: Fsa + ?Sized + 'a> {
+    cursor: SequenceCursor<'a, T>,
+}
+
+impl<'a, T: Fsa + ?Sized> ByteSequenceIterator<'a, T> {
+    pub fn new(fsa: &'a T) -> Self {
+        ByteSequenceIterator { cursor: SequenceCursor::new(fsa) }
+    }
+
+    pub fn from_node(fsa: &'a T, start_node_id: Node) -> Self {
+        ByteSequenceIterator { cursor: SequenceCursor::from_node(fsa, start_node_id) }
+    }
+
+    /// Patrz [`SequenceCursor::stop_at`].
+    pub fn stop_at(self, label: u8) -> Self {
+        ByteSequenceIterator { cursor: self.cursor.stop_at(label) }
+    }
+
+    /// Patrz [`SequenceCursor::until`].
+    pub fn until(self, upper: &[u8]) -> Self {
+        ByteSequenceIterator { cursor: self.cursor.until(upper) }
+    }
+
+    /// Patrz [`SequenceCursor::seek`].
+    pub fn seek(&mut self, key: &[u8]) -> FsaResult<()> {
+        self.cursor.seek(key)
+    }
+
+    /// Patrz [`SequenceCursor::seek_after`].
+    pub fn seek_after(&mut self, key: &[u8]) -> FsaResult<()> {
+        self.cursor.seek_after(key)
     }
 }
 
@@ -145,67 +305,48 @@ impl<'a, T: Fsa + ?Sized> Iterator for ByteSequenceIterator<'a, T> {
     type Item = FsaResult<Vec<u8>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.find_next_sequence()
+        Some(self.cursor.advance()?.map(<[u8]>::to_vec))
     }
 }
 
+/// Wywołuje `visitor` dla każdej sekwencji automatu w kolejności łuków, bez alokacji
+/// na sekwencję (patrz [`SequenceCursor::try_for_each`]).
+pub fn for_each_sequence<T, E, F>(fsa: &T, visitor: F) -> Result<(), E>
+where
+    T: Fsa + ?Sized,
+    E: From<FsaError>,
+    F: FnMut(&[u8]) -> Result<(), E>,
+{
+    SequenceCursor::new(fsa).try_for_each(visitor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::fsa5::FSA5; 
-    use crate::fsa_header::{FSA_MAGIC, VERSION_FSA5};
-    use crate::fsa5::{BIT_ARC_FINAL, BIT_ARC_LAST}; 
+    use crate::fsa5::{ARC_FLAGS_BITS, BIT_ARC_FINAL, BIT_ARC_LAST}; 
+    use crate::test_utils::{arc, fsa5_bytes};
     use std::io::Cursor;
     use std::collections::HashSet;
     use std::sync::Arc;
 
     /// Creates FSA for: "a", "ab", "abc", "ax"
-    /// Structure:
-    /// Node 0 (root, offset 0):
-    ///   Arc 'a' (FINAL) -> target Node 1 (offset 3) [LAST arc from Node 0]
-    /// Node 1 (state after "a", offset 3):
-    ///   Arc 'b' (FINAL) -> target Node 2 (offset 9) [NOT LAST from Node 1]
-    ///   Arc 'x' (FINAL) -> target dummy_x_target (101) [LAST arc from Node 1]
-    /// Node 2 (state after "ab", offset 9):
-    ///   Arc 'c' (FINAL) -> target dummy_c_target (100) [LAST arc from Node 2]
+    /// Structure (after the terminal and epsilon nodes at offsets 0 and 2):
+    /// Node 4 (root):
+    ///   Arc 'a' (FINAL) -> target Node 6 [LAST arc from Node 4]
+    /// Node 6 (state after "a"):
+    ///   Arc 'b' (FINAL) -> target Node 10 [NOT LAST from Node 6]
+    ///   Arc 'x' (FINAL) -> leaf (node 0) [LAST arc from Node 6]
+    /// Node 10 (state after "ab"):
+    ///   Arc 'c' (FINAL) -> leaf (node 0) [LAST arc from Node 10]
     fn create_test_fsa_for_iterator() -> FSA5 {
         let mut arcs_data = Vec::new();
-        let dummy_c_target = 100; 
-        let dummy_x_target = 101;
-
-        // Arcs from Node 0 (root, offset 0 in arcs_data)
-        // Arc 'a'
-        arcs_data.push(b'a');
-        arcs_data.push(BIT_ARC_FINAL | BIT_ARC_LAST); // "a" is a word. It's the only arc from Node 0.
-        arcs_data.push(3);                           // Target: Node 1 (offset 3)
-
-        // Arcs from Node 1 (state after "a", starts at offset 3 in arcs_data)
-        // Arc 'b'
-        arcs_data.push(b'b');
-        arcs_data.push(BIT_ARC_FINAL);              // "ab" is a word. NOT LAST from Node 1 (because 'x' follows).
-        arcs_data.push(9);                          // Target: Node 2 (offset 9)
-        // Arc 'x'
-        arcs_data.push(b'x');
-        arcs_data.push(BIT_ARC_FINAL | BIT_ARC_LAST); // "ax" is a word. IS LAST from Node 1.
-        arcs_data.push(dummy_x_target);
-
-        // Arcs from Node 2 (state after "ab", starts at offset 9 in arcs_data)
-        // Arc 'c'
-        arcs_data.push(b'c');
-        arcs_data.push(BIT_ARC_FINAL | BIT_ARC_LAST); // "abc" is a word. IS LAST from Node 2.
-        arcs_data.push(dummy_c_target);
-
-        let mut header_data = Vec::new();
-        header_data.extend_from_slice(&FSA_MAGIC);
-        header_data.push(VERSION_FSA5);
-        header_data.push(1); 
-        header_data.push(1); 
-        header_data.extend_from_slice(&0u16.to_le_bytes()); 
-
-        let mut fsa_file_bytes = header_data;
-        fsa_file_bytes.extend_from_slice(&arcs_data);
-        
-        FSA5::from_reader(&mut Cursor::new(fsa_file_bytes)).unwrap()
+        arcs_data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 6));
+        arcs_data.extend_from_slice(&arc(b'b', BIT_ARC_FINAL, 10));
+        arcs_data.extend_from_slice(&arc(b'x', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+        arcs_data.extend_from_slice(&arc(b'c', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+
+        FSA5::from_reader(&mut Cursor::new(fsa5_bytes(0, &arcs_data))).unwrap()
     }
 
     #[test]
@@ -246,24 +387,14 @@ mod tests {
 
     #[test]
     fn test_iterator_on_fsa_with_only_root_no_arcs() {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC); data.push(VERSION_FSA5);
-        data.push(1); data.push(1); data.extend_from_slice(&0u16.to_le_bytes());
-
-        let fsa = FSA5::from_reader(&mut Cursor::new(data)).unwrap();
+        let fsa = FSA5::from_reader(&mut Cursor::new(fsa5_bytes(0, &[]))).unwrap();
         let mut iterator = ByteSequenceIterator::new(&fsa); 
         assert!(iterator.next().is_none()); 
     }
 
     #[test]
     fn test_iterator_on_fsa_single_node_final_empty_seq() {
-        let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC); data.push(VERSION_FSA5);
-        data.push(1); data.push(1); data.extend_from_slice(&0u16.to_le_bytes());
-        data.push(0); 
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); 
-        data.push(100); 
-
+        let data = fsa5_bytes(0, &arc(0, BIT_ARC_FINAL | BIT_ARC_LAST, 0));
         let fsa = FSA5::from_reader(&mut Cursor::new(data)).unwrap();
         let iterator = ByteSequenceIterator::new(&fsa);
         let results: Vec<_> = iterator.map(|r| r.unwrap()).collect();
@@ -274,27 +405,16 @@ mod tests {
     
     /// Creates FSA for "a" (final) and "b" (final) as separate branches from the root.
     /// Node 0 (root):
-    ///   Arc 'a' (FINAL) -> dummy_target_a [NOT LAST]
-    ///   Arc 'b' (FINAL) -> dummy_target_b [LAST]
+    ///   Arc 'a' (FINAL) -> leaf [NOT LAST]
+    ///   Arc 'b' (FINAL) -> leaf [LAST]
     fn create_fsa_a_b_separate() -> FSA5 {
         let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC); data.push(VERSION_FSA5);
-        data.push(1); data.push(1); data.extend_from_slice(&0u16.to_le_bytes());
-        
-        let dummy_target_a = 100;
-        let dummy_target_b = 101;
-
-        // Arc 'a' (offset 0) from root. Not last, 'b' follows from root.
-        data.push(b'a'); 
-        data.push(BIT_ARC_FINAL); // NOT LAST
-        data.push(dummy_target_a); 
-        
-        // Arc 'b' (offset 3) from root. Last from root.
-        data.push(b'b'); 
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); // IS LAST
-        data.push(dummy_target_b); 
+        // Arc 'a' from root. Not last, 'b' follows from root.
+        data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL, 0));
+        // Arc 'b' from root. Last from root.
+        data.extend_from_slice(&arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
 
-        FSA5::from_reader(&mut Cursor::new(data)).unwrap()
+        FSA5::from_reader(&mut Cursor::new(fsa5_bytes(0, &data))).unwrap()
     }
 
     #[test]
@@ -305,4 +425,118 @@ mod tests {
         let expected: HashSet<Vec<u8>> = [b"a".to_vec(), b"b".to_vec()].iter().cloned().collect();
         assert_eq!(results, expected, "Test: a_b_separate iterator results mismatch");
     }
+
+    fn sorted_words() -> Vec<&'static [u8]> {
+        vec![b"ala", b"alan", b"ale", b"kot", b"kota", b"kotek", b"koty", b"pies", b"psy"]
+    }
+
+    fn collect(iterator: ByteSequenceIterator<'_, FSA5>) -> Vec<Vec<u8>> {
+        iterator.map(|s| s.unwrap()).collect()
+    }
+
+    fn owned(words: &[&[u8]]) -> Vec<Vec<u8>> {
+        words.iter().map(|w| w.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_seek_to_lower_bound() {
+        let fsa = crate::test_utils::trie_fsa(&sorted_words());
+        let mut iterator = ByteSequenceIterator::new(&fsa);
+        iterator.seek(b"kot").unwrap();
+        assert_eq!(collect(iterator), owned(&sorted_words()[3..]));
+
+        // Klucze spoza automatu: między sekwencjami, dłuższe niż ścieżka, przed i za wszystkimi.
+        let cases: [(&[u8], usize); 6] = [(b"kotb", 5), (b"kotekzz", 6), (b"b", 3), (b"", 0), (b"alb", 2), (b"zzz", 9)];
+        for (key, first) in cases {
+            let mut iterator = ByteSequenceIterator::new(&fsa);
+            iterator.seek(key).unwrap();
+            assert_eq!(collect(iterator), owned(&sorted_words()[first..]), "key {:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_range_and_resume() {
+        let fsa = crate::test_utils::trie_fsa(&sorted_words());
+        let mut iterator = ByteSequenceIterator::new(&fsa).until(b"pies");
+        iterator.seek(b"alan").unwrap();
+        assert_eq!(collect(iterator), owned(&sorted_words()[1..7]));
+
+        // Stronicowanie po 2, wznawiane od ostatniej zwróconej sekwencji.
+        let mut pages = Vec::new();
+        let mut last: Option<Vec<u8>> = None;
+        loop {
+            let mut iterator = ByteSequenceIterator::new(&fsa);
+            if let Some(last) = &last {
+                iterator.seek_after(last).unwrap();
+            }
+            let page: Vec<Vec<u8>> = iterator.take(2).map(|s| s.unwrap()).collect();
+            if page.is_empty() {
+                break;
+            }
+            last = page.last().cloned();
+            pages.push(page);
+        }
+        assert_eq!(pages.len(), 5);
+        assert_eq!(pages.concat(), owned(&sorted_words()));
+    }
+
+    #[test]
+    fn test_cursor_reuses_buffer() {
+        let fsa = crate::test_utils::trie_fsa(&sorted_words());
+        let mut cursor = SequenceCursor::new(&fsa);
+        let mut sequences = Vec::new();
+        let mut buffer = None;
+        while let Some(sequence) = cursor.advance() {
+            let sequence = sequence.unwrap();
+            buffer.get_or_insert(sequence.as_ptr());
+            assert_eq!(Some(sequence.as_ptr()), buffer);
+            sequences.push(sequence.to_vec());
+        }
+        assert_eq!(sequences, owned(&sorted_words()));
+        assert!(cursor.advance().is_none());
+
+        let mut cursor = SequenceCursor::new(&fsa).until(b"kotek");
+        cursor.seek_after(b"ale").unwrap();
+        assert_eq!(cursor.advance().unwrap().unwrap(), b"kot");
+        assert_eq!(cursor.advance().unwrap().unwrap(), b"kota");
+        assert!(cursor.advance().is_none());
+    }
+
+    #[test]
+    fn test_for_each_sequence_stops_on_error() {
+        let fsa = crate::test_utils::trie_fsa(&sorted_words());
+        let mut lengths = 0;
+        for_each_sequence(&fsa, |sequence| {
+            lengths += sequence.len();
+            Ok::<(), FsaError>(())
+        })
+        .unwrap();
+        assert_eq!(lengths, sorted_words().iter().map(|w| w.len()).sum::<usize>());
+
+        let mut visited = Vec::new();
+        let result = for_each_sequence(&fsa, |sequence| {
+            if sequence == b"kot" {
+                return Err(FsaError::ParsingError("stop".to_string()));
+            }
+            visited.push(sequence.to_vec());
+            Ok(())
+        });
+        assert!(matches!(result, Err(FsaError::ParsingError(_))));
+        assert_eq!(visited, owned(&sorted_words()[..3]));
+    }
+
+    #[test]
+    fn test_corrupt_target_is_an_error_not_a_leaf() {
+        // "a", "b": oba łuki korzenia prowadzą do węzła 0 (liścia).
+        let mut bytes = crate::test_utils::trie_bytes(&[b"a", b"b"]);
+        let len = bytes.len();
+        // Cel łuku 'b' poza danymi zamiast węzła 0.
+        let flags = u16::from(bytes[len - 2] & 0x07);
+        bytes[len - 2..].copy_from_slice(&((20 << ARC_FLAGS_BITS) | flags).to_le_bytes());
+        let fsa = FSA5::from_bytes(bytes).unwrap();
+
+        let results: Vec<_> = ByteSequenceIterator::new(&fsa).collect();
+        assert_eq!(results[0].as_ref().unwrap(), b"a");
+        assert!(results.iter().any(|r| matches!(r, Err(FsaError::InvalidNodeOffset(20)))));
+    }
 }
@@ -0,0 +1,271 @@
+// Tablice przejść dla węzłów o dużej liczbie łuków
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::{FsaError, Result};
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+
+/// Rozmiar pełnej tablicy przejść (256 adresów `u32`).
+const DENSE_TABLE_SIZE: usize = 256 * 4;
+/// Brak łuku w pełnej tablicy.
+const NO_ARC: u32 = u32::MAX;
+
+/// Parametry budowy [`JumpTables`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JumpTableOptions {
+    /// Najmniejsza liczba łuków węzła, dla której budowany jest indeks.
+    pub min_fan_out: usize,
+    /// Od tej liczby łuków budowana jest pełna tablica 256 adresów (1 KiB) zamiast
+    /// posortowanego indeksu etykiet (5 bajtów na łuk) przeszukiwanego binarnie.
+    pub dense_fan_out: usize,
+    /// Maksymalny łączny rozmiar indeksów w bajtach.
+    pub memory_budget: usize,
+}
+
+impl Default for JumpTableOptions {
+    fn default() -> Self {
+        JumpTableOptions { min_fan_out: 8, dense_fan_out: 48, memory_budget: 1 << 20 }
+    }
+}
+
+/// Indeks łuków jednego węzła.
+#[derive(Debug, Clone)]
+enum NodeTable {
+    /// Adres łuku dla każdej etykiety albo [`NO_ARC`].
+    Dense(Box<[u32; 256]>),
+    /// Etykiety posortowane rosnąco i adresy odpowiadających im łuków.
+    Sorted { labels: Box<[u8]>, arcs: Box<[u32]> },
+}
+
+impl NodeTable {
+    fn find(&self, label: u8) -> Option<ArcOffset> {
+        match self {
+            NodeTable::Dense(arcs) => Some(arcs[label as usize]).filter(|&arc| arc != NO_ARC).map(|arc| arc as usize),
+            NodeTable::Sorted { labels, arcs } => labels.binary_search(&label).ok().map(|i| arcs[i] as usize),
+        }
+    }
+}
+
+/// Warstwa przyspieszająca `get_arc`: indeksy łuków węzłów o dużej liczbie łuków.
+///
+/// Bez indeksu `get_arc` przegląda łuki węzła po kolei, a korzeń i węzły bliskie mu
+/// mają ich w słownikach dziesiątki. Indeksy są budowane raz (np. przy wczytaniu, patrz
+/// [`FSA5::with_jump_tables`](crate::fsa5::FSA5::with_jump_tables)) dla węzłów w kolejności
+/// wszerz od korzenia, dopóki mieści się w [`JumpTableOptions::memory_budget`]; do
+/// budżetu wlicza się też mapa bitowa węzłów z indeksem (z licznikami bitów słów), dzięki
+/// której sprawdzenie pozostałych węzłów kosztuje jeden odczyt bitu, a pozycję indeksu
+/// wyznacza zliczenie bitów zamiast mieszania.
+#[derive(Debug, Clone, Default)]
+pub struct JumpTables {
+    /// Bit `n` ustawiony, jeśli węzeł `n` ma indeks.
+    marks: Vec<u64>,
+    /// Liczba ustawionych bitów w `marks` przed danym słowem.
+    ranks: Vec<u32>,
+    /// Indeksy węzłów w kolejności rosnących numerów węzłów.
+    tables: Vec<NodeTable>,
+    memory_usage: usize,
+}
+
+impl JumpTables {
+    /// Buduje indeksy dla węzłów automatu osiągalnych z korzenia.
+    pub fn build<F: Fsa + ?Sized>(fsa: &F, options: JumpTableOptions) -> Result<Self> {
+        let mut tables = JumpTables::default();
+        let mut built = HashMap::new();
+        let min_fan_out = options.min_fan_out.max(1);
+        let smallest_table = min_fan_out * 5;
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        let root = fsa.get_root_node();
+        visited.insert(root);
+        queue.push_back(root);
+
+        while let Some(node) = queue.pop_front() {
+            if tables.memory_usage + smallest_table > options.memory_budget {
+                break;
+            }
+            let Some(mut arc) = fsa.first_arc_opt(node)? else {
+                continue;
+            };
+            let mut arcs = Vec::new();
+            loop {
+                arcs.push((fsa.get_arc_label(arc)?, arc));
+                let target = fsa.get_end_node(arc)?;
+                if visited.insert(target) {
+                    queue.push_back(target);
+                }
+                if fsa.is_arc_terminal(arc)? {
+                    break;
+                }
+                arc = fsa.get_next_arc(arc)?;
+            }
+            if arcs.len() >= min_fan_out {
+                if let Some(table) = tables.insert(node, arcs, &options) {
+                    built.insert(node, table);
+                }
+            }
+        }
+
+        let mut built: Vec<(Node, NodeTable)> = built.into_iter().collect();
+        built.sort_unstable_by_key(|&(node, _)| node);
+        tables.tables = built.into_iter().map(|(_, table)| table).collect();
+        let mut rank = 0;
+        tables.ranks = tables.marks.iter().map(|word| {
+            let before = rank;
+            rank += word.count_ones();
+            before
+        }).collect();
+        Ok(tables)
+    }
+
+    /// Łuk węzła `node` o etykiecie `label`, jeśli węzeł ma indeks: `Some(Ok(łuk))` albo
+    /// `Some(Err(FsaError::ArcNotFound))`. `None` oznacza brak indeksu dla węzła.
+    #[inline]
+    pub fn lookup(&self, node: Node, label: u8) -> Option<Result<ArcOffset>> {
+        let word = *self.marks.get(node / 64)?;
+        let bit = 1u64 << (node % 64);
+        if word & bit == 0 {
+            return None;
+        }
+        let index = self.ranks[node / 64] as usize + (word & (bit - 1)).count_ones() as usize;
+        Some(self.tables[index].find(label).ok_or(FsaError::ArcNotFound { label, node_offset: node }))
+    }
+
+    /// Węzły o numerach od tej wartości nie mają indeksu.
+    #[inline]
+    pub fn node_limit(&self) -> Node {
+        self.marks.len() * 64
+    }
+
+    /// Liczba węzłów z indeksem.
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Łączny rozmiar indeksów i mapy bitowej w bajtach.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Zwraca indeks węzła, jeśli mieści się w budżecie (i zaznacza węzeł w mapie bitowej);
+    /// pełną tablicę tylko wtedy, gdy węzeł ma dość łuków, a tablica się mieści.
+    fn insert(&mut self, node: Node, mut arcs: Vec<(u8, ArcOffset)>, options: &JumpTableOptions) -> Option<NodeTable> {
+        if arcs.iter().any(|&(_, arc)| arc >= NO_ARC as usize) {
+            return None;
+        }
+        // Słowo mapy bitowej (8 bajtów) i jego licznik (4 bajty).
+        let marks_len = self.marks.len().max(node / 64 + 1);
+        let marks_growth = (marks_len - self.marks.len()) * 12;
+        let available = options.memory_budget.saturating_sub(self.memory_usage + marks_growth);
+
+        let sorted_size = arcs.len() * 5;
+        let table = if arcs.len() >= options.dense_fan_out && DENSE_TABLE_SIZE <= available {
+            let mut dense = Box::new([NO_ARC; 256]);
+            for &(label, arc) in arcs.iter().rev() {
+                dense[label as usize] = arc as u32;
+            }
+            NodeTable::Dense(dense)
+        } else if sorted_size <= available {
+            // Stabilne sortowanie: przy powtórzonej etykiecie wygrywa pierwszy łuk, jak w `get_arc`.
+            arcs.sort_by_key(|&(label, _)| label);
+            arcs.dedup_by_key(|(label, _)| *label);
+            NodeTable::Sorted {
+                labels: arcs.iter().map(|&(label, _)| label).collect(),
+                arcs: arcs.iter().map(|&(_, arc)| arc as u32).collect(),
+            }
+        } else {
+            return None;
+        };
+        self.memory_usage += marks_growth
+            + match &table {
+                NodeTable::Dense(_) => DENSE_TABLE_SIZE,
+                NodeTable::Sorted { .. } => sorted_size,
+            };
+        self.marks.resize(marks_len, 0);
+        self.marks[node / 64] |= 1 << (node % 64);
+        Some(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iterator::ByteSequenceIterator;
+    use crate::test_utils::trie_fsa;
+
+    /// Korzeń z 40 łukami, węzeł po "m" z 10 łukami, pozostałe po jednym.
+    fn wide_fsa() -> crate::fsa5::FSA5 {
+        let mut sequences: Vec<Vec<u8>> = (b'0'..b'0' + 40).map(|label| vec![label, b'x']).collect();
+        sequences.extend((b'a'..b'a' + 10).map(|label| vec![b'm', label]));
+        sequences.sort();
+        let sequences: Vec<&[u8]> = sequences.iter().map(Vec::as_slice).collect();
+        trie_fsa(&sequences)
+    }
+
+    fn assert_same_arcs(fsa: &crate::fsa5::FSA5, tables: &JumpTables) {
+        for node in [fsa.get_root_node(), fsa.get_end_node(fsa.get_arc(fsa.get_root_node(), b'm').unwrap()).unwrap()] {
+            for label in 0..=255u8 {
+                let expected = fsa.get_arc(node, label).ok();
+                let found = tables.lookup(node, label).map(|result| result.ok());
+                assert!(found.is_none() || found == Some(expected), "node {node}, label {label}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dense_and_sorted_tables() {
+        let fsa = wide_fsa();
+        let options = JumpTableOptions { min_fan_out: 8, dense_fan_out: 32, memory_budget: 1 << 20 };
+        let tables = JumpTables::build(&fsa, options).unwrap();
+        assert_eq!(tables.table_count(), 2);
+        let m = fsa.get_end_node(fsa.get_arc(fsa.get_root_node(), b'm').unwrap()).unwrap();
+        assert_eq!(tables.memory_usage(), DENSE_TABLE_SIZE + 10 * 5 + (m / 64 + 1) * 12);
+        let root = fsa.get_root_node();
+        assert!(tables.lookup(root, b'5').is_some_and(|arc| arc.is_ok()));
+        assert!(tables.lookup(root, b'z').is_some_and(|arc| arc.is_err()));
+        assert_same_arcs(&fsa, &tables);
+    }
+
+    #[test]
+    fn test_jump_tables_agree_with_linear_search() {
+        let mut input: Vec<Vec<u8>> = (b'a'..=b'z')
+            .flat_map(|first| (b'a'..=b'z').step_by(first as usize % 3 + 1).map(move |second| vec![first, second, b'!']))
+            .collect();
+        input.sort();
+        let sequences: Vec<&[u8]> = input.iter().map(Vec::as_slice).collect();
+        let plain = trie_fsa(&sequences);
+        let options = JumpTableOptions { min_fan_out: 8, dense_fan_out: 20, memory_budget: 1 << 16 };
+        let fast = trie_fsa(&sequences).with_jump_tables(options).unwrap();
+        assert!(fast.jump_tables().unwrap().table_count() > 1);
+
+        let root = plain.get_root_node();
+        let mut nodes = vec![root];
+        for first in &input {
+            nodes.push(plain.get_end_node(plain.get_arc(root, first[0]).unwrap()).unwrap());
+        }
+        for node in nodes {
+            for label in 0..=255u8 {
+                assert_eq!(fast.get_arc(node, label).ok(), plain.get_arc(node, label).ok());
+            }
+        }
+        let all: Vec<Vec<u8>> = ByteSequenceIterator::new(&fast).map(|s| s.unwrap()).collect();
+        assert_eq!(all, input);
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let fsa = wide_fsa();
+        let dense_budget = JumpTableOptions { memory_budget: DENSE_TABLE_SIZE + 12, dense_fan_out: 32, ..Default::default() };
+        let tables = JumpTables::build(&fsa, dense_budget).unwrap();
+        assert_eq!(tables.table_count(), 1);
+
+        // Za mało na pełną tablicę: korzeń dostaje indeks posortowany.
+        let small = JumpTableOptions { memory_budget: 400, dense_fan_out: 32, ..Default::default() };
+        let tables = JumpTables::build(&fsa, small).unwrap();
+        assert_eq!(tables.table_count(), 2);
+        assert!(tables.memory_usage() <= 400);
+        assert_same_arcs(&fsa, &tables);
+
+        let none = JumpTableOptions { memory_budget: 0, ..Default::default() };
+        assert_eq!(JumpTables::build(&fsa, none).unwrap().table_count(), 0);
+    }
+}
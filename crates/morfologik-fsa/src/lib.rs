@@ -2,9 +2,23 @@ pub mod error;
 pub mod fsa_header;
 pub mod fsa_trait;
 pub mod fsa5;
+pub mod cfsa;
 pub mod cfsa2;
+pub mod loader;
+pub mod any_fsa;
 pub mod traversal;
+pub mod jump_tables;
 pub mod iterator; // Dodajemy nowy moduł
+pub mod perfect_hash;
+pub mod completion;
+pub mod fuzzy;
+pub mod pattern;
+pub mod validation;
+pub mod validated;
+pub mod scanner;
+
+#[cfg(test)]
+mod test_utils;
 
 // Przykładowa funkcja
 pub fn add(left: usize, right: usize) -> usize {
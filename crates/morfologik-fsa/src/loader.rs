@@ -0,0 +1,326 @@
+// Wczytywanie automatów z rozpoznaniem formatu po nagłówku
+
+use std::any::Any;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::any_fsa::AnyFsa;
+use crate::cfsa::CFSA;
+use crate::cfsa2::CFSA2;
+use crate::error::{FsaError, Result};
+use crate::fsa5::FSA5;
+use crate::fsa_header::{FsaHeader, VERSION_CFSA, VERSION_CFSA2, VERSION_FSA5};
+use crate::fsa_trait::Fsa;
+use crate::jump_tables::JumpTableOptions;
+use crate::validation::validate;
+
+/// Automat dowolnego formatu zwracany przez [`FsaRegistry`].
+pub type BoxedFsa = Box<dyn Fsa + Send + Sync>;
+
+/// Tworzy automat z bajtów całego pliku (razem z nagłówkiem).
+pub type FsaFactory = fn(FsaBytes) -> Result<BoxedFsa>;
+
+/// Bajty pliku automatu przekazywane do [`FsaFactory`] formatów dodanych do rejestru.
+///
+/// Opakowuje dowolne źródło (`Vec<u8>`, `&'static [u8]`, mapowanie pamięci), więc
+/// automat może czytać łuki bezpośrednio z niego, np. jako `FSA5<FsaBytes>`. Źródła
+/// `Vec<u8>`, `&'static [u8]` i `Arc<[u8]>` są przechowywane wprost, bez wywołania
+/// wirtualnego przy każdym odczycie; pozostałe przez `Box<dyn AsRef<[u8]>>`.
+pub struct FsaBytes(Source);
+
+enum Source {
+    Owned(Vec<u8>),
+    Static(&'static [u8]),
+    Shared(Arc<[u8]>),
+    Boxed(Box<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl FsaBytes {
+    pub fn new<S: AsRef<[u8]> + Send + Sync + 'static>(bytes: S) -> Self {
+        let any: Box<dyn Any> = Box::new(bytes);
+        let any = match any.downcast::<Vec<u8>>() {
+            Ok(bytes) => return FsaBytes(Source::Owned(*bytes)),
+            Err(any) => any,
+        };
+        let any = match any.downcast::<&'static [u8]>() {
+            Ok(bytes) => return FsaBytes(Source::Static(*bytes)),
+            Err(any) => any,
+        };
+        match any.downcast::<Arc<[u8]>>() {
+            Ok(bytes) => FsaBytes(Source::Shared(*bytes)),
+            Err(any) => {
+                let bytes = any.downcast::<S>().expect("the boxed value has type S");
+                FsaBytes(Source::Boxed(bytes))
+            }
+        }
+    }
+}
+
+impl AsRef<[u8]> for FsaBytes {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        match &self.0 {
+            Source::Owned(bytes) => bytes,
+            Source::Static(bytes) => bytes,
+            Source::Shared(bytes) => bytes,
+            Source::Boxed(bytes) => bytes.as_ref().as_ref(),
+        }
+    }
+}
+
+impl fmt::Debug for FsaBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FsaBytes({} B)", self.as_ref().len())
+    }
+}
+
+/// Sposób odczytu formatu: wbudowane formaty czytają bajty typu źródła bez opakowania.
+#[derive(Clone, Copy)]
+enum FormatReader {
+    Fsa5,
+    Cfsa,
+    Cfsa2,
+    Custom(FsaFactory),
+}
+
+#[derive(Clone)]
+struct Format {
+    version: u8,
+    name: &'static str,
+    reader: FormatReader,
+}
+
+/// Rejestr formatów automatów: wersja z nagłówka → nazwa i sposób odczytu.
+///
+/// [`FsaRegistry::new`] zna FSA5, CFSA (odczyt starszych plików) i CFSA2; kolejne
+/// wersje dodaje się przez [`FsaRegistry::register`]. Funkcje [`open`], [`read`]
+/// i [`from_bytes`] tego modułu korzystają z rejestru domyślnego.
+#[derive(Clone)]
+pub struct FsaRegistry {
+    formats: Vec<Format>,
+    jump_tables: Option<JumpTableOptions>,
+    validate: bool,
+}
+
+impl Default for FsaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FsaRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsaRegistry")
+            .field("formats", &self.formats.iter().map(|format| (format.version, format.name)).collect::<Vec<_>>())
+            .field("jump_tables", &self.jump_tables)
+            .field("validate", &self.validate)
+            .finish()
+    }
+}
+
+impl FsaRegistry {
+    /// Rejestr z wbudowanymi formatami FSA5, CFSA i CFSA2.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.insert(Format { version: VERSION_FSA5, name: "FSA5", reader: FormatReader::Fsa5 });
+        registry.insert(Format { version: VERSION_CFSA, name: "CFSA", reader: FormatReader::Cfsa });
+        registry.insert(Format { version: VERSION_CFSA2, name: "CFSA2", reader: FormatReader::Cfsa2 });
+        registry
+    }
+
+    /// Rejestr bez żadnego formatu.
+    pub fn empty() -> Self {
+        FsaRegistry { formats: Vec::new(), jump_tables: None, validate: false }
+    }
+
+    /// Dodaje format o podanej wersji, zastępując format wcześniej zarejestrowany pod nią.
+    pub fn register(&mut self, version: u8, name: &'static str, factory: FsaFactory) -> &mut Self {
+        self.insert(Format { version, name, reader: FormatReader::Custom(factory) });
+        self
+    }
+
+    /// Buduje [`JumpTables`](crate::jump_tables::JumpTables) dla automatów wbudowanych
+    /// formatów; formaty z [`FsaRegistry::register`] decydują o tym same.
+    pub fn with_jump_tables(mut self, options: JumpTableOptions) -> Self {
+        self.jump_tables = Some(options);
+        self
+    }
+
+    /// Sprawdza każdy wczytany automat funkcją [`validate`] i zwraca
+    /// [`FsaError::CorruptedFsa`] zamiast automatu, w którym wykryła problem.
+    /// Przydatne dla plików z niezaufanych źródeł; walidacja przechodzi cały automat.
+    pub fn with_validation(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Nazwa formatu zarejestrowanego dla wersji.
+    pub fn format_name(&self, version: u8) -> Option<&'static str> {
+        self.format(version).map(|format| format.name)
+    }
+
+    /// Zarejestrowane wersje, rosnąco.
+    pub fn versions(&self) -> impl Iterator<Item = u8> + '_ {
+        self.formats.iter().map(|format| format.version)
+    }
+
+    /// Tworzy automat nad bajtami całego pliku; wbudowane formaty nie kopiują danych.
+    pub fn from_bytes<S>(&self, bytes: S) -> Result<BoxedFsa>
+    where
+        S: AsRef<[u8]> + fmt::Debug + Send + Sync + 'static,
+    {
+        self.load(bytes).map(AnyFsa::into_boxed)
+    }
+
+    /// Jak [`FsaRegistry::from_bytes`], ale automaty FSA5 i CFSA2 zwraca jako warianty
+    /// [`AnyFsa`] wywoływane bez tablicy metod wirtualnych; pozostałe formaty jako
+    /// [`AnyFsa::Other`].
+    pub fn load<S>(&self, bytes: S) -> Result<AnyFsa>
+    where
+        S: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        let bytes = FsaBytes::new(bytes);
+        let version = FsaHeader::read_any_version(&mut Cursor::new(bytes.as_ref()))?.version;
+        let format = self.format(version).ok_or(FsaError::UnsupportedVersion(version))?;
+        let fsa = match (format.reader, self.jump_tables) {
+            (FormatReader::Fsa5, None) => AnyFsa::Fsa5(FSA5::from_bytes(bytes)?),
+            (FormatReader::Fsa5, Some(options)) => AnyFsa::Fsa5(FSA5::from_bytes(bytes)?.with_jump_tables(options)?),
+            (FormatReader::Cfsa, None) => AnyFsa::Other(Box::new(CFSA::from_bytes(bytes)?)),
+            (FormatReader::Cfsa, Some(options)) => AnyFsa::Other(Box::new(CFSA::from_bytes(bytes)?.with_jump_tables(options)?)),
+            (FormatReader::Cfsa2, None) => AnyFsa::Cfsa2(CFSA2::from_bytes(bytes)?),
+            (FormatReader::Cfsa2, Some(options)) => AnyFsa::Cfsa2(CFSA2::from_bytes(bytes)?.with_jump_tables(options)?),
+            (FormatReader::Custom(factory), _) => AnyFsa::Other(factory(bytes)?),
+        };
+        if self.validate {
+            validate(&fsa).into_result()?;
+        }
+        Ok(fsa)
+    }
+
+    /// Wczytuje automat z czytnika (do końca strumienia).
+    pub fn read<R: Read>(&self, reader: &mut R) -> Result<BoxedFsa> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => FsaError::UnexpectedEof,
+            _ => FsaError::Io(e),
+        })?;
+        self.from_bytes(bytes)
+    }
+
+    /// Wczytuje automat z pliku.
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<BoxedFsa> {
+        let file = File::open(path).map_err(FsaError::Io)?;
+        self.read(&mut BufReader::new(file))
+    }
+
+    fn format(&self, version: u8) -> Option<&Format> {
+        self.formats.iter().find(|format| format.version == version)
+    }
+
+    fn insert(&mut self, format: Format) {
+        self.formats.retain(|existing| existing.version != format.version);
+        let position = self.formats.partition_point(|existing| existing.version < format.version);
+        self.formats.insert(position, format);
+    }
+}
+
+/// Wczytuje automat z pliku, rozpoznając format po nagłówku (patrz [`FsaRegistry`]).
+pub fn open<P: AsRef<Path>>(path: P) -> Result<BoxedFsa> {
+    FsaRegistry::new().open(path)
+}
+
+/// Jak [`open`], ale odrzuca automat, w którym walidacja wykryje problem
+/// (patrz [`FsaRegistry::with_validation`]).
+pub fn open_validated<P: AsRef<Path>>(path: P) -> Result<BoxedFsa> {
+    FsaRegistry::new().with_validation().open(path)
+}
+
+/// Wczytuje automat z czytnika, rozpoznając format po nagłówku.
+pub fn read<R: Read>(reader: &mut R) -> Result<BoxedFsa> {
+    FsaRegistry::new().read(reader)
+}
+
+/// Tworzy automat nad bajtami pliku, rozpoznając format po nagłówku.
+pub fn from_bytes<S>(bytes: S) -> Result<BoxedFsa>
+where
+    S: AsRef<[u8]> + fmt::Debug + Send + Sync + 'static,
+{
+    FsaRegistry::new().from_bytes(bytes)
+}
+
+/// Jak [`from_bytes`], ale odrzuca automat, w którym walidacja wykryje problem
+/// (patrz [`FsaRegistry::with_validation`]).
+pub fn from_bytes_validated<S>(bytes: S) -> Result<BoxedFsa>
+where
+    S: AsRef<[u8]> + fmt::Debug + Send + Sync + 'static,
+{
+    FsaRegistry::new().with_validation().from_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfsa2::{CFSA_ARC_IS_FINAL_BIT, CFSA_ARC_IS_LAST_BIT};
+    use crate::fsa_header::FSA_MAGIC;
+    use crate::iterator::ByteSequenceIterator;
+    use crate::test_utils::trie_bytes;
+    use assert_matches::assert_matches;
+
+    fn sequences(fsa: &dyn Fsa) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_detects_builtin_formats() {
+        let bytes = trie_bytes(&[b"ala", b"kot"]);
+        let fsa = from_bytes(bytes.clone()).unwrap();
+        assert_eq!(sequences(fsa.as_ref()), [b"ala".to_vec(), b"kot".to_vec()]);
+        assert_eq!(sequences(read(&mut Cursor::new(&bytes)).unwrap().as_ref()), sequences(fsa.as_ref()));
+
+        let mut cfsa2 = FSA_MAGIC.to_vec();
+        cfsa2.extend_from_slice(&[VERSION_CFSA2, 0, 0, 1, 0]);
+        cfsa2.extend_from_slice(&[CFSA_ARC_IS_LAST_BIT, b'^', 3, CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT, b'a', 0]);
+        let expected = sequences(&CFSA2::from_bytes(cfsa2.clone()).unwrap());
+        assert_eq!(sequences(from_bytes(cfsa2).unwrap().as_ref()), expected);
+
+        let registry = FsaRegistry::new();
+        assert_eq!(registry.versions().collect::<Vec<_>>(), [VERSION_FSA5, VERSION_CFSA, VERSION_CFSA2]);
+        assert_eq!(registry.format_name(VERSION_CFSA2), Some("CFSA2"));
+    }
+
+    #[test]
+    fn test_unknown_version_and_custom_format() {
+        let mut bytes = trie_bytes(&[b"kot"]);
+        bytes[FSA_MAGIC.len()] = 0x42;
+        assert_matches!(from_bytes(bytes.clone()), Err(FsaError::UnsupportedVersion(0x42)));
+        assert_matches!(from_bytes(b"\\fsb".to_vec()), Err(FsaError::InvalidMagic));
+
+        // Format testowy: FSA5 z inną wersją w nagłówku.
+        fn read_renumbered(bytes: FsaBytes) -> Result<BoxedFsa> {
+            let mut bytes = bytes.as_ref().to_vec();
+            bytes[FSA_MAGIC.len()] = VERSION_FSA5;
+            Ok(Box::new(FSA5::from_bytes(bytes)?))
+        }
+        let mut registry = FsaRegistry::new();
+        registry.register(0x42, "FSA5-test", read_renumbered);
+        assert_eq!(registry.format_name(0x42), Some("FSA5-test"));
+        assert_eq!(sequences(registry.from_bytes(bytes).unwrap().as_ref()), [b"kot".to_vec()]);
+
+        assert_matches!(FsaRegistry::empty().from_bytes(trie_bytes(&[b"kot"])), Err(FsaError::UnsupportedVersion(5)));
+    }
+
+    #[test]
+    fn test_validated_loading_rejects_corrupted_automaton() {
+        let bytes = trie_bytes(&[b"ala", b"kot"]);
+        let fsa = from_bytes_validated(bytes.clone()).unwrap();
+        assert_eq!(sequences(fsa.as_ref()), [b"ala".to_vec(), b"kot".to_vec()]);
+
+        // Obcięty automat wczytuje się bez walidacji, ale nie przechodzi jej.
+        let truncated = bytes[..bytes.len() - 2].to_vec();
+        assert!(from_bytes(truncated.clone()).is_ok());
+        assert_matches!(from_bytes_validated(truncated), Err(FsaError::CorruptedFsa(_)));
+    }
+}
@@ -0,0 +1,363 @@
+// Dopasowywanie wzorców (wyrażenia regularne, maski) do języka automatu
+
+use crate::error::{FsaError, Result};
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+use crate::fuzzy::utf8_len;
+
+/// Warunek przejścia automatu wzorca dla jednego znaku.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CharTest {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+}
+
+impl CharTest {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharTest::Char(expected) => *expected == c,
+            CharTest::Any => true,
+            CharTest::Class { ranges, negated } => ranges.iter().any(|&(from, to)| from <= c && c <= to) != *negated,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct PatternState {
+    transitions: Vec<(CharTest, usize)>,
+    epsilon: Vec<usize>,
+}
+
+/// Wzorzec skompilowany do niedeterministycznego automatu nad znakami UTF-8.
+///
+/// Wzorzec musi pasować do całej sekwencji. Obsługiwane wyrażenia regularne to znaki
+/// dosłowne, `.`, klasy `[a-z]` i `[^...]`, operatory `?`, `*`, `+`, alternatywa `|`,
+/// nawiasy oraz `\` przed znakiem specjalnym. W maskach `*` oznacza dowolny ciąg znaków,
+/// `?` dowolny znak, a `[...]` klasę znaków.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    states: Vec<PatternState>,
+    start: usize,
+    accept: usize,
+}
+
+impl Pattern {
+    /// Kompiluje ograniczone wyrażenie regularne.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let mut parser = Parser { chars: pattern.chars().collect(), position: 0, states: Vec::new() };
+        let (start, accept) = parser.alternation()?;
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unexpected ')'"));
+        }
+        Ok(Pattern { states: parser.states, start, accept })
+    }
+
+    /// Kompiluje maskę w stylu powłoki, np. `prze*ać`.
+    pub fn wildcard(pattern: &str) -> Result<Self> {
+        let mut regex = String::with_capacity(pattern.len() * 2);
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                '[' => {
+                    regex.push('[');
+                    for c in chars.by_ref() {
+                        regex.push(c);
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                '.' | '+' | '(' | ')' | '|' | '\\' | ']' => {
+                    regex.push('\\');
+                    regex.push(c);
+                }
+                c => regex.push(c),
+            }
+        }
+        Self::regex(&regex)
+    }
+
+    /// Sprawdza, czy wzorzec pasuje do całej sekwencji bajtów.
+    pub fn is_match(&self, sequence: &[u8]) -> bool {
+        let Ok(text) = std::str::from_utf8(sequence) else {
+            return false;
+        };
+        let mut states = self.closure(vec![self.start]);
+        for c in text.chars() {
+            states = self.step(&states, c);
+            if states.is_empty() {
+                return false;
+            }
+        }
+        states.contains(&self.accept)
+    }
+
+    /// Zwraca sekwencje automatu pasujące do wzorca, w kolejności łuków.
+    ///
+    /// Automat przechodzony jest w głąb razem z automatem wzorca; gałęzie, w których
+    /// zbiór stanów wzorca staje się pusty, są odcinane bez schodzenia głębiej.
+    pub fn find_all<F: Fsa + ?Sized>(&self, fsa: &F) -> Result<Vec<Vec<u8>>> {
+        let mut walk = Walk { path: Vec::new(), matches: Vec::new() };
+        let states = self.closure(vec![self.start]);
+        self.visit(fsa, fsa.get_root_node(), &states, 0, &mut walk)?;
+        Ok(walk.matches)
+    }
+
+    fn visit<F: Fsa + ?Sized>(&self, fsa: &F, node: Node, states: &[usize], unit_start: usize, walk: &mut Walk) -> Result<()> {
+        let Some(mut arc) = fsa.first_arc_opt(node)? else {
+            return Ok(());
+        };
+        loop {
+            walk.path.push(fsa.get_arc_label(arc)?);
+            self.visit_arc(fsa, arc, states, unit_start, walk)?;
+            walk.path.pop();
+            if fsa.is_arc_terminal(arc)? {
+                return Ok(());
+            }
+            arc = fsa.get_next_arc(arc)?;
+        }
+    }
+
+    fn visit_arc<F: Fsa + ?Sized>(&self, fsa: &F, arc: ArcOffset, states: &[usize], unit_start: usize, walk: &mut Walk) -> Result<()> {
+        let pending = &walk.path[unit_start..];
+        if pending.len() < utf8_len(pending[0]) {
+            // Znak nie jest jeszcze kompletny: stan wzorca się nie zmienia.
+            return self.visit(fsa, fsa.get_end_node(arc)?, states, unit_start, walk);
+        }
+        let Some(c) = std::str::from_utf8(pending).ok().and_then(|text| text.chars().next()) else {
+            return Ok(());
+        };
+        let next = self.step(states, c);
+        if next.is_empty() {
+            return Ok(());
+        }
+        if next.contains(&self.accept) && fsa.is_arc_final(arc)? {
+            walk.matches.push(walk.path.clone());
+        }
+        let next_start = walk.path.len();
+        self.visit(fsa, fsa.get_end_node(arc)?, &next, next_start, walk)
+    }
+
+    fn step(&self, states: &[usize], c: char) -> Vec<usize> {
+        let targets = states
+            .iter()
+            .flat_map(|&state| &self.states[state].transitions)
+            .filter(|(test, _)| test.matches(c))
+            .map(|&(_, target)| target)
+            .collect();
+        self.closure(targets)
+    }
+
+    /// Domknięcie zbioru stanów po przejściach pustych (posortowane, bez powtórzeń).
+    fn closure(&self, mut stack: Vec<usize>) -> Vec<usize> {
+        let mut seen = vec![false; self.states.len()];
+        let mut closure = Vec::new();
+        while let Some(state) = stack.pop() {
+            if !std::mem::replace(&mut seen[state], true) {
+                closure.push(state);
+                stack.extend_from_slice(&self.states[state].epsilon);
+            }
+        }
+        closure.sort_unstable();
+        closure
+    }
+}
+
+#[derive(Debug)]
+struct Walk {
+    path: Vec<u8>,
+    matches: Vec<Vec<u8>>,
+}
+
+/// Parser zstępujący budujący automat wzorca konstrukcją Thompsona.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    states: Vec<PatternState>,
+}
+
+/// Fragment automatu: stan początkowy i końcowy.
+type Fragment = (usize, usize);
+
+impl Parser {
+    fn new_state(&mut self) -> usize {
+        self.states.push(PatternState::default());
+        self.states.len() - 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> FsaError {
+        let pattern: String = self.chars.iter().collect();
+        FsaError::ParsingError(format!("invalid pattern '{}' at position {}: {}", pattern, self.position, message))
+    }
+
+    fn alternation(&mut self) -> Result<Fragment> {
+        let first = self.concatenation()?;
+        if self.peek() != Some('|') {
+            return Ok(first);
+        }
+        let (start, end) = (self.new_state(), self.new_state());
+        let mut branch = first;
+        loop {
+            self.states[start].epsilon.push(branch.0);
+            self.states[branch.1].epsilon.push(end);
+            if self.peek() != Some('|') {
+                return Ok((start, end));
+            }
+            self.position += 1;
+            branch = self.concatenation()?;
+        }
+    }
+
+    fn concatenation(&mut self) -> Result<Fragment> {
+        let start = self.new_state();
+        let mut end = start;
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let (item_start, item_end) = self.repetition()?;
+            self.states[end].epsilon.push(item_start);
+            end = item_end;
+        }
+        Ok((start, end))
+    }
+
+    fn repetition(&mut self) -> Result<Fragment> {
+        let mut fragment = self.atom()?;
+        while let Some(operator @ ('*' | '+' | '?')) = self.peek() {
+            self.position += 1;
+            let (start, end) = (self.new_state(), self.new_state());
+            self.states[start].epsilon.push(fragment.0);
+            self.states[fragment.1].epsilon.push(end);
+            if operator != '+' {
+                self.states[start].epsilon.push(end);
+            }
+            if operator != '?' {
+                self.states[fragment.1].epsilon.push(fragment.0);
+            }
+            fragment = (start, end);
+        }
+        Ok(fragment)
+    }
+
+    fn atom(&mut self) -> Result<Fragment> {
+        let test = match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let fragment = self.alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("missing ')'"));
+                }
+                self.position += 1;
+                return Ok(fragment);
+            }
+            Some('[') => {
+                self.position += 1;
+                self.class()?
+            }
+            Some('.') => {
+                self.position += 1;
+                CharTest::Any
+            }
+            Some('*' | '+' | '?') => return Err(self.error("operator without operand")),
+            Some(_) => CharTest::Char(self.literal()?),
+            None => return Err(self.error("unexpected end of pattern")),
+        };
+        let (start, end) = (self.new_state(), self.new_state());
+        self.states[start].transitions.push((test, end));
+        Ok((start, end))
+    }
+
+    fn class(&mut self) -> Result<CharTest> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+        let mut ranges = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("missing ']'")),
+                Some(']') if !ranges.is_empty() => break,
+                _ => {}
+            }
+            let from = self.literal()?;
+            let to = if self.peek() == Some('-') && self.chars.get(self.position + 1).is_some_and(|&c| c != ']') {
+                self.position += 1;
+                self.literal()?
+            } else {
+                from
+            };
+            if to < from {
+                return Err(self.error("reversed character range"));
+            }
+            ranges.push((from, to));
+        }
+        self.position += 1;
+        Ok(CharTest::Class { ranges, negated })
+    }
+
+    fn literal(&mut self) -> Result<char> {
+        let mut c = self.peek().ok_or_else(|| self.error("unexpected end of pattern"))?;
+        if c == '\\' {
+            self.position += 1;
+            c = self.peek().ok_or_else(|| self.error("dangling '\\'"))?;
+        }
+        self.position += 1;
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::trie_fsa;
+    use assert_matches::assert_matches;
+
+    const WORDS: [&str; 8] = ["kot", "kota", "koty", "przeczytać", "przed", "przejechać", "pies", "psy"];
+
+    fn find(pattern: &Pattern) -> Vec<String> {
+        let mut sequences: Vec<&[u8]> = WORDS.iter().map(|w| w.as_bytes()).collect();
+        sequences.sort();
+        let fsa = trie_fsa(&sequences);
+        pattern.find_all(&fsa).unwrap().into_iter().map(|s| String::from_utf8(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_wildcard() {
+        assert_eq!(find(&Pattern::wildcard("prze*ać").unwrap()), ["przeczytać", "przejechać"]);
+        assert_eq!(find(&Pattern::wildcard("kot?").unwrap()), ["kota", "koty"]);
+        assert_eq!(find(&Pattern::wildcard("p[a-j]*").unwrap()), ["pies"]);
+        assert!(find(&Pattern::wildcard("kot.").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(find(&Pattern::regex("(kot|pies)a?").unwrap()), ["kot", "kota", "pies"]);
+        assert_eq!(find(&Pattern::regex("kot[^a]").unwrap()), ["koty"]);
+        assert_eq!(find(&Pattern::regex("p.*s.*").unwrap()), ["pies", "psy"]);
+        assert_eq!(find(&Pattern::regex("prze.+").unwrap()), ["przeczytać", "przed", "przejechać"]);
+        assert_eq!(find(&Pattern::regex(".*ć").unwrap()), ["przeczytać", "przejechać"]);
+    }
+
+    #[test]
+    fn test_is_match() {
+        let pattern = Pattern::regex(r"a(b|c)*\.[0-9]+").unwrap();
+        assert!(pattern.is_match(b"abcb.42"));
+        assert!(pattern.is_match(b"a.0"));
+        assert!(!pattern.is_match(b"a.x"));
+        assert!(!pattern.is_match(b"ab."));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in ["(ab", "ab)", "[a", "*a", "a\\", "[z-a]"] {
+            assert_matches!(Pattern::regex(pattern), Err(FsaError::ParsingError(_)), "{}", pattern);
+        }
+    }
+}
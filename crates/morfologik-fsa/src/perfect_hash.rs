@@ -0,0 +1,159 @@
+// Minimalne doskonałe haszowanie sekwencji (flaga NUMBERS)
+
+use crate::error::{FsaError, Result};
+use crate::fsa_header::FsaFlags;
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+
+/// Minimalna funkcja doskonałego haszowania nad automatem z flagą NUMBERS.
+///
+/// Każda akceptowana sekwencja dostaje numer z przedziału `0..len()` równy jej pozycji
+/// w porządku łuków, czyli w porządku leksykograficznym bajtów dla automatów zbudowanych
+/// z posortowanego wejścia. Odpowiada `FSATraversal.perfectHash` z Javy, uzupełnionemu
+/// o odwzorowanie odwrotne.
+#[derive(Debug)]
+pub struct PerfectHash<'a, F: Fsa + ?Sized> {
+    fsa: &'a F,
+    size: usize,
+}
+
+impl<'a, F: Fsa + ?Sized> PerfectHash<'a, F> {
+    /// Tworzy funkcję haszującą; zwraca [`FsaError::MissingNumbers`], jeśli automat
+    /// nie zapisuje liczby sekwencji w węzłach.
+    pub fn new(fsa: &'a F) -> Result<Self> {
+        if !fsa.get_flags().contains(FsaFlags::NUMBERS) {
+            return Err(FsaError::MissingNumbers);
+        }
+        let mut hash = PerfectHash { fsa, size: 0 };
+        hash.size = hash.right_language_count(fsa.get_root_node())?;
+        Ok(hash)
+    }
+
+    /// Liczba sekwencji akceptowanych przez automat.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Czy automat nie akceptuje żadnej sekwencji.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Zwraca numer sekwencji albo `None`, jeśli automat jej nie akceptuje.
+    pub fn index_of(&self, sequence: &[u8]) -> Result<Option<usize>> {
+        let mut node = self.fsa.get_root_node();
+        let mut index = 0;
+        for (i, &label) in sequence.iter().enumerate() {
+            let Some(mut arc) = self.fsa.first_arc_opt(node)? else {
+                return Ok(None);
+            };
+            // Sekwencje wychodzące przez wcześniejsze łuki mają mniejsze numery.
+            while self.fsa.get_arc_label(arc)? != label {
+                index += self.arc_count(arc)?;
+                if self.fsa.is_arc_terminal(arc)? {
+                    return Ok(None);
+                }
+                arc = self.fsa.get_next_arc(arc)?;
+            }
+            let is_final = self.fsa.is_arc_final(arc)?;
+            if i + 1 == sequence.len() {
+                return Ok(is_final.then_some(index));
+            }
+            // Przedrostek kończący się na tym łuku poprzedza swoje przedłużenia.
+            if is_final {
+                index += 1;
+            }
+            node = self.fsa.get_end_node(arc)?;
+        }
+        Ok(None)
+    }
+
+    /// Zwraca sekwencję o podanym numerze (odwrotność [`PerfectHash::index_of`]).
+    pub fn sequence_at(&self, index: usize) -> Result<Vec<u8>> {
+        if index >= self.size {
+            return Err(FsaError::IndexOutOfRange { index, count: self.size });
+        }
+        let mut remaining = index;
+        let mut node = self.fsa.get_root_node();
+        let mut sequence = Vec::new();
+        loop {
+            let mut arc = self.fsa.first_arc_opt(node)?.ok_or_else(|| corrupted(index))?;
+            loop {
+                let count = self.arc_count(arc)?;
+                if remaining < count {
+                    break;
+                }
+                remaining -= count;
+                if self.fsa.is_arc_terminal(arc)? {
+                    return Err(corrupted(index));
+                }
+                arc = self.fsa.get_next_arc(arc)?;
+            }
+            sequence.push(self.fsa.get_arc_label(arc)?);
+            if self.fsa.is_arc_final(arc)? {
+                if remaining == 0 {
+                    return Ok(sequence);
+                }
+                remaining -= 1;
+            }
+            node = self.fsa.get_end_node(arc)?;
+        }
+    }
+
+    /// Liczba sekwencji przechodzących przez łuk.
+    fn arc_count(&self, arc: ArcOffset) -> Result<usize> {
+        let own = usize::from(self.fsa.is_arc_final(arc)?);
+        Ok(own + self.right_language_count(self.fsa.get_end_node(arc)?)?)
+    }
+
+    /// Liczba sekwencji z węzła; węzły bez łuków nie mają zapisanego licznika.
+    fn right_language_count(&self, node: Node) -> Result<usize> {
+        match self.fsa.first_arc_opt(node)? {
+            Some(_) => self.fsa.get_right_language_count(node),
+            None => Ok(0),
+        }
+    }
+
+}
+
+fn corrupted(index: usize) -> FsaError {
+    FsaError::CorruptedFsa(format!("Right language counts do not lead to sequence {}", index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsa5::{FSA5, BIT_ARC_FINAL, BIT_ARC_LAST};
+    use crate::test_utils::{arc, fsa5_bytes};
+    use assert_matches::assert_matches;
+
+    /// Automat FSA5 z NUMBERS dla sekwencji "a", "ab", "b".
+    fn create_numbered_fsa() -> FSA5 {
+        let mut data = vec![3]; // korzeń (6): "a", "ab", "b"
+        data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL, 11));
+        data.extend_from_slice(&arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+        data.push(1); // węzeł 11: "b"
+        data.extend_from_slice(&arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+        FSA5::from_bytes(fsa5_bytes(1, &data)).unwrap()
+    }
+
+    #[test]
+    fn test_index_of_and_sequence_at() {
+        let fsa = create_numbered_fsa();
+        let hash = PerfectHash::new(&fsa).unwrap();
+        assert_eq!(hash.len(), 3);
+        for (index, sequence) in [&b"a"[..], b"ab", b"b"].iter().enumerate() {
+            assert_eq!(hash.index_of(sequence).unwrap(), Some(index));
+            assert_eq!(hash.sequence_at(index).unwrap(), sequence.to_vec());
+        }
+        assert_eq!(hash.index_of(b"").unwrap(), None);
+        assert_eq!(hash.index_of(b"abc").unwrap(), None);
+        assert_eq!(hash.index_of(b"c").unwrap(), None);
+        assert_matches!(hash.sequence_at(3), Err(FsaError::IndexOutOfRange { index: 3, count: 3 }));
+    }
+
+    #[test]
+    fn test_requires_numbers() {
+        let fsa = FSA5::from_bytes(fsa5_bytes(0, &arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 0))).unwrap();
+        assert_matches!(PerfectHash::new(&fsa), Err(FsaError::MissingNumbers));
+    }
+}
@@ -0,0 +1,162 @@
+// Wyszukiwanie fraz (wyrażeń wielowyrazowych) w tekście
+
+use std::ops::Range;
+
+use crate::error::{FsaError, Result};
+use crate::fsa_trait::{Fsa, Node};
+
+/// Które dopasowania zwraca [`Scanner::find`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanMode {
+    /// Wszystkie dopasowania, także nakładające się i zawarte w dłuższych.
+    All,
+    /// Najdłuższe dopasowanie od najbardziej lewej pozycji; skanowanie wznawiane jest
+    /// za jego końcem, więc dopasowania się nie nakładają.
+    #[default]
+    LeftmostLongest,
+}
+
+/// Parametry skanowania tekstu.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    pub mode: ScanMode,
+    /// Dopasowanie musi zaczynać się i kończyć na granicy tokenu (patrz [`is_word_byte`]).
+    pub token_boundaries: bool,
+}
+
+/// Fragment tekstu `start..end` (offsety w bajtach) będący sekwencją automatu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScanMatch {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ScanMatch {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Czy bajt należy do słowa: litery i cyfry ASCII, `_` oraz wszystkie bajty znaków
+/// spoza ASCII w UTF-8 (m.in. polskie litery diakrytyzowane).
+pub fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Wyszukuje w tekście fragmenty akceptowane przez automat fraz.
+///
+/// Z każdej pozycji startowej tekst przechodzony jest po łukach automatu, dopóki istnieje
+/// łuk dla kolejnego bajtu; każdy łuk końcowy wyznacza dopasowanie. Działa przez trait
+/// [`Fsa`], więc dla FSA5 i CFSA2 tak samo.
+#[derive(Debug)]
+pub struct Scanner<'a, F: Fsa + ?Sized> {
+    fsa: &'a F,
+    options: ScanOptions,
+}
+
+impl<'a, F: Fsa + ?Sized> Scanner<'a, F> {
+    pub fn new(fsa: &'a F, options: ScanOptions) -> Self {
+        Scanner { fsa, options }
+    }
+
+    /// Zwraca dopasowania posortowane według początku, a potem końca.
+    pub fn find(&self, text: &[u8]) -> Result<Vec<ScanMatch>> {
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start < text.len() {
+            if !self.options.token_boundaries || is_boundary(text, start) {
+                let before = matches.len();
+                self.match_from(text, start, &mut matches)?;
+                if self.options.mode == ScanMode::LeftmostLongest && matches.len() > before {
+                    let longest = matches[matches.len() - 1];
+                    matches.truncate(before);
+                    matches.push(longest);
+                    start = longest.end;
+                    continue;
+                }
+            }
+            start += 1;
+        }
+        Ok(matches)
+    }
+
+    /// Dopisuje dopasowania zaczynające się w `start`, w kolejności rosnącego końca.
+    fn match_from(&self, text: &[u8], start: usize, matches: &mut Vec<ScanMatch>) -> Result<()> {
+        let mut node: Node = self.fsa.get_root_node();
+        for (end, &byte) in text.iter().enumerate().skip(start).map(|(i, b)| (i + 1, b)) {
+            let arc = match self.fsa.get_arc(node, byte) {
+                Ok(arc) => arc,
+                // Brak łuku albo węzeł bez łuków: z tej pozycji nie ma dłuższych dopasowań.
+                Err(FsaError::ArcNotFound { .. }) => break,
+                Err(e) => return Err(e),
+            };
+            if self.fsa.is_arc_final(arc)? && (!self.options.token_boundaries || is_boundary(text, end)) {
+                matches.push(ScanMatch { start, end });
+            }
+            node = self.fsa.get_end_node(arc)?;
+        }
+        Ok(())
+    }
+}
+
+/// Granica tokenu: początek lub koniec tekstu albo pozycja, po której obu stronach nie
+/// stoją bajty słowa.
+fn is_boundary(text: &[u8], position: usize) -> bool {
+    position == 0
+        || position == text.len()
+        || !is_word_byte(text[position - 1])
+        || !is_word_byte(text[position])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::trie_fsa;
+
+    fn find<'t>(fsa: &crate::fsa5::FSA5, text: &'t str, mode: ScanMode, token_boundaries: bool) -> Vec<&'t str> {
+        let scanner = Scanner::new(fsa, ScanOptions { mode, token_boundaries });
+        scanner.find(text.as_bytes()).unwrap().into_iter().map(|m| &text[m.range()]).collect()
+    }
+
+    #[test]
+    fn test_leftmost_longest_and_all() {
+        let fsa = trie_fsa(&[b"New York", b"New York City", b"York", b"ork"]);
+        let text = "I love New York City and York.";
+        assert_eq!(find(&fsa, text, ScanMode::LeftmostLongest, false), ["New York City", "York"]);
+        assert_eq!(
+            find(&fsa, text, ScanMode::All, false),
+            ["New York", "New York City", "York", "ork", "York", "ork"]
+        );
+    }
+
+    #[test]
+    fn test_token_boundaries() {
+        let fsa = trie_fsa(&[b"kot", b"kot w butach"]);
+        let text = "Kotek i kotwica; kot w butach, kot.";
+        assert_eq!(find(&fsa, text, ScanMode::All, false), ["kot", "kot", "kot w butach", "kot"]);
+        assert_eq!(find(&fsa, text, ScanMode::All, true), ["kot", "kot w butach", "kot"]);
+        assert_eq!(find(&fsa, text, ScanMode::LeftmostLongest, true), ["kot w butach", "kot"]);
+    }
+
+    #[test]
+    fn test_scanner_finds_phrases() {
+        let fsa = trie_fsa(&[b"kot", b"kot w butach", b"pies"]);
+        let options = ScanOptions { token_boundaries: true, ..Default::default() };
+        let matches = Scanner::new(&fsa, options).find(b"kot w butach i pies").unwrap();
+        assert_eq!(matches, [ScanMatch { start: 0, end: 12 }, ScanMatch { start: 15, end: 19 }]);
+    }
+
+    #[test]
+    fn test_offsets_in_utf8_text() {
+        let mut phrases: Vec<&[u8]> = vec!["Łódź".as_bytes(), "Łódź Kaliska".as_bytes()];
+        phrases.sort();
+        let fsa = trie_fsa(&phrases);
+        let text = "Z Łodzi? Nie, z Łódź Kaliska.";
+        let scanner = Scanner::new(&fsa, ScanOptions { token_boundaries: true, ..Default::default() });
+        let matches = scanner.find(text.as_bytes()).unwrap();
+        let start = text.find("Łódź").unwrap();
+        assert_eq!(matches, [ScanMatch { start, end: start + "Łódź Kaliska".len() }]);
+        // "Łódź" w "Łódźka" nie kończy się na granicy tokenu.
+        assert!(scanner.find("Łódźka".as_bytes()).unwrap().is_empty());
+    }
+}
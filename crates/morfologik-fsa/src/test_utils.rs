@@ -0,0 +1,81 @@
+// Pomocnicze funkcje dla testów jednostkowych
+
+use crate::fsa5::{FSA5, ARC_FLAGS_BITS, BIT_ARC_FINAL, BIT_ARC_LAST, BIT_ARC_TARGET_NEXT};
+use crate::fsa_header::{FSA_MAGIC, VERSION_FSA5};
+
+/// Pusty węzeł stanu końcowego (łuk z 2-bajtowym adresem) i łuk epsilon do korzenia.
+const PROLOGUE: [u8; 5] = [0, 0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT];
+
+/// Zapisuje drzewo (bez minimalizacji) dla posortowanych sekwencji; łuki do liści mają
+/// adres 0, jak w `FSA5Serializer.java`.
+fn write_node(sequences: &[&[u8]], depth: usize, out: &mut Vec<u8>) {
+    let mut groups: Vec<(u8, Vec<&[u8]>)> = Vec::new();
+    for &sequence in sequences.iter().filter(|s| s.len() > depth) {
+        match groups.last_mut() {
+            Some((label, group)) if *label == sequence[depth] => group.push(sequence),
+            _ => groups.push((sequence[depth], vec![sequence])),
+        }
+    }
+    let start = out.len();
+    for (i, (label, group)) in groups.iter().enumerate() {
+        let mut flags = 0;
+        if group.iter().any(|s| s.len() == depth + 1) {
+            flags |= BIT_ARC_FINAL;
+        }
+        if i + 1 == groups.len() {
+            flags |= BIT_ARC_LAST;
+        }
+        out.extend_from_slice(&[*label, flags, 0]);
+    }
+    for (i, (_, group)) in groups.iter().enumerate() {
+        if group.iter().any(|s| s.len() > depth + 1) {
+            let address = start + i * 3 + 1;
+            let value = u16::from(out[address]) | ((out.len() as u16) << ARC_FLAGS_BITS);
+            out[address..address + 2].copy_from_slice(&value.to_le_bytes());
+            write_node(group, depth + 1, out);
+        }
+    }
+}
+
+/// Automat FSA5 (drzewo z 2-bajtowymi adresami) akceptujący posortowane `sequences`.
+pub(crate) fn trie_fsa(sequences: &[&[u8]]) -> FSA5 {
+    FSA5::from_bytes(trie_bytes(sequences)).unwrap()
+}
+
+/// Bajty pliku FSA5 zwracanego przez [`trie_fsa`]; korzeń leży pod adresem 5.
+pub(crate) fn trie_bytes(sequences: &[&[u8]]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&FSA_MAGIC);
+    data.extend_from_slice(&[VERSION_FSA5, b'_', b'+', 0x02]);
+    let mut arcs = PROLOGUE.to_vec();
+    write_node(sequences, 0, &mut arcs);
+    data.extend_from_slice(&arcs);
+    data
+}
+
+/// Bajty pliku FSA5 (gtl = 1) z łukami `arcs` zapisanymi za pustym węzłem stanu końcowego
+/// i węzłem epsilon; korzeń zaczyna się pod adresem [`fsa5_root`]. Przy niezerowym
+/// `node_data_length` `arcs` zawiera też liczby sekwencji węzłów.
+pub(crate) fn fsa5_bytes(node_data_length: u8, arcs: &[u8]) -> Vec<u8> {
+    let mut data = FSA_MAGIC.to_vec();
+    data.extend_from_slice(&[VERSION_FSA5, b'_', b'+', (node_data_length << 4) | 1]);
+    let node_data = vec![0; node_data_length as usize];
+    data.extend_from_slice(&node_data);
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&node_data);
+    let epsilon = if arcs.is_empty() { BIT_ARC_LAST } else { BIT_ARC_LAST | BIT_ARC_TARGET_NEXT };
+    data.extend_from_slice(&[b'^', epsilon]);
+    data.extend_from_slice(arcs);
+    data
+}
+
+/// Adres korzenia w automacie z [`fsa5_bytes`].
+pub(crate) fn fsa5_root(node_data_length: u8) -> usize {
+    2 * (node_data_length as usize + 2)
+}
+
+/// Łuk z jednobajtowym adresem (gtl = 1): etykieta i `(target << 3) | flags`.
+pub(crate) fn arc(label: u8, flags: u8, target: usize) -> [u8; 2] {
+    assert!(target < 32, "target {} does not fit in one byte", target);
+    [label, ((target as u8) << ARC_FLAGS_BITS) | flags]
+}
@@ -96,20 +96,7 @@ impl<'a, T: Fsa + ?Sized> FsaTraversal<'a, T> {
             return Ok(());
         }
 
-        let mut current_arc_opt = match self.fsa.get_first_arc(start_node) {
-            Ok(arc) => {
-                // println!("[DFS GET_FIRST_ARC] Node: {}, First Arc: {}", start_node, arc);
-                Some(arc)
-            },
-            Err(FsaError::InvalidNodeOffset(_)) => {
-                // println!("[DFS GET_FIRST_ARC] Node: {}, No arcs or invalid node.", start_node);
-                None
-            }, 
-            Err(e) => {
-                // eprintln!("[DFS GET_FIRST_ARC] Node: {}, Error: {:?}", start_node, e);
-                return Err(e);
-            }
-        };
+        let mut current_arc_opt = self.fsa.first_arc_opt(start_node)?;
 
         while let Some(arc) = current_arc_opt {
             // println!("[DFS LOOP_TOP] Node: {}, Processing Arc: {}", start_node, arc);
@@ -166,32 +153,18 @@ mod tests {
     use super::*;
     use crate::fsa5::FSA5;
     use crate::fsa5::{BIT_ARC_FINAL, BIT_ARC_LAST};
-    use crate::fsa_header::{FSA_MAGIC, VERSION_FSA5};
+    use crate::test_utils::{arc, fsa5_bytes};
     use std::io::Cursor;
 
     use assert_matches::assert_matches;
 
+    /// "a", "ab", "abc": węzły 4, 6 i 8 (za węzłem stanu końcowego i epsilon), ostatni łuk do liścia.
     fn create_linear_abc_fsa_data() -> Vec<u8> {
         let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_FSA5);
-        data.push(1); 
-        data.push(1); 
-        data.extend_from_slice(&0u16.to_le_bytes()); 
-
-        data.push(b'a');
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); 
-        data.push(3);                            
-
-        data.push(b'b');
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); 
-        data.push(6);                            
-
-        data.push(b'c');
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); 
-        data.push(9);                            
-        
-        data
+        data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 6));
+        data.extend_from_slice(&arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 8));
+        data.extend_from_slice(&arc(b'c', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+        fsa5_bytes(0, &data)
     }
 
     #[test]
@@ -203,17 +176,17 @@ mod tests {
         let result_a = traversal.perfect_match(b"a").unwrap();
         assert_eq!(result_a.kind, MatchKind::ExactMatch);
         assert_eq!(result_a.index, 1);
-        assert_eq!(result_a.node, 3);
+        assert_eq!(result_a.node, 6);
 
         let result_ab = traversal.perfect_match(b"ab").unwrap();
         assert_eq!(result_ab.kind, MatchKind::ExactMatch);
         assert_eq!(result_ab.index, 2);
-        assert_eq!(result_ab.node, 6);
+        assert_eq!(result_ab.node, 8);
 
         let result_abc = traversal.perfect_match(b"abc").unwrap();
         assert_eq!(result_abc.kind, MatchKind::ExactMatch);
         assert_eq!(result_abc.index, 3);
-        assert_eq!(result_abc.node, 9);
+        assert_eq!(result_abc.node, 0);
     }
 
     #[test]
@@ -225,7 +198,7 @@ mod tests {
         let result_ax = traversal.perfect_match(b"ax").unwrap();
         assert_eq!(result_ax.kind, MatchKind::NoMatch, "Test 'ax'");
         assert_eq!(result_ax.index, 1); 
-        assert_eq!(result_ax.node, 3);  
+        assert_eq!(result_ax.node, 6);  
 
         let result_b = traversal.perfect_match(b"b").unwrap();
         assert_eq!(result_b.kind, MatchKind::NoMatch, "Test 'b'");
@@ -235,19 +208,17 @@ mod tests {
         let result_abd = traversal.perfect_match(b"abd").unwrap();
         assert_eq!(result_abd.kind, MatchKind::NoMatch, "Test 'abd'");
         assert_eq!(result_abd.index, 2); 
-        assert_eq!(result_abd.node, 6);  
+        assert_eq!(result_abd.node, 8);  
     }
     
     #[test]
     fn test_perfect_match_sequence_is_prefix_linear() {
         let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC); data.push(VERSION_FSA5);
-        data.push(1); data.push(1); data.extend_from_slice(&0u16.to_le_bytes());
-        data.push(b'a'); data.push(BIT_ARC_FINAL | BIT_ARC_LAST); data.push(3);
-        data.push(b'b'); data.push(BIT_ARC_LAST); data.push(6); 
-        data.push(b'c'); data.push(BIT_ARC_FINAL | BIT_ARC_LAST); data.push(9);
+        data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 6));
+        data.extend_from_slice(&arc(b'b', BIT_ARC_LAST, 8));
+        data.extend_from_slice(&arc(b'c', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
         
-        let fsa_bytes = data;
+        let fsa_bytes = fsa5_bytes(0, &data);
         let fsa = FSA5::from_reader(&mut Cursor::new(fsa_bytes)).unwrap();
         let traversal = FsaTraversal::new(&fsa);
 
@@ -257,7 +228,7 @@ mod tests {
         let result_ab = traversal.perfect_match(b"ab").unwrap();
         assert_eq!(result_ab.kind, MatchKind::SequenceIsAPrefix);
         assert_eq!(result_ab.index, 2);
-        assert_eq!(result_ab.node, 6);
+        assert_eq!(result_ab.node, 8);
 
         let result_abc = traversal.perfect_match(b"abc").unwrap();
         assert_eq!(result_abc.kind, MatchKind::ExactMatch);
@@ -306,7 +277,7 @@ mod tests {
 
         traversal.visit_dfs(fsa.get_root_node(), &mut path, &mut visitor).unwrap();
 
-        assert_eq!(visitor.visited_nodes, vec![0, 3, 6, 9]); 
+        assert_eq!(visitor.visited_nodes, vec![4, 6, 8, 0]); 
         assert_eq!(visitor.accepted_arcs_labels, vec![b'a', b'b', b'c']);
     }
 
@@ -319,13 +290,13 @@ mod tests {
         let mut visitor = TestVisitor {
             visited_nodes: Vec::new(),
             accepted_arcs_labels: Vec::new(),
-            stop_at_node: Some(3), 
+            stop_at_node: Some(6), 
             skip_arc_label: None,
         };
         let mut path = Vec::new();
         traversal.visit_dfs(fsa.get_root_node(), &mut path, &mut visitor).unwrap();
 
-        assert_eq!(visitor.visited_nodes, vec![0, 3]);
+        assert_eq!(visitor.visited_nodes, vec![4, 6]);
         assert_eq!(visitor.accepted_arcs_labels, vec![b'a']); 
     }
 
@@ -344,7 +315,7 @@ mod tests {
         let mut path = Vec::new();
         traversal.visit_dfs(fsa.get_root_node(), &mut path, &mut visitor).unwrap();
         
-        assert_eq!(visitor.visited_nodes, vec![0, 3]); 
+        assert_eq!(visitor.visited_nodes, vec![4, 6]); 
         assert_eq!(visitor.accepted_arcs_labels, vec![b'a']);
     }
 
@@ -362,20 +333,9 @@ mod tests {
 
     fn create_empty_and_a_fsa_data() -> Vec<u8> {
         let mut data = Vec::new();
-        data.extend_from_slice(&FSA_MAGIC);
-        data.push(VERSION_FSA5);
-        data.push(1);
-        data.push(1);
-        data.extend_from_slice(&0u16.to_le_bytes());
-
-        data.push(0); 
-        data.push(BIT_ARC_FINAL); 
-        data.push(0); 
-
-        data.push(b'a'); 
-        data.push(BIT_ARC_FINAL | BIT_ARC_LAST); 
-        data.push(0);
-        data
+        data.extend_from_slice(&arc(0, BIT_ARC_FINAL, 0));
+        data.extend_from_slice(&arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 0));
+        fsa5_bytes(0, &data)
     }
 
     #[test]
@@ -0,0 +1,346 @@
+// Automat po jednorazowej walidacji z nawigacją bez sprawdzania błędów
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::error::{FsaError, Result};
+use crate::fsa_header::FsaFlags;
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+use crate::jump_tables::{JumpTableOptions, JumpTables};
+use crate::validation::validate;
+
+const ARC_FINAL: u8 = 0x01;
+const ARC_LAST: u8 = 0x02;
+
+/// Zdekodowany łuk: indeks węzła docelowego, etykieta i flagi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PackedArc {
+    target: u32,
+    label: u8,
+    flags: u8,
+}
+
+/// Automat sprawdzony przez [`validate`] i zdekodowany do płaskich tablic.
+///
+/// Węzły są numerowane kolejno od korzenia (indeks 0), a łuki każdego węzła leżą obok
+/// siebie posortowane według etykiet. Wszystkie węzły bez łuków są jednym węzłem o
+/// indeksie [`ValidatedFsa::node_count`]. Metody nawigacji ([`find_arc`](Self::find_arc),
+/// [`target`](Self::target), [`walk`](Self::walk) itd.) nie zwracają `Result`: poprawność
+/// automatu sprawdzono raz, przy tworzeniu. Dla indeksów spoza automatu panikują.
+///
+/// Węzły o dużej liczbie łuków dostają też [`JumpTables`] (z [`JumpTableOptions::default`],
+/// zmiana przez [`with_jump_tables`](Self::with_jump_tables)), z których `find_arc`
+/// korzysta przed wyszukiwaniem binarnym.
+///
+/// Struktura implementuje też [`Fsa`], więc działa z iteratorami i pozostałymi
+/// algorytmami; węzły i łuki to wtedy indeksy, a nie offsety w danych źródłowych.
+/// Kosztem jest kopia automatu w pamięci (8 bajtów na łuk i 4 na węzeł).
+#[derive(Debug, Clone)]
+pub struct ValidatedFsa {
+    flags: FsaFlags,
+    /// Łuki węzła `n` to `arcs[first_arcs[n]..first_arcs[n + 1]]`; ostatni wpis
+    /// powtórzony, by węzeł bez łuków miał pusty zakres.
+    first_arcs: Vec<u32>,
+    arcs: Vec<PackedArc>,
+    /// Liczby sekwencji węzłów (tylko z flagą NUMBERS).
+    counts: Vec<usize>,
+    /// Indeksy łuków węzłów o dużej liczbie łuków (patrz [`ValidatedFsa::with_jump_tables`]).
+    jump_tables: Option<JumpTables>,
+    /// [`JumpTables::node_limit`]: węzły numerowane wszerz od korzenia, więc indeksy mają
+    /// tylko węzły o małych numerach, a pozostałe omijają je jednym porównaniem.
+    jump_limit: usize,
+}
+
+impl ValidatedFsa {
+    /// Waliduje automat i dekoduje jego osiągalną część.
+    ///
+    /// Zwraca [`FsaError::CorruptedFsa`] z opisem pierwszego problemu, jeśli walidacja
+    /// go wykryje.
+    pub fn new<F: Fsa + ?Sized>(fsa: &F) -> Result<Self> {
+        let report = validate(fsa).into_result()?;
+        let too_large = || FsaError::CorruptedFsa("automaton too large for 32-bit indices".to_string());
+        let leaf = u32::try_from(report.nodes).map_err(|_| too_large())?;
+        u32::try_from(report.arcs).map_err(|_| too_large())?;
+
+        let numbers = fsa.get_flags().contains(FsaFlags::NUMBERS);
+        let mut decoded = ValidatedFsa {
+            flags: fsa.get_flags(),
+            first_arcs: Vec::with_capacity(report.nodes + 2),
+            arcs: Vec::with_capacity(report.arcs),
+            counts: Vec::new(),
+            jump_tables: None,
+            jump_limit: 0,
+        };
+        // Węzły w kolejności wykrycia (wszerz); indeks węzła to jego pozycja w `queue`.
+        let mut indices: HashMap<Node, u32> = HashMap::new();
+        let mut queue: Vec<Node> = Vec::with_capacity(report.nodes);
+        let root = fsa.get_root_node();
+        if fsa.first_arc_opt(root)?.is_some() {
+            indices.insert(root, 0);
+            queue.push(root);
+        }
+
+        let mut next = 0;
+        while let Some(&node) = queue.get(next) {
+            next += 1;
+            decoded.first_arcs.push(decoded.arcs.len() as u32);
+            if numbers {
+                decoded.counts.push(fsa.get_right_language_count(node)?);
+            }
+            let mut arc = fsa.get_first_arc(node)?;
+            loop {
+                let target = fsa.get_end_node(arc)?;
+                let target = match indices.get(&target) {
+                    Some(&index) => index,
+                    None if fsa.first_arc_opt(target)?.is_none() => leaf,
+                    None => {
+                        let index = queue.len() as u32;
+                        indices.insert(target, index);
+                        queue.push(target);
+                        index
+                    }
+                };
+                let flags = if fsa.is_arc_final(arc)? { ARC_FINAL } else { 0 };
+                decoded.arcs.push(PackedArc { target, label: fsa.get_arc_label(arc)?, flags });
+                if fsa.is_arc_terminal(arc)? {
+                    break;
+                }
+                arc = fsa.get_next_arc(arc)?;
+            }
+            // Źródło nie musi trzymać łuków w kolejności etykiet; po sortowaniu flaga LAST
+            // trafia na ostatni łuk zakresu. Etykiety są unikalne (sprawdziła to walidacja).
+            let start = *decoded.first_arcs.last().unwrap() as usize;
+            let node_arcs = &mut decoded.arcs[start..];
+            node_arcs.sort_unstable_by_key(|arc| arc.label);
+            node_arcs.last_mut().unwrap().flags |= ARC_LAST;
+        }
+        // Zakres łuków ostatniego węzła i pusty zakres węzła bez łuków.
+        decoded.first_arcs.push(decoded.arcs.len() as u32);
+        decoded.first_arcs.push(decoded.arcs.len() as u32);
+        if numbers {
+            decoded.counts.push(0);
+        }
+        let defaults = JumpTableOptions::default();
+        decoded.with_jump_tables(JumpTableOptions { min_fan_out: defaults.dense_fan_out, ..defaults })
+    }
+
+    /// Buduje na nowo indeksy łuków węzłów o dużej liczbie łuków (np. z innym budżetem
+    /// pamięci; przy zerowym budżecie `find_arc` używa tylko wyszukiwania binarnego).
+    pub fn with_jump_tables(mut self, options: JumpTableOptions) -> Result<Self> {
+        self.jump_tables = None;
+        let tables = JumpTables::build(&self, options)?;
+        self.jump_limit = tables.node_limit();
+        self.jump_tables = (tables.table_count() > 0).then_some(tables);
+        Ok(self)
+    }
+
+    pub fn jump_tables(&self) -> Option<&JumpTables> {
+        self.jump_tables.as_ref()
+    }
+
+    /// Liczba węzłów z łukami; jest to zarazem indeks wspólnego węzła bez łuków.
+    pub fn node_count(&self) -> usize {
+        self.first_arcs.len() - 2
+    }
+
+    /// Liczba łuków.
+    pub fn arc_count(&self) -> usize {
+        self.arcs.len()
+    }
+
+    #[inline]
+    pub fn root(&self) -> Node {
+        0
+    }
+
+    /// Łuki węzła (pusty zakres dla węzła bez łuków).
+    #[inline]
+    pub fn arcs(&self, node: Node) -> Range<ArcOffset> {
+        self.first_arcs[node] as usize..self.first_arcs[node + 1] as usize
+    }
+
+    /// Łuk węzła o podanej etykiecie: z indeksu węzła, jeśli go ma, a w przeciwnym razie
+    /// przez wyszukiwanie binarne w posortowanych łukach.
+    #[inline]
+    pub fn find_arc(&self, node: Node, label: u8) -> Option<ArcOffset> {
+        if node < self.jump_limit {
+            if let Some(found) = self.jump_tables.as_ref().and_then(|tables| tables.lookup(node, label)) {
+                return found.ok();
+            }
+        }
+        let range = self.arcs(node);
+        let start = range.start;
+        self.arcs[range].binary_search_by_key(&label, |arc| arc.label).ok().map(|i| start + i)
+    }
+
+    /// Węzeł, do którego prowadzi ścieżka `sequence` z węzła `node`, jeśli istnieje.
+    #[inline]
+    pub fn walk(&self, node: Node, sequence: &[u8]) -> Option<Node> {
+        sequence.iter().try_fold(node, |node, &label| self.find_arc(node, label).map(|arc| self.target(arc)))
+    }
+
+    #[inline]
+    pub fn target(&self, arc: ArcOffset) -> Node {
+        self.arcs[arc].target as usize
+    }
+
+    #[inline]
+    pub fn label(&self, arc: ArcOffset) -> u8 {
+        self.arcs[arc].label
+    }
+
+    /// Czy łuk kończy akceptowaną sekwencję.
+    #[inline]
+    pub fn is_final(&self, arc: ArcOffset) -> bool {
+        self.arcs[arc].flags & ARC_FINAL != 0
+    }
+
+    /// Czy to ostatni łuk węzła.
+    #[inline]
+    pub fn is_last(&self, arc: ArcOffset) -> bool {
+        self.arcs[arc].flags & ARC_LAST != 0
+    }
+
+    fn packed(&self, arc: ArcOffset) -> Result<&PackedArc> {
+        self.arcs.get(arc).ok_or(FsaError::InvalidArcOffset(arc))
+    }
+}
+
+impl Fsa for ValidatedFsa {
+    fn get_flags(&self) -> FsaFlags {
+        self.flags
+    }
+
+    fn get_root_node(&self) -> Node {
+        self.root()
+    }
+
+    fn get_first_arc(&self, node: Node) -> Result<ArcOffset> {
+        match self.first_arcs.get(node..node.saturating_add(2)) {
+            Some(&[start, end]) if start < end => Ok(start as usize),
+            _ => Err(FsaError::InvalidNodeOffset(node)),
+        }
+    }
+
+    fn first_arc_opt(&self, node: Node) -> Result<Option<ArcOffset>> {
+        match self.first_arcs.get(node..node.saturating_add(2)) {
+            Some(&[start, end]) => Ok((start < end).then_some(start as usize)),
+            _ => Err(FsaError::InvalidNodeOffset(node)),
+        }
+    }
+
+    fn get_next_arc(&self, arc: ArcOffset) -> Result<ArcOffset> {
+        if self.packed(arc)?.flags & ARC_LAST != 0 {
+            return Err(FsaError::NoNextArc(arc));
+        }
+        Ok(arc + 1)
+    }
+
+    fn get_arc(&self, node: Node, label: u8) -> Result<ArcOffset> {
+        self.first_arc_opt(node)?;
+        self.find_arc(node, label).ok_or(FsaError::ArcNotFound { label, node_offset: node })
+    }
+
+    fn get_end_node(&self, arc: ArcOffset) -> Result<Node> {
+        Ok(self.packed(arc)?.target as usize)
+    }
+
+    fn get_arc_label(&self, arc: ArcOffset) -> Result<u8> {
+        Ok(self.packed(arc)?.label)
+    }
+
+    fn is_arc_final(&self, arc: ArcOffset) -> Result<bool> {
+        Ok(self.packed(arc)?.flags & ARC_FINAL != 0)
+    }
+
+    fn is_arc_terminal(&self, arc: ArcOffset) -> Result<bool> {
+        Ok(self.packed(arc)?.flags & ARC_LAST != 0)
+    }
+
+    fn get_right_language_count(&self, node: Node) -> Result<usize> {
+        if self.counts.is_empty() {
+            return Err(FsaError::MissingNumbers);
+        }
+        self.counts.get(node).copied().ok_or(FsaError::InvalidNodeOffset(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iterator::ByteSequenceIterator;
+    use crate::test_utils::trie_fsa;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn test_same_language_and_navigation() {
+        let sequences: [&[u8]; 4] = [b"a", b"ab", b"abc", b"b"];
+        let source = trie_fsa(&sequences);
+        let fsa = ValidatedFsa::new(&source).unwrap();
+        assert_eq!(fsa.node_count(), 3);
+        assert_eq!(fsa.arc_count(), 4);
+
+        let all: Vec<Vec<u8>> = ByteSequenceIterator::new(&fsa).map(|s| s.unwrap()).collect();
+        assert_eq!(all, sequences.map(|s| s.to_vec()));
+
+        let node = fsa.walk(fsa.root(), b"ab").unwrap();
+        let arc = fsa.find_arc(node, b'c').unwrap();
+        assert!(fsa.is_final(arc) && fsa.is_last(arc));
+        assert_eq!(fsa.label(arc), b'c');
+        let leaf = fsa.target(arc);
+        assert_eq!(leaf, fsa.node_count());
+        assert!(fsa.arcs(leaf).is_empty());
+        assert_eq!(fsa.walk(fsa.root(), b"ac"), None);
+        assert_matches!(fsa.get_first_arc(leaf), Err(FsaError::InvalidNodeOffset(_)));
+        assert_eq!(fsa.first_arc_opt(leaf).unwrap(), None);
+        assert_matches!(fsa.first_arc_opt(leaf + 1), Err(FsaError::InvalidNodeOffset(_)));
+        assert_matches!(fsa.get_arc(0, b'z'), Err(FsaError::ArcNotFound { label: b'z', node_offset: 0 }));
+    }
+
+    #[test]
+    fn test_sorts_arcs_of_unsorted_source() {
+        // Korzeń FSA5 z łukami w kolejności 'c', 'a', 'b'; wszystkie prowadzą do liścia.
+        use crate::fsa5::{FSA5, BIT_ARC_FINAL, BIT_ARC_LAST};
+        use crate::test_utils::{arc, fsa5_bytes};
+        let arcs = [arc(b'c', BIT_ARC_FINAL, 0), arc(b'a', BIT_ARC_FINAL, 0), arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0)];
+        let source = FSA5::from_bytes(fsa5_bytes(0, &arcs.concat())).unwrap();
+        let fsa = ValidatedFsa::new(&source).unwrap();
+
+        let labels: Vec<u8> = fsa.arcs(fsa.root()).map(|arc| fsa.label(arc)).collect();
+        assert_eq!(labels, b"abc");
+        let last: Vec<bool> = fsa.arcs(fsa.root()).map(|arc| fsa.is_last(arc)).collect();
+        assert_eq!(last, [false, false, true]);
+        for label in *b"abc" {
+            assert_eq!(fsa.find_arc(fsa.root(), label).map(|arc| fsa.label(arc)), Some(label));
+        }
+        assert_eq!(fsa.find_arc(fsa.root(), b'd'), None);
+    }
+
+    #[test]
+    fn test_jump_tables_agree_with_binary_search() {
+        let mut sequences: Vec<Vec<u8>> = (b'0'..b'0' + 60).map(|label| vec![label, b'x']).collect();
+        sequences.extend((b'a'..b'a' + 10).map(|label| vec![b'm', label]));
+        sequences.sort();
+        let sequences: Vec<&[u8]> = sequences.iter().map(Vec::as_slice).collect();
+        let fsa = ValidatedFsa::new(&trie_fsa(&sequences)).unwrap();
+        assert!(fsa.jump_tables().is_some_and(|tables| tables.table_count() == 1));
+
+        let plain = fsa.clone().with_jump_tables(JumpTableOptions { memory_budget: 0, ..Default::default() }).unwrap();
+        assert!(plain.jump_tables().is_none());
+        let m = fsa.walk(fsa.root(), b"m").unwrap();
+        for node in [fsa.root(), m] {
+            for label in 0..=255u8 {
+                assert_eq!(fsa.find_arc(node, label), plain.find_arc(node, label), "node {node}, label {label}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_automaton() {
+        // Łuk bez flagi FINAL wraca do korzenia: cykl.
+        let root = crate::test_utils::fsa5_root(0);
+        let data = crate::test_utils::fsa5_bytes(0, &crate::test_utils::arc(b'a', crate::fsa5::BIT_ARC_LAST, root));
+        let source = crate::fsa5::FSA5::from_bytes(data).unwrap();
+        assert_matches!(ValidatedFsa::new(&source), Err(FsaError::CorruptedFsa(_)));
+    }
+}
@@ -0,0 +1,388 @@
+// Walidacja struktury automatu (np. z niezaufanych plików)
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{FsaError, Result};
+use crate::fsa_header::FsaFlags;
+use crate::fsa_trait::{ArcOffset, Fsa, Node};
+
+/// Problem wykryty podczas walidacji automatu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// Węzła nie da się odczytać albo wskazuje poza dane automatu.
+    InvalidNode { node: Node, reason: String },
+    /// Łuku nie da się odczytać (etykieta, flagi lub adresy poza danymi).
+    InvalidArc { arc: ArcOffset, reason: String },
+    /// Dwa łuki węzła mają tę samą etykietę (także gdy łańcuch łuków się zapętla).
+    DuplicateLabel { node: Node, label: u8 },
+    /// Łuk prowadzi do węzła leżącego na bieżącej ścieżce, czyli automat ma cykl.
+    Cycle { arc: ArcOffset, node: Node },
+    /// Nieakceptujący łuk prowadzi do węzła, z którego nie wychodzi żadna sekwencja.
+    DeadEnd { arc: ArcOffset },
+    /// Liczba sekwencji zapisana w węźle (flaga NUMBERS) różni się od rzeczywistej.
+    WrongCount { node: Node, stored: usize, actual: usize },
+    /// Bajty danych, które nie należą do żadnego osiągalnego węzła ani łuku.
+    UnreachableData { start: usize, end: usize },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::InvalidNode { node, reason } => write!(f, "nieprawidłowy węzeł {}: {}", node, reason),
+            ValidationIssue::InvalidArc { arc, reason } => write!(f, "nieprawidłowy łuk {}: {}", arc, reason),
+            ValidationIssue::DuplicateLabel { node, label } => {
+                write!(f, "powtórzona etykieta {} w węźle {}", label, node)
+            }
+            ValidationIssue::Cycle { arc, node } => write!(f, "cykl: łuk {} wraca do węzła {}", arc, node),
+            ValidationIssue::DeadEnd { arc } => write!(f, "łuk {} nie prowadzi do żadnej sekwencji", arc),
+            ValidationIssue::WrongCount { node, stored, actual } => {
+                write!(f, "węzeł {} zapisuje {} sekwencji, a akceptuje {}", node, stored, actual)
+            }
+            ValidationIssue::UnreachableData { start, end } => {
+                write!(f, "nieosiągalne dane w bajtach {}..{}", start, end)
+            }
+        }
+    }
+}
+
+/// Wynik walidacji: statystyki osiągalnej części automatu i lista problemów.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Liczba osiągalnych węzłów z łukami.
+    pub nodes: usize,
+    /// Liczba osiągalnych łuków.
+    pub arcs: usize,
+    /// Liczba akceptowanych sekwencji (`None`, jeśli automat ma cykl).
+    pub sequences: Option<usize>,
+    /// Rozmiar danych łuków, jeśli implementacja go udostępnia.
+    pub data_size: Option<usize>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Czy nie wykryto żadnego problemu.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Zwraca raport albo [`FsaError::CorruptedFsa`] z opisem pierwszego problemu.
+    pub fn into_result(self) -> Result<Self> {
+        match self.issues.first() {
+            None => Ok(self),
+            Some(issue) => Err(FsaError::CorruptedFsa(format!(
+                "{} (wykryte problemy: {})",
+                issue,
+                self.issues.len()
+            ))),
+        }
+    }
+}
+
+/// Sprawdza strukturę automatu, przechodząc wszystkie węzły osiągalne z korzenia.
+///
+/// Sprawdzane są: odczyt każdego łuku (etykieta, flagi, następny łuk, węzeł docelowy),
+/// unikalność etykiet w węźle, brak cykli i martwych gałęzi, liczby sekwencji przy
+/// fladze NUMBERS oraz — jeśli automat podaje [`Fsa::get_data_size`] — zakres adresów
+/// i pokrycie danych przez osiągalne węzły. Przejście nie używa rekurencji i kończy się
+/// także na uszkodzonych danych, więc nadaje się do sprawdzania plików przed użyciem.
+pub fn validate<F: Fsa + ?Sized>(fsa: &F) -> ValidationReport {
+    let mut validator = Validator {
+        fsa,
+        data_size: fsa.get_data_size(),
+        numbers: fsa.get_flags().contains(FsaFlags::NUMBERS),
+        marks: HashMap::new(),
+        spans: Vec::new(),
+        report: ValidationReport::default(),
+    };
+    validator.report.data_size = validator.data_size;
+    let reserved = fsa.get_reserved_size();
+    if reserved > 0 {
+        validator.spans.push((0, reserved));
+    }
+    let root = fsa.get_root_node();
+    let total = validator.run(root);
+    validator.check_coverage();
+
+    let mut report = validator.report;
+    let acyclic = !report.issues.iter().any(|issue| matches!(issue, ValidationIssue::Cycle { .. }));
+    report.sequences = acyclic.then_some(total);
+    report
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Mark {
+    /// Węzeł jest na bieżącej ścieżce.
+    Active,
+    /// Węzeł przetworzony; liczba sekwencji z niego.
+    Done(usize),
+}
+
+/// Odczytany łuk: offset, czy końcowy, węzeł docelowy.
+type ArcInfo = (ArcOffset, bool, Node);
+
+struct Frame {
+    node: Node,
+    arcs: Vec<ArcInfo>,
+    next: usize,
+    count: usize,
+}
+
+struct Validator<'a, F: Fsa + ?Sized> {
+    fsa: &'a F,
+    data_size: Option<usize>,
+    numbers: bool,
+    marks: HashMap<Node, Mark>,
+    /// Zakresy bajtów zajęte przez osiągalne węzły i łuki.
+    spans: Vec<(usize, usize)>,
+    report: ValidationReport,
+}
+
+impl<F: Fsa + ?Sized> Validator<'_, F> {
+    /// Przechodzi automat w głąb od korzenia i zwraca liczbę akceptowanych sekwencji.
+    fn run(&mut self, root: Node) -> usize {
+        let mut stack = match self.enter(root) {
+            Some(frame) => vec![frame],
+            None => return 0,
+        };
+        let mut total = 0;
+        while let Some(frame) = stack.last_mut() {
+            let Some(&(arc, is_final, target)) = frame.arcs.get(frame.next) else {
+                let frame = stack.pop().expect("stack is not empty");
+                let count = self.leave(&frame);
+                match stack.last_mut() {
+                    Some(parent) => self.add_arc_count(parent, count),
+                    None => total = count,
+                }
+                continue;
+            };
+            frame.next += 1;
+            frame.count = frame.count.saturating_add(usize::from(is_final));
+
+            match self.marks.get(&target) {
+                Some(Mark::Active) => self.report.issues.push(ValidationIssue::Cycle { arc, node: target }),
+                Some(&Mark::Done(count)) => self.add_arc_count(frame, count),
+                None => match self.enter(target) {
+                    Some(child) => stack.push(child),
+                    None => self.add_arc_count(frame, 0),
+                },
+            }
+        }
+        total
+    }
+
+    /// Dolicza sekwencje węzła docelowego ostatnio odwiedzonego łuku ramki.
+    fn add_arc_count(&mut self, frame: &mut Frame, count: usize) {
+        let (arc, is_final, _) = frame.arcs[frame.next - 1];
+        if count == 0 && !is_final {
+            self.report.issues.push(ValidationIssue::DeadEnd { arc });
+        }
+        frame.count = frame.count.saturating_add(count);
+    }
+
+    /// Odczytuje łuki węzła; `None` dla liścia (węzła bez łuków) lub węzła uszkodzonego.
+    fn enter(&mut self, node: Node) -> Option<Frame> {
+        let first = match self.fsa.first_arc_opt(node) {
+            Ok(Some(arc)) => arc,
+            Ok(None) => {
+                self.marks.insert(node, Mark::Done(0));
+                return None;
+            }
+            Err(e) => {
+                self.report.issues.push(ValidationIssue::InvalidNode { node, reason: e.to_string() });
+                self.marks.insert(node, Mark::Done(0));
+                return None;
+            }
+        };
+        self.marks.insert(node, Mark::Active);
+        self.report.nodes += 1;
+        if first > node {
+            self.spans.push((node, first));
+        }
+
+        let mut arcs = Vec::new();
+        let mut labels = [false; 256];
+        let mut arc = first;
+        loop {
+            let (label, is_final, is_last, target) = match self.read_arc(arc) {
+                Ok(read) => read,
+                Err(e) => {
+                    self.report.issues.push(ValidationIssue::InvalidArc { arc, reason: e.to_string() });
+                    break;
+                }
+            };
+            if std::mem::replace(&mut labels[label as usize], true) {
+                self.report.issues.push(ValidationIssue::DuplicateLabel { node, label });
+                break;
+            }
+            self.report.arcs += 1;
+            arcs.push((arc, is_final, target));
+            if is_last {
+                break;
+            }
+            arc = match self.fsa.get_next_arc(arc) {
+                Ok(next) => next,
+                Err(e) => {
+                    self.report.issues.push(ValidationIssue::InvalidArc { arc, reason: e.to_string() });
+                    break;
+                }
+            };
+        }
+        Some(Frame { node, arcs, next: 0, count: 0 })
+    }
+
+    /// Odczytuje łuk: etykieta, czy końcowy, czy ostatni, węzeł docelowy.
+    fn read_arc(&mut self, arc: ArcOffset) -> Result<(u8, bool, bool, Node)> {
+        let read = (
+            self.fsa.get_arc_label(arc)?,
+            self.fsa.is_arc_final(arc)?,
+            self.fsa.is_arc_terminal(arc)?,
+            self.fsa.get_end_node(arc)?,
+        );
+        if let Some(size) = self.data_size {
+            let end = arc.checked_add(self.fsa.get_arc_size(arc)?).filter(|&end| end <= size);
+            let end = end.ok_or(FsaError::InvalidArcOffset(arc))?;
+            if read.3 > size {
+                return Err(FsaError::InvalidNodeOffset(read.3));
+            }
+            self.spans.push((arc, end));
+        }
+        Ok(read)
+    }
+
+    /// Zamyka węzeł: sprawdza zapisaną liczbę sekwencji i zwraca rzeczywistą.
+    fn leave(&mut self, frame: &Frame) -> usize {
+        if self.numbers {
+            match self.fsa.get_right_language_count(frame.node) {
+                Ok(stored) if stored != frame.count => self.report.issues.push(ValidationIssue::WrongCount {
+                    node: frame.node,
+                    stored,
+                    actual: frame.count,
+                }),
+                Ok(_) => {}
+                Err(e) => self.report.issues.push(ValidationIssue::InvalidNode { node: frame.node, reason: e.to_string() }),
+            }
+        }
+        self.marks.insert(frame.node, Mark::Done(frame.count));
+        frame.count
+    }
+
+    /// Zgłasza zakresy danych niepokryte przez żaden osiągalny węzeł ani łuk.
+    fn check_coverage(&mut self) {
+        let Some(size) = self.data_size else {
+            return;
+        };
+        self.spans.sort_unstable();
+        let mut covered = 0;
+        for &(start, end) in &self.spans {
+            if start > covered {
+                self.report.issues.push(ValidationIssue::UnreachableData { start: covered, end: start });
+            }
+            covered = covered.max(end);
+        }
+        if covered < size {
+            self.report.issues.push(ValidationIssue::UnreachableData { start: covered, end: size });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfsa2::{CFSA2, CFSA_ARC_IS_FINAL_BIT, CFSA_ARC_IS_LAST_BIT, CFSA_ARC_TARGET_IS_NEXT_BIT};
+    use crate::fsa5::{FSA5, BIT_ARC_FINAL, BIT_ARC_LAST};
+    use crate::fsa_header::{FSA_MAGIC, VERSION_CFSA2};
+    use crate::test_utils::{arc, fsa5_bytes, trie_fsa};
+
+    /// FSA5 z NUMBERS (gtl = 1, node_data_length = 1) z podanymi danymi węzłów; korzeń pod adresem 6.
+    fn numbered_fsa5(nodes: &[u8]) -> FSA5 {
+        FSA5::from_bytes(fsa5_bytes(1, nodes)).unwrap()
+    }
+
+    fn nodes(parts: &[&[u8]]) -> Vec<u8> {
+        parts.concat()
+    }
+
+    #[test]
+    fn test_valid_automata() {
+        let report = validate(&trie_fsa(&[b"ala", b"alan", b"kot"]));
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.sequences, Some(3));
+        assert_eq!(report.arcs, 7);
+
+        // "a", "ab", "b" z poprawnymi licznikami.
+        let report = validate(&numbered_fsa5(&nodes(&[
+            &[3], &arc(b'a', BIT_ARC_FINAL, 11), &arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0),
+            &[1], &arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0),
+        ])));
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!((report.nodes, report.sequences, report.data_size), (2, Some(3), Some(14)));
+    }
+
+    #[test]
+    fn test_wrong_counts_and_unreachable_data() {
+        let fsa = numbered_fsa5(&nodes(&[
+            &[2], &arc(b'a', BIT_ARC_FINAL, 11), &arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0),
+            &[1], &arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 0),
+            &arc(b'x', 0, 0),
+        ]));
+        let report = validate(&fsa);
+        assert_eq!(
+            report.issues,
+            [
+                ValidationIssue::WrongCount { node: 6, stored: 2, actual: 3 },
+                ValidationIssue::UnreachableData { start: 14, end: 16 },
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn test_cycle_is_reported_instead_of_looping() {
+        // Węzeł 4: "a" -> 6; węzeł 6: "b" (końcowy) -> 4.
+        let data = nodes(&[&arc(b'a', BIT_ARC_LAST, 6), &arc(b'b', BIT_ARC_FINAL | BIT_ARC_LAST, 4)]);
+        let report = validate(&FSA5::from_bytes(fsa5_bytes(0, &data)).unwrap());
+        assert_eq!(report.issues, [ValidationIssue::Cycle { arc: 6, node: 4 }]);
+        assert_eq!(report.sequences, None);
+    }
+
+    #[test]
+    fn test_broken_arcs() {
+        // Niekońcowy łuk do liścia, powtórzona etykieta i adres poza danymi.
+        let data = nodes(&[&arc(b'a', 0, 0), &arc(b'a', BIT_ARC_FINAL, 0), &arc(b'c', BIT_ARC_FINAL | BIT_ARC_LAST, 30)]);
+        let report = validate(&FSA5::from_bytes(fsa5_bytes(0, &data)).unwrap());
+        assert_eq!(
+            report.issues,
+            [
+                ValidationIssue::DuplicateLabel { node: 4, label: b'a' },
+                ValidationIssue::DeadEnd { arc: 4 },
+                ValidationIssue::UnreachableData { start: 8, end: 10 },
+            ]
+        );
+
+        let data = arc(b'a', BIT_ARC_FINAL | BIT_ARC_LAST, 30);
+        let report = validate(&FSA5::from_bytes(fsa5_bytes(0, &data)).unwrap());
+        assert!(matches!(report.issues[..], [ValidationIssue::InvalidArc { arc: 4, .. }, ..]));
+    }
+
+    #[test]
+    fn test_cfsa2_layout() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&FSA_MAGIC);
+        data.push(VERSION_CFSA2);
+        data.extend_from_slice(&[0, 0, 1, 0]);
+        // "ab": łuk 'a' z celem tuż za nim, łuk 'b' bez dalszych przejść (adres 0).
+        data.extend_from_slice(&[CFSA_ARC_IS_LAST_BIT, b'^', 3]);
+        data.extend_from_slice(&[CFSA_ARC_IS_LAST_BIT | CFSA_ARC_TARGET_IS_NEXT_BIT, b'a']);
+        data.extend_from_slice(&[CFSA_ARC_IS_FINAL_BIT | CFSA_ARC_IS_LAST_BIT, b'b', 0]);
+        let fsa = CFSA2::from_bytes(data.clone()).unwrap();
+        let report = validate(&fsa);
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert_eq!(report.sequences, Some(1));
+
+        // Adres wskazujący poza dane.
+        *data.last_mut().unwrap() = 50;
+        let report = validate(&CFSA2::from_bytes(data).unwrap());
+        assert!(matches!(report.issues[..], [ValidationIssue::InvalidArc { arc: 5, .. }, ..]), "{:?}", report.issues);
+    }
+}
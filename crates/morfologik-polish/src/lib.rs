@@ -24,36 +24,35 @@ mod tests {
     }
     
     fn create_fsa_with_kot_subst(path: &Path) {
-        use morfologik_fsa::fsa_header::{FSA_MAGIC, VERSION_FSA5, FsaFlags};
+        use morfologik_fsa::fsa5::{BIT_ARC_FINAL, BIT_ARC_LAST, BIT_ARC_TARGET_NEXT};
+        use morfologik_fsa::fsa_header::{FSA_MAGIC, VERSION_FSA5};
 
         let key = b"kot";
         let value_as_single_sequence = b"KOT+SUBST"; 
 
-        let mut arcs_data = Vec::new();
-        let combined_sequence: Vec<u8> = key.iter().chain(value_as_single_sequence.iter()).cloned().collect();
+        let combined_sequence: Vec<u8> = key.iter()
+            .chain(std::iter::once(&b'+'))
+            .chain(value_as_single_sequence.iter())
+            .cloned()
+            .collect();
         let total_len = combined_sequence.len();
 
+        // Pusty węzeł stanu końcowego i łuk epsilon do korzenia (zapisanego zaraz za nim).
+        let mut arcs_data = vec![0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT];
         for (i, &byte_val) in combined_sequence.iter().enumerate() {
             arcs_data.push(byte_val); 
-            let mut arc_flags = 0u8;
-            if i == total_len - 1 { 
-                arc_flags |= morfologik_fsa::fsa5::BIT_ARC_FINAL;
-            }
-            if i == total_len - 1 { 
-                 arc_flags |= morfologik_fsa::fsa5::BIT_ARC_LAST;
-            }
+            // Każdy stan na ścieżce ma dokładnie jeden łuk wychodzący, a następny stan
+            // leży zaraz za nim; ostatni łuk prowadzi do stanu końcowego (adres 0).
+            let arc_flags = if i == total_len - 1 {
+                BIT_ARC_LAST | BIT_ARC_FINAL
+            } else {
+                BIT_ARC_LAST | BIT_ARC_TARGET_NEXT
+            };
             arcs_data.push(arc_flags);
-            arcs_data.push(if i < total_len - 1 { ((i + 1) * 3) as u8 } else { 0 }); 
         }
         
-        let mut header_data = Vec::new();
-        header_data.extend_from_slice(&FSA_MAGIC);
-        header_data.push(VERSION_FSA5);
-        header_data.push(1); 
-        header_data.push(1); 
-        header_data.extend_from_slice(&FsaFlags::empty().bits().to_le_bytes()); 
-
-        let mut fsa_file_bytes = header_data;
+        let mut fsa_file_bytes = FSA_MAGIC.to_vec();
+        fsa_file_bytes.extend_from_slice(&[VERSION_FSA5, b'_', b'+', 0x01]);
         fsa_file_bytes.extend_from_slice(&arcs_data);
         
         let mut file = File::create(path).expect("Failed to create FSA file for test");
@@ -127,6 +126,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_embedded_dictionary_lookup() -> StemmingResult<()> {
+        let stemmer = PolishStemmer::new()?;
+        let lookup = |word: &str| -> StemmingResult<Vec<(String, String)>> {
+            Ok(stemmer
+                .lookup(word.as_bytes())?
+                .iter()
+                .map(|w| {
+                    let stem = String::from_utf8_lossy(w.stem().unwrap_or_default()).into_owned();
+                    let tag = String::from_utf8_lossy(w.tag().unwrap_or_default()).into_owned();
+                    (stem, tag)
+                })
+                .collect())
+        };
+
+        let pair = |stem: &str, tag: &str| (stem.to_string(), tag.to_string());
+        assert_eq!(lookup("domy")?, [pair("dom", "subst:pl:acc:m3+subst:pl:nom:m3+subst:pl:voc:m3")]);
+        assert_eq!(
+            lookup("kotami")?,
+            [pair("kota", "subst:pl:inst:f"), pair("kot", "subst:pl:inst:m1+subst:pl:inst:m2")]
+        );
+        // Enkoder PREFIX: forma i lemat różnią się prefiksem i sufiksem.
+        assert_eq!(lookup("najpiękniejszy")?, [pair("piękny", "adj:sg:acc:m3:sup+adj:sg:nom.voc:m1.m2.m3:sup")]);
+        assert!(lookup("xyzzy")?.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_polish_stemmer_from_path_fails_if_dict_missing() {
         let non_existent_path = PathBuf::from("hopefully_non_existent_dictionary_12345.dict");
@@ -1,7 +1,7 @@
 // Definicja struktury PolishStemmer i jej implementacje
 
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
 
 use morfologik_stemming::dictionary::Dictionary;
 use morfologik_stemming::dictionary_lookup::DictionaryLookup;
@@ -14,11 +14,14 @@ use morfologik_stemming::error::{Result as StemmingResult, StemmingError};
 // Jeśli polish_stemmer.rs jest w morfologik-rs/crates/morfologik-polish/src/
 // a zasoby w morfologik-rs/crates/morfologik-polish/resources/
 // to ścieżka powinna być "../resources/..."
-const EMBEDDED_POLISH_DICT_BYTES: &'static [u8] = 
+const EMBEDDED_POLISH_DICT_BYTES: &[u8] = 
     include_bytes!("../resources/morfologik/stemming/polish/polish.dict");
-const EMBEDDED_POLISH_INFO_BYTES: &'static [u8] = 
+const EMBEDDED_POLISH_INFO_BYTES: &[u8] = 
     include_bytes!("../resources/morfologik/stemming/polish/polish.info");
 
+/// Wyszukiwarka nad osadzonym słownikiem, dekodowanym raz na proces i współdzielonym
+/// przez kolejne wywołania [`PolishStemmer::new`].
+static EMBEDDED_LOOKUP: OnceLock<DictionaryLookup> = OnceLock::new();
 
 /// Stemer dla języka polskiego.
 #[derive(Debug, Clone)]
@@ -27,38 +30,32 @@ pub struct PolishStemmer {
 }
 
 impl PolishStemmer {
+    /// Tworzy stemer nad słownikiem osadzonym w bibliotece. Automat jest przy pierwszym
+    /// wywołaniu sprawdzany i dekodowany ([`DictionaryLookup::validated`]); kolejne
+    /// stemery współdzielą tę kopię.
     pub fn new() -> StemmingResult<Self> {
-        println!("[PolishStemmer::new()] Próba wczytania osadzonego słownika.");
-        println!("[PolishStemmer::new()] Długość EMBEDDED_POLISH_DICT_BYTES: {}", EMBEDDED_POLISH_DICT_BYTES.len());
-        println!("[PolishStemmer::new()] Długość EMBEDDED_POLISH_INFO_BYTES: {}", EMBEDDED_POLISH_INFO_BYTES.len());
-
+        if let Some(dictionary_lookup) = EMBEDDED_LOOKUP.get() {
+            return Ok(PolishStemmer { dictionary_lookup: dictionary_lookup.clone() });
+        }
         if EMBEDDED_POLISH_DICT_BYTES.is_empty() {
-            eprintln!("[PolishStemmer::new()] BŁĄD: Osadzony plik .dict jest pusty!");
             return Err(StemmingError::DictionaryConfigurationError("Osadzony plik .dict jest pusty.".to_string()));
         }
         if EMBEDDED_POLISH_INFO_BYTES.is_empty() {
-            eprintln!("[PolishStemmer::new()] BŁĄD: Osadzony plik .info jest pusty!");
             return Err(StemmingError::DictionaryConfigurationError("Osadzony plik .info jest pusty.".to_string()));
         }
 
-        // Wyświetl kilka pierwszych bajtów dla weryfikacji
-        let dict_preview_len = std::cmp::min(16, EMBEDDED_POLISH_DICT_BYTES.len());
-        println!("[PolishStemmer::new()] Początek .dict (max 16B): {:?}", &EMBEDDED_POLISH_DICT_BYTES[..dict_preview_len]);
-        let info_preview_len = std::cmp::min(64, EMBEDDED_POLISH_INFO_BYTES.len());
-        println!("[PolishStemmer::new()] Początek .info (max 64B): {:?}", String::from_utf8_lossy(&EMBEDDED_POLISH_INFO_BYTES[..info_preview_len]));
-
-
         let dictionary = Dictionary::from_embedded(
             EMBEDDED_POLISH_DICT_BYTES,
             EMBEDDED_POLISH_INFO_BYTES,
         )?;
-        println!("[PolishStemmer::new()] Dictionary::from_embedded zakończone sukcesem.");
-        let dictionary_lookup = DictionaryLookup::new(Arc::new(dictionary));
+        let dictionary_lookup = DictionaryLookup::validated(Arc::new(dictionary))?;
         Ok(PolishStemmer {
-            dictionary_lookup,
+            dictionary_lookup: EMBEDDED_LOOKUP.get_or_init(|| dictionary_lookup).clone(),
         })
     }
 
+    /// Tworzy stemer nad słownikiem z pliku (i pliku `.info` obok niego); automat jest
+    /// sprawdzany i dekodowany jak w [`PolishStemmer::new`].
     pub fn from_path<P: AsRef<Path>>(dictionary_file_path: P) -> StemmingResult<Self> {
         let path_ref = dictionary_file_path.as_ref();
         
@@ -77,7 +74,7 @@ impl PolishStemmer {
         }
 
         let dictionary = Dictionary::from_file(path_ref)?;
-        let dictionary_lookup = DictionaryLookup::new(Arc::new(dictionary));
+        let dictionary_lookup = DictionaryLookup::validated(Arc::new(dictionary))?;
         Ok(PolishStemmer {
             dictionary_lookup,
         })
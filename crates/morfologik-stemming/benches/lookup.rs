@@ -0,0 +1,83 @@
+// Porównanie wyszukiwania w słowniku: DictionaryLookup::new na automacie i enkoderze
+// wywoływanych przez `Box<dyn ...>` (wariant `boxed`, jak przed wprowadzeniem AnyFsa)
+// i przez AnyFsa/AnyEncoder (`any`) w grupie `dispatch` oraz `any` kontra
+// DictionaryLookup::validated w grupie `validated`.
+//
+// Domyślnie używany jest dołączony polish.dict (CFSA2 z Javy). Inny słownik można podać
+// przez zmienną MORFOLOGIK_BENCH_DICT (ścieżka do pliku .dict obok pliku .info):
+//
+//     MORFOLOGIK_BENCH_DICT=pl.dict cargo bench -p morfologik-stemming --bench lookup
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use morfologik_fsa::any_fsa::AnyFsa;
+use morfologik_fsa::iterator::ByteSequenceIterator;
+use morfologik_fsa::loader::FsaRegistry;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::dictionary_lookup::DictionaryLookup;
+use morfologik_stemming::dictionary_metadata::DictionaryMetadata;
+use morfologik_stemming::encoders::{create_encoder, AnyEncoder};
+use morfologik_stemming::stemmer_trait::Stemmer;
+
+const POLISH_DICT: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../morfologik-polish/resources/morfologik/stemming/polish/polish.dict");
+
+/// Liczba słów wyszukiwanych w jednej iteracji.
+const SAMPLE_SIZE: usize = 100_000;
+
+/// Słownik z pliku; słowa do wyszukania to formy (część przed separatorem) jego sekwencji,
+/// próbkowane równomiernie z całego słownika, a nie tylko z początku alfabetu.
+fn dictionary_from_file(path: &str) -> (Vec<u8>, DictionaryMetadata, Vec<Vec<u8>>) {
+    let fsa_bytes = std::fs::read(path).expect("dictionary file");
+    let metadata = DictionaryMetadata::from_dictionary_path(path).expect("dictionary metadata");
+    let separator = metadata.get_separator().expect("separator") as u8;
+    let fsa = FsaRegistry::new().load(fsa_bytes.clone()).expect("automaton");
+    let mut words: Vec<Vec<u8>> = ByteSequenceIterator::new(&fsa)
+        .filter_map(|sequence| {
+            let sequence = sequence.ok()?;
+            let end = sequence.iter().position(|&b| b == separator)?;
+            Some(sequence[..end].to_vec())
+        })
+        .collect();
+    words.dedup();
+    let step = words.len().div_ceil(SAMPLE_SIZE).max(1);
+    let words = words.into_iter().step_by(step).collect();
+    (fsa_bytes, metadata, words)
+}
+
+/// Słownik z automatem i enkoderem za `Box<dyn ...>` (wariant `Other`).
+fn boxed_dictionary(fsa_bytes: Vec<u8>, metadata: DictionaryMetadata) -> Dictionary {
+    let fsa = FsaRegistry::new().from_bytes(fsa_bytes).expect("automaton");
+    let encoder = create_encoder(&metadata.get_encoder().expect("encoder"), metadata.get_separator().expect("separator") as u8);
+    let dictionary = Dictionary::from_fsa(AnyFsa::Other(fsa), metadata).expect("dictionary");
+    Dictionary { encoder: Arc::new(AnyEncoder::Other(Box::new(encoder))), ..dictionary }
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let path = std::env::var("MORFOLOGIK_BENCH_DICT").unwrap_or_else(|_| POLISH_DICT.to_string());
+    let (fsa_bytes, metadata, words) = dictionary_from_file(&path);
+    let boxed = DictionaryLookup::new(Arc::new(boxed_dictionary(fsa_bytes.clone(), metadata.clone())));
+    let dictionary = Arc::new(Dictionary::from_bytes(fsa_bytes, metadata).expect("dictionary"));
+    let any = DictionaryLookup::new(dictionary.clone());
+    let validated = DictionaryLookup::validated(dictionary).expect("valid dictionary");
+
+    // `dispatch`: Box<dyn ...> kontra AnyFsa/AnyEncoder; `validated`: AnyFsa kontra ValidatedFsa.
+    // Grupę można uruchomić osobno, np. `cargo bench ... -- dispatch`.
+    let groups = [("dispatch", [("boxed", &boxed), ("any", &any)]), ("validated", [("any", &any), ("validated", &validated)])];
+    for (group_name, lookups) in groups {
+        let mut group = c.benchmark_group(group_name);
+        // Jedna iteracja na polish.dict trwa od kilkuset ms do ponad sekundy.
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(words.len() as u64));
+        for (name, lookup) in lookups {
+            group.bench_with_input(BenchmarkId::new(name, words.len()), &words, |b, words| {
+                b.iter(|| words.iter().map(|word| lookup.lookup(word).unwrap().len()).sum::<usize>())
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_lookup);
+criterion_main!(benches);
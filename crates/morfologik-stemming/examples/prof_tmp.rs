@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Instant;
+use morfologik_fsa::iterator::ByteSequenceIterator;
+use morfologik_fsa::fsa_trait::Fsa;
+use morfologik_fsa::validated::ValidatedFsa;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::dictionary_lookup::DictionaryLookup;
+use morfologik_stemming::stemmer_trait::Stemmer;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../morfologik-polish/resources/morfologik/stemming/polish/polish.dict");
+    let dict = Arc::new(Dictionary::from_file(path).unwrap());
+    let sep = b';';
+    let mut words: Vec<Vec<u8>> = ByteSequenceIterator::new(dict.fsa.as_ref()).filter_map(|s| { let s = s.ok()?; let e = s.iter().position(|&b| b == sep)?; Some(s[..e].to_vec()) }).collect();
+    words.dedup();
+    let step = words.len().div_ceil(100_000);
+    let words: Vec<Vec<u8>> = words.into_iter().step_by(step).collect();
+    let mut out = Vec::new(); for w in &words { out.extend_from_slice(w); out.push(b'\n'); } std::fs::write("/tmp/words.txt", out).unwrap(); if std::env::args().count() > 1 { return; }
+    let t = Instant::now();
+    let v = ValidatedFsa::new(dict.fsa.as_ref()).unwrap();
+    println!("validate+decode {:?}, nodes {}, arcs {}", t.elapsed(), v.node_count(), v.arc_count());
+    let lookup = DictionaryLookup::validated(dict.clone()).unwrap();
+    let plain = DictionaryLookup::new(dict.clone());
+    let v0 = v.clone().with_jump_tables(morfologik_fsa::jump_tables::JumpTableOptions { memory_budget: 0, ..Default::default() }).unwrap();
+    println!("tables {:?}", v.jump_tables().map(|t| (t.table_count(), t.memory_usage())));
+    for _ in 0..3 {
+        let t = Instant::now();
+        let mut n = 0; for _ in &words { n += dict.metadata.get_separator().unwrap() as usize; }
+        println!("get_separator {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0; for w in &words {
+            if let Some(node) = v.walk(v.root(), w).and_then(|node| v.find_arc(node, sep)).map(|a| v.target(a)) {
+                let mut c = morfologik_fsa::iterator::SequenceCursor::from_node(&v, node);
+                while let Some(x) = c.advance() { n += x.unwrap().len(); }
+            }
+        }
+        println!("validated walk+cursor {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0; for w in &words {
+            if let Some(node) = v.walk(v.root(), w).and_then(|node| v.find_arc(node, sep)).map(|a| v.target(a)) {
+                let mut stack: Vec<std::ops::Range<usize>> = vec![v.arcs(node)];
+                let mut buf: Vec<u8> = Vec::new();
+                while let Some(range) = stack.last_mut() {
+                    let Some(arc) = range.next() else { stack.pop(); buf.pop(); continue; };
+                    buf.push(v.label(arc));
+                    if v.is_final(arc) { n += buf.len(); }
+                    let children = v.arcs(v.target(arc));
+                    if children.is_empty() { buf.pop(); } else { stack.push(children); }
+                }
+            }
+        }
+        println!("validated walk+direct dfs {:?} {}", t.elapsed(), n);
+        let enc = dict.encoder.as_ref();
+        use morfologik_stemming::sequence_encoder_trait::SequenceEncoder;
+        let t = Instant::now();
+        let mut n = 0; for w in &words {
+            if let Some(node) = v.walk(v.root(), w).and_then(|node| v.find_arc(node, sep)).map(|a| v.target(a)) {
+                let mut c = morfologik_fsa::iterator::SequenceCursor::from_node(&v, node);
+                while let Some(x) = c.advance() { let x = x.unwrap(); let p = x.iter().position(|&b| b == sep).unwrap(); n += enc.decode(w, &x[..p]).unwrap().len(); }
+            }
+        }
+        println!("validated walk+cursor+decode {:?} {}", t.elapsed(), n);
+    }
+    for _ in 0..2 {
+        let t = Instant::now();
+        let mut n = 0; for w in &words { n += v.walk(v.root(), w).is_some() as usize; }
+        println!("validated walk only {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0; for w in &words { n += v0.walk(v0.root(), w).is_some() as usize; }
+        println!("no-table walk only {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0; for w in &words { n += lookup.lookup(w).unwrap().len(); }
+        println!("validated lookup {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0usize; for w in &words { let mut node = dict.fsa.get_root_node(); for &b in w.iter() { node = dict.fsa.get_end_node(dict.fsa.get_arc(node, b).unwrap()).unwrap(); } n += node; }
+        println!("checked walk only {:?} {}", t.elapsed(), n);
+        let t = Instant::now();
+        let mut n = 0; for w in &words { n += plain.lookup(w).unwrap().len(); }
+        println!("checked lookup {:?} {}", t.elapsed(), n);
+    }
+}
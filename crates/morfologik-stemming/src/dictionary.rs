@@ -1,99 +1,164 @@
 // Plik dla struktury Dictionary
 
+use std::fmt::Debug;
+#[cfg(feature = "mmap")]
 use std::fs::File;
-use std::io::{BufReader, Cursor, Read, Seek, SeekFrom}; 
 use std::path::{Path, PathBuf};
 use std::sync::Arc; 
 
-use morfologik_fsa::fsa_trait::Fsa;
-use morfologik_fsa::fsa5::FSA5;
-use morfologik_fsa::cfsa2::CFSA2;
-// Używamy poprawnych stałych wersji
-use morfologik_fsa::fsa_header::{VERSION_FSA5, VERSION_CFSA2, FsaHeader};
-use morfologik_fsa::error::FsaError;
+use morfologik_fsa::any_fsa::AnyFsa;
+use morfologik_fsa::completion::{CompletionOptions, Completions};
+use morfologik_fsa::validation::{validate, ValidationReport};
+use morfologik_fsa::jump_tables::JumpTableOptions;
+use morfologik_fsa::loader::FsaRegistry;
 
 
-use crate::dictionary_metadata::{DictionaryMetadata, EncoderType, DictionaryAttribute};
-use crate::encoders::{
-    NoEncoder, TrimPrefixEncoder, TrimPrefixAndSuffixEncoder, TrimSuffixEncoder,
-    TrimInfixAndSuffixEncoder,
-};
-use crate::sequence_encoder_trait::SequenceEncoder;
+use crate::dictionary_metadata::DictionaryMetadata;
+use crate::encoders::{create_encoder, AnyEncoder};
 use crate::error::{Result as StemmingResult, StemmingError};
 
 /// Reprezentuje słownik morfologiczny.
 #[derive(Debug, Clone)] 
 pub struct Dictionary {
-    pub fsa: Arc<Box<dyn Fsa + Send + Sync>>, 
-    pub metadata: Arc<DictionaryMetadata>, 
-    pub encoder: Arc<Box<dyn SequenceEncoder + Send + Sync>>, 
+    pub fsa: Arc<AnyFsa>,
+    pub metadata: Arc<DictionaryMetadata>,
+    pub encoder: Arc<AnyEncoder>,
 }
 
 impl Dictionary {
+    /// Wczytuje słownik z pliku `.dict` (oraz pliku `.info` obok niego) do pamięci.
     pub fn from_file<P: AsRef<Path>>(dictionary_file_path: P) -> StemmingResult<Self> {
+        Self::from_file_with_registry(dictionary_file_path, &FsaRegistry::new())
+    }
+
+    /// Jak [`Dictionary::from_file`], ale najpierw sprawdza strukturę automatu
+    /// (patrz [`FsaRegistry::with_validation`]) i dla uszkodzonego pliku zwraca
+    /// [`StemmingError::Fsa`] z [`FsaError::CorruptedFsa`](morfologik_fsa::error::FsaError::CorruptedFsa).
+    pub fn from_file_validated<P: AsRef<Path>>(dictionary_file_path: P) -> StemmingResult<Self> {
+        Self::from_file_with_registry(dictionary_file_path, &FsaRegistry::new().with_validation())
+    }
+
+    /// Jak [`Dictionary::from_file`], ale format automatu rozpoznaje podany rejestr.
+    pub fn from_file_with_registry<P: AsRef<Path>>(
+        dictionary_file_path: P,
+        registry: &FsaRegistry,
+    ) -> StemmingResult<Self> {
         let path_ref = dictionary_file_path.as_ref();
 
         let metadata = DictionaryMetadata::from_dictionary_path(path_ref)?;
-        
-        let dict_file = File::open(path_ref).map_err(|e| {
+
+        let fsa_bytes = std::fs::read(path_ref).map_err(|e| {
             StemmingError::Io(format!(
                 "Failed to open dictionary file '{}': {}",
                 path_ref.display(),
                 e
             ))
         })?;
-        let mut reader = BufReader::new(dict_file);
 
-        Self::from_readers(&mut reader, metadata)
+        Self::from_bytes_with_registry(fsa_bytes, metadata, registry)
     }
 
-    pub fn from_embedded(dict_fsa_bytes: &[u8], info_bytes: &[u8]) -> StemmingResult<Self> {
+    /// Mapuje plik `.dict` do pamięci tylko do odczytu i czyta automat wprost z mapowania,
+    /// więc procesy korzystające z tego samego pliku współdzielą jego strony.
+    ///
+    /// # Safety
+    ///
+    /// Plik nie może zostać zmieniony ani skrócony, dopóki słownik (lub jego klon) istnieje;
+    /// w przeciwnym razie odczyt z mapowania jest niezdefiniowany.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_file_mapped<P: AsRef<Path>>(dictionary_file_path: P) -> StemmingResult<Self> {
+        let path_ref = dictionary_file_path.as_ref();
+
+        let metadata = DictionaryMetadata::from_dictionary_path(path_ref)?;
+
+        let io_error = |e: std::io::Error| {
+            StemmingError::Io(format!(
+                "Failed to map dictionary file '{}': {}",
+                path_ref.display(),
+                e
+            ))
+        };
+        let dict_file = File::open(path_ref).map_err(io_error)?;
+        let mapping = memmap2::Mmap::map(&dict_file).map_err(io_error)?;
+
+        Self::from_bytes(mapping, metadata)
+    }
+
+    /// Tworzy słownik z osadzonych bajtów (np. `include_bytes!`) bez kopiowania automatu.
+    pub fn from_embedded<S>(dict_fsa_bytes: S, info_bytes: &[u8]) -> StemmingResult<Self>
+    where
+        S: AsRef<[u8]> + Debug + Send + Sync + 'static,
+    {
         let metadata = DictionaryMetadata::from_bytes(info_bytes)?;
-        let mut reader = Cursor::new(dict_fsa_bytes); 
-        Self::from_readers(&mut reader, metadata)
+        Self::from_bytes(dict_fsa_bytes, metadata)
     }
-    
-    fn from_readers<R: Read + Seek>(reader: &mut R, metadata: DictionaryMetadata) -> StemmingResult<Self> {
-        let arc_metadata = Arc::new(metadata);
 
-        let original_pos = reader.stream_position().map_err(|e| StemmingError::Io(e.to_string()))?;
-        
-        let fsa_header_for_check = FsaHeader::read(reader)
-            .map_err(StemmingError::Fsa)?;
-        
-        reader.seek(SeekFrom::Start(original_pos)).map_err(|e| StemmingError::Io(e.to_string()))?;
-
-        let fsa_instance: Box<dyn Fsa + Send + Sync> = match fsa_header_for_check.version {
-            VERSION_FSA5 => Box::new(FSA5::from_reader(reader).map_err(StemmingError::Fsa)?),
-            // Teraz VERSION_CFSA2 to 0xC6
-            VERSION_CFSA2 => {
-                println!("[Dictionary::from_readers] Wykryto wersję CFSA2 (0xC6).");
-                Box::new(CFSA2::from_reader(reader).map_err(StemmingError::Fsa)?)
-            }
-            // Usunięto gałąź dla VERSION_CFSA_LEGACY, ponieważ jest to teraz VERSION_CFSA2
-            ver => return Err(StemmingError::Fsa(FsaError::UnsupportedVersion(ver))),
-        };
-        
-        let arc_fsa = Arc::new(fsa_instance);
+    /// Tworzy słownik nad bajtami pliku `.dict` typu `S` (`Vec<u8>`, `&'static [u8]`,
+    /// `Arc<[u8]>`, mapowanie pamięci); automat czyta łuki bezpośrednio z nich.
+    pub fn from_bytes<S>(fsa_bytes: S, metadata: DictionaryMetadata) -> StemmingResult<Self>
+    where
+        S: AsRef<[u8]> + Debug + Send + Sync + 'static,
+    {
+        Self::from_bytes_with_registry(fsa_bytes, metadata, &FsaRegistry::new())
+    }
 
-        let encoder_type_from_meta = arc_metadata.get_encoder()?;
-        let separator = arc_metadata.get_separator()?;
+    /// Jak [`Dictionary::from_bytes`], ale od razu buduje indeksy łuków węzłów o dużej
+    /// liczbie łuków (patrz [`JumpTables`](morfologik_fsa::jump_tables::JumpTables)) w
+    /// ramach podanego budżetu pamięci.
+    pub fn from_bytes_with_jump_tables<S>(
+        fsa_bytes: S,
+        metadata: DictionaryMetadata,
+        options: JumpTableOptions,
+    ) -> StemmingResult<Self>
+    where
+        S: AsRef<[u8]> + Debug + Send + Sync + 'static,
+    {
+        Self::from_bytes_with_registry(fsa_bytes, metadata, &FsaRegistry::new().with_jump_tables(options))
+    }
 
-        let sequence_encoder: Box<dyn SequenceEncoder + Send + Sync> = match encoder_type_from_meta {
-            EncoderType::None => Box::new(NoEncoder::new()),
-            EncoderType::Suffix => Box::new(TrimSuffixEncoder::new(separator as u8)),
-            EncoderType::Prefix => Box::new(TrimPrefixEncoder::new(separator as u8)),
-            EncoderType::Infix => Box::new(TrimInfixAndSuffixEncoder::new(separator as u8)),
-        };
-        let arc_encoder = Arc::new(sequence_encoder);
+    /// Jak [`Dictionary::from_bytes`], ale format automatu rozpoznaje podany rejestr
+    /// (np. z dodatkowymi formatami).
+    pub fn from_bytes_with_registry<S>(
+        fsa_bytes: S,
+        metadata: DictionaryMetadata,
+        registry: &FsaRegistry,
+    ) -> StemmingResult<Self>
+    where
+        S: AsRef<[u8]> + Debug + Send + Sync + 'static,
+    {
+        Self::from_fsa(registry.load(fsa_bytes)?, metadata)
+    }
 
+    /// Tworzy słownik z gotowego automatu, np. zbudowanego w pamięci i zdekodowanego
+    /// przez [`ValidatedFsa`](morfologik_fsa::validated::ValidatedFsa); enkoder wybiera
+    /// według metadanych.
+    pub fn from_fsa<F: Into<AnyFsa>>(fsa: F, metadata: DictionaryMetadata) -> StemmingResult<Self> {
+        let encoder = create_encoder(&metadata.get_encoder()?, metadata.get_separator()? as u8);
         Ok(Dictionary {
-            fsa: arc_fsa,
-            metadata: arc_metadata,
-            encoder: arc_encoder,
+            fsa: Arc::new(fsa.into()),
+            metadata: Arc::new(metadata),
+            encoder: Arc::new(encoder),
         })
     }
 
+    /// Zwraca formy fleksyjne ze słownika zaczynające się od `prefix` (bez lematów i tagów),
+    /// leniwie i w kolejności leksykograficznej, z pominięciem `skip` pierwszych form.
+    pub fn complete(
+        &self,
+        prefix: &[u8],
+        limit: Option<usize>,
+        skip: usize,
+    ) -> StemmingResult<Completions<'_, AnyFsa>> {
+        let separator = self.metadata.get_separator()? as u8;
+        let options = CompletionOptions { limit, skip, separator: Some(separator) };
+        Completions::new(self.fsa.as_ref(), prefix, options).map_err(StemmingError::Fsa)
+    }
+
+    /// Sprawdza strukturę automatu słownika (patrz [`validate`]). Słowniki z niezaufanych
+    /// źródeł można odrzucić od razu po wczytaniu: `dictionary.validate().into_result()?`.
+    pub fn validate(&self) -> ValidationReport {
+        validate(self.fsa.as_ref())
+    }
 
     pub fn get_info_file_path<P: AsRef<Path>>(dictionary_file_path: P) -> PathBuf {
         dictionary_file_path.as_ref().with_extension("info")
@@ -109,9 +174,14 @@ impl Dictionary {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dictionary_metadata::EncoderType;
+    use crate::sequence_encoder_trait::SequenceEncoder;
+    use morfologik_fsa::fsa_trait::Fsa;
+    use std::fs::File;
     use std::io::Write;
     use tempfile::NamedTempFile;
-    use morfologik_fsa::fsa_header::FSA_MAGIC; 
+    use morfologik_fsa::error::FsaError;
+    use morfologik_fsa::fsa_header::{FSA_MAGIC, VERSION_CFSA2, VERSION_FSA5};
 
     fn create_test_info_file_content(sep: char, enc_type: EncoderType, fsa_ver_str: &str) -> String {
         format!(
@@ -123,25 +193,25 @@ mod tests {
         )
     }
 
+    // Pusty automat FSA5: bajt wypełnienia, separator, gtl = 1, pusty węzeł stanu końcowego
+    // i łuk epsilon '^' do adresu 0 (korzeń bez łuków).
     fn create_test_fsa5_dict_bytes() -> Vec<u8> {
         let mut data = Vec::new();
         data.extend_from_slice(&FSA_MAGIC);
-        data.extend_from_slice(&[VERSION_FSA5]); 
-        data.extend_from_slice(&[1]); 
-        data.extend_from_slice(&[1]); 
-        data.extend_from_slice(&[0u8, 0u8]); 
+        data.extend_from_slice(&[VERSION_FSA5, b'_', b'+', 0x01]);
+        data.extend_from_slice(&[0, 0, b'^', morfologik_fsa::fsa5::BIT_ARC_LAST]);
         data
     }
     
-    // Ta funkcja teraz tworzy poprawny nagłówek dla CFSA2 (wersja 0xC6)
+    // Pusty automat CFSA2 (wersja 0xC6): flagi big-endian, pusta tablica etykiet
+    // i węzeł epsilon z łukiem '^' do adresu 0 (korzeń bez łuków).
     fn create_test_cfsa2_dict_bytes() -> Vec<u8> {
         let mut data = Vec::new();
         data.extend_from_slice(&FSA_MAGIC);
-        data.extend_from_slice(&[VERSION_CFSA2]); // Używa poprawionej VERSION_CFSA2 = 0xC6
-        let fsa_flags_val = morfologik_fsa::fsa_header::FsaFlags::empty().bits();
-        let gtl_info_val = 0u8; 
-        let automaton_flags_short = (fsa_flags_val & 0x00FF) | ((gtl_info_val as u16) << 8);
-        data.extend_from_slice(&automaton_flags_short.to_le_bytes());
+        data.extend_from_slice(&[VERSION_CFSA2]);
+        data.extend_from_slice(&morfologik_fsa::fsa_header::FsaFlags::empty().bits().to_be_bytes());
+        data.push(0);
+        data.extend_from_slice(&[morfologik_fsa::cfsa2::CFSA_ARC_IS_LAST_BIT, b'^', 0]);
         data
     }
     
@@ -149,7 +219,9 @@ mod tests {
 
 
      fn create_test_info_file(content: &str) -> NamedTempFile {
-        let mut temp_file = NamedTempFile::new().unwrap();
+        // Rozszerzenie `.info` jest potrzebne, by `with_extension("dict")`
+        // dało ścieżkę, z której `Dictionary::from_file` odnajdzie ten plik.
+        let mut temp_file = NamedTempFile::with_suffix(".info").unwrap();
         write!(temp_file, "{}", content).unwrap();
         temp_file.flush().unwrap();
         temp_file
@@ -173,7 +245,7 @@ mod tests {
         let dict_bytes_vec = create_test_fsa5_dict_bytes();
         let info_bytes_vec = info_content_str.into_bytes(); 
 
-        let dict = Dictionary::from_embedded(&dict_bytes_vec, &info_bytes_vec)?;
+        let dict = Dictionary::from_embedded(dict_bytes_vec, &info_bytes_vec)?;
 
         assert_eq!(dict.metadata.get_separator()?, '+');
         assert_eq!(dict.metadata.get_encoder()?, EncoderType::None);
@@ -189,7 +261,7 @@ mod tests {
         let dict_bytes_vec = create_test_cfsa2_dict_bytes();
         let info_bytes_vec = info_content_str.into_bytes(); 
         
-        let dict = Dictionary::from_embedded(&dict_bytes_vec, &info_bytes_vec)?;
+        let dict = Dictionary::from_embedded(dict_bytes_vec, &info_bytes_vec)?;
         
         assert_eq!(dict.metadata.get_separator()?, '|');
         assert_eq!(dict.metadata.get_encoder()?, EncoderType::Suffix);
@@ -272,4 +344,167 @@ mod tests {
         let result = Dictionary::from_file(&dict_path_v3);
         assert!(matches!(result, Err(StemmingError::Fsa(FsaError::UnsupportedVersion(3)))));
     }
+
+    #[test]
+    fn test_dictionary_from_shared_bytes() -> StemmingResult<()> {
+        let info_content = create_test_info_file_content('+', EncoderType::None, "FSA5");
+        let metadata = DictionaryMetadata::from_bytes(info_content.as_bytes())?;
+        let shared: Arc<[u8]> = create_test_fsa5_dict_bytes().into();
+
+        let dict = Dictionary::from_bytes(Arc::clone(&shared), metadata)?;
+
+        assert_eq!(dict.fsa.get_root_node(), 0);
+        assert_eq!(Arc::strong_count(&shared), 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_dictionary_from_file_mapped() -> StemmingResult<()> {
+        let info_content = create_test_info_file_content('+', EncoderType::Suffix, "CFSA2");
+        let info_file = create_test_info_file(&info_content);
+        let dict_path = info_file.path().with_extension("dict");
+        create_test_cfsa2_dict_file(&dict_path);
+
+        let dict = unsafe { Dictionary::from_file_mapped(&dict_path)? };
+
+        assert_eq!(dict.metadata.get_encoder()?, EncoderType::Suffix);
+        assert_eq!(dict.fsa.get_flags(), morfologik_fsa::fsa_header::FsaFlags::empty());
+        std::fs::remove_file(dict_path).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_complete_inflected_forms() -> StemmingResult<()> {
+        use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+        use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+        use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+
+        let entries: [&[u8]; 5] = [b"kot+kot+sg", b"kota+kot+gen", b"kotem+kot+inst", b"koty+kot+pl", b"pies+pies+sg"];
+        let automaton = FsaBuilder::build(entries.iter()).unwrap();
+        let mut fsa_bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&automaton, &mut fsa_bytes).unwrap();
+        let info = create_test_info_file_content('+', EncoderType::None, "FSA5");
+        let dict = Dictionary::from_embedded(fsa_bytes, info.as_bytes())?;
+
+        let forms: Vec<Vec<u8>> = dict.complete(b"kot", None, 0)?.collect::<Result<_, _>>()?;
+        assert_eq!(forms, vec![b"kot".to_vec(), b"kota".to_vec(), b"kotem".to_vec(), b"koty".to_vec()]);
+        let page: Vec<Vec<u8>> = dict.complete(b"", Some(2), 3)?.collect::<Result<_, _>>()?;
+        assert_eq!(page, vec![b"koty".to_vec(), b"pies".to_vec()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_rejects_truncated_automaton() -> StemmingResult<()> {
+        use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+        use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+        use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+
+        let entries: [&[u8]; 3] = [b"kot+kot+sg", b"kota+kot+gen", b"pies+pies+sg"];
+        let mut fsa_bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&FsaBuilder::build(entries.iter()).unwrap(), &mut fsa_bytes).unwrap();
+        let info = create_test_info_file_content('+', EncoderType::None, "FSA5");
+
+        let dict = Dictionary::from_embedded(fsa_bytes.clone(), info.as_bytes())?;
+        assert!(dict.validate().is_valid());
+
+        let info_file = create_test_info_file(&info);
+        let dict_path = info_file.path().with_extension("dict");
+        std::fs::write(&dict_path, &fsa_bytes).unwrap();
+        assert!(Dictionary::from_file_validated(&dict_path)?.validate().is_valid());
+
+        fsa_bytes.truncate(fsa_bytes.len() - 4);
+        let dict = Dictionary::from_embedded(fsa_bytes.clone(), info.as_bytes())?;
+        assert!(dict.validate().into_result().is_err());
+
+        std::fs::write(&dict_path, &fsa_bytes).unwrap();
+        assert!(Dictionary::from_file(&dict_path).is_ok());
+        assert_matches::assert_matches!(
+            Dictionary::from_file_validated(&dict_path),
+            Err(StemmingError::Fsa(FsaError::CorruptedFsa(_)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_with_jump_tables() -> StemmingResult<()> {
+        use crate::dictionary_lookup::DictionaryLookup;
+        use crate::stemmer_trait::Stemmer;
+        use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+        use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+        use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+
+        let mut entries: Vec<Vec<u8>> = (b'a'..=b'z').map(|first| vec![first, b'+', first, b'+', b't']).collect();
+        entries.push(b"kot+kot+sg".to_vec());
+        entries.sort();
+        let mut fsa_bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&FsaBuilder::build(entries.iter()).unwrap(), &mut fsa_bytes).unwrap();
+        let info = create_test_info_file_content('+', EncoderType::None, "FSA5");
+        let metadata = DictionaryMetadata::from_bytes(info.as_bytes())?;
+        let options = JumpTableOptions { min_fan_out: 4, ..Default::default() };
+        let dict = Dictionary::from_bytes_with_jump_tables(fsa_bytes, metadata, options)?;
+
+        assert_matches::assert_matches!(dict.fsa.get_arc(dict.fsa.get_root_node(), b'0'), Err(FsaError::ArcNotFound { .. }));
+        let lookup = DictionaryLookup::new(Arc::new(dict));
+        assert_eq!(lookup.lookup(b"kot")?[0].stem(), Some(&b"kot"[..]));
+        assert_eq!(lookup.lookup(b"q")?[0].tag(), Some(&b"t"[..]));
+        assert!(lookup.lookup(b"ko")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_from_legacy_cfsa() -> StemmingResult<()> {
+        use crate::dictionary_lookup::DictionaryLookup;
+        use crate::stemmer_trait::Stemmer;
+        use morfologik_fsa::cfsa::{CFSA_ARC_IS_FINAL_BIT, CFSA_ARC_IS_LAST_BIT, CFSA_ARC_TARGET_IS_NEXT_BIT};
+        use morfologik_fsa::fsa_header::VERSION_CFSA;
+
+        // Jedna sekwencja "ab+X+": pusta tablica etykiet, etykiety zapisane wprost, każdy
+        // węzeł zaraz za łukiem; ostatni łuk prowadzi do pustego węzła pod adresem 0.
+        let mut data = FSA_MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION_CFSA, b'_', b'+', 0x01]);
+        data.extend_from_slice(&[0; morfologik_fsa::cfsa::CFSA_LABEL_MAPPING_SIZE]);
+        data.extend_from_slice(&[0, 0]);
+        for &label in b"^ab+X" {
+            data.extend_from_slice(&[CFSA_ARC_IS_LAST_BIT | CFSA_ARC_TARGET_IS_NEXT_BIT, label]);
+        }
+        data.extend_from_slice(&[CFSA_ARC_IS_LAST_BIT | CFSA_ARC_IS_FINAL_BIT, b'+']);
+        let info = create_test_info_file_content('+', EncoderType::None, "CFSA");
+        let dict = Dictionary::from_embedded(data, info.as_bytes())?;
+        assert!(dict.validate().is_valid());
+
+        let forms = DictionaryLookup::new(Arc::new(dict)).lookup(b"ab")?;
+        assert_eq!(forms.len(), 1);
+        assert_eq!(forms[0].stem(), Some(&b"X"[..]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_from_in_memory_fsa() -> StemmingResult<()> {
+        use crate::dictionary_lookup::DictionaryLookup;
+        use crate::stemmer_trait::Stemmer;
+        use morfologik_fsa::validated::ValidatedFsa;
+
+        let info = create_test_info_file_content('+', EncoderType::None, "FSA5");
+        let loaded = Dictionary::from_embedded(create_test_fsa5_dict_bytes(), info.as_bytes())?;
+        assert!(matches!(loaded.fsa.as_ref(), AnyFsa::Fsa5(_)));
+
+        use morfologik_fsa::fsa5::{BIT_ARC_FINAL, BIT_ARC_LAST, BIT_ARC_TARGET_NEXT};
+        let mut data = FSA_MAGIC.to_vec();
+        data.extend_from_slice(&[VERSION_FSA5, b'_', b'+', 0x01, 0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT]);
+        for (i, &label) in b"ab+X+".iter().enumerate() {
+            let flags = if i == 4 { BIT_ARC_FINAL | BIT_ARC_LAST } else { BIT_ARC_LAST | BIT_ARC_TARGET_NEXT };
+            data.extend_from_slice(&[label, flags]);
+        }
+        let source = Dictionary::from_embedded(data, info.as_bytes())?;
+        let in_memory = ValidatedFsa::new(source.fsa.as_ref())?;
+        let dict = Dictionary::from_fsa(in_memory, DictionaryMetadata::from_bytes(info.as_bytes())?)?;
+        assert!(matches!(dict.fsa.as_ref(), AnyFsa::Validated(_)));
+        assert_eq!(dict.encoder.get_encoder_type(), EncoderType::None);
+
+        let forms = DictionaryLookup::new(Arc::new(dict)).lookup(b"ab")?;
+        assert_eq!(forms.len(), 1);
+        assert_eq!(forms[0].stem(), Some(&b"X"[..]));
+        Ok(())
+    }
 }
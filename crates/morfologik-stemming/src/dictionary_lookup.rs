@@ -2,11 +2,13 @@
 
 use std::sync::Arc;
 
-// morfologik_fsa::fsa_trait::Fsa jest używane w typie Box<dyn Fsa...>
-use morfologik_fsa::iterator::ByteSequenceIterator; 
+use morfologik_fsa::fsa_trait::{Fsa, Node};
+use morfologik_fsa::iterator::SequenceCursor;
+use morfologik_fsa::validated::ValidatedFsa;
 
 use crate::dictionary::Dictionary;
 use crate::word_data::WordData;
+use crate::sequence_encoder_trait::SequenceEncoder;
 use crate::stemmer_trait::Stemmer;
 use crate::error::{Result as StemmingResult, StemmingError}; 
 use crate::dictionary_metadata::DictionaryMetadata; 
@@ -16,83 +18,113 @@ use crate::dictionary_metadata::DictionaryMetadata;
 #[derive(Debug, Clone)] 
 pub struct DictionaryLookup {
     dictionary: Arc<Dictionary>,
+    /// Separator z metadanych, odczytany raz zamiast przy każdym wyszukiwaniu; `None`, gdy
+    /// metadane go nie określają poprawnie (błąd zgłasza wtedy `lookup`).
+    separator: Option<u8>,
+    /// Zwalidowana kopia automatu słownika, jeśli wyszukiwanie ma z niej korzystać.
+    validated: Option<Arc<ValidatedFsa>>,
 }
 
 impl DictionaryLookup {
     pub fn new(dictionary: Arc<Dictionary>) -> Self {
-        DictionaryLookup { dictionary }
+        let separator = Self::cached_separator(&dictionary);
+        DictionaryLookup { dictionary, separator, validated: None }
     }
 
-    fn split_decoded_data_to_stem_and_tag<'a>(
-        &self,
-        decoded_data: &'a [u8],
-        separator: u8,
-    ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
-        if let Some(sep_pos) = decoded_data.iter().position(|&b| b == separator) {
-            let stem_part = &decoded_data[..sep_pos];
-            let tag_part = &decoded_data[sep_pos + 1..];
-
-            let stem = if stem_part.is_empty() { None } else { Some(stem_part.to_vec()) };
-            let tag = if tag_part.is_empty() { None } else { Some(tag_part.to_vec()) };
-            (stem, tag)
-        } else {
-            (Some(decoded_data.to_vec()).filter(|s| !s.is_empty()), None)
-        }
+    /// Tworzy wyszukiwarkę korzystającą z [`ValidatedFsa`]: automat jest raz sprawdzany
+    /// i dekodowany, a przejście po słowie nie sprawdza już błędów odczytu.
+    ///
+    /// Dla uszkodzonego automatu zwraca [`StemmingError::Fsa`].
+    pub fn validated(dictionary: Arc<Dictionary>) -> StemmingResult<Self> {
+        let validated = ValidatedFsa::new(dictionary.fsa.as_ref())?;
+        let separator = Self::cached_separator(&dictionary);
+        Ok(DictionaryLookup { dictionary, separator, validated: Some(Arc::new(validated)) })
     }
-}
 
-impl Stemmer for DictionaryLookup {
-    fn lookup(&self, word: &[u8]) -> StemmingResult<Vec<WordData>> {
-        let mut forms: Vec<WordData> = Vec::new();
-
-        let fsa = self.dictionary.fsa.as_ref().as_ref(); 
-        let encoder = self.dictionary.encoder.as_ref().as_ref(); 
-        let metadata = self.dictionary.metadata.as_ref(); 
+    fn cached_separator(dictionary: &Dictionary) -> Option<u8> {
+        dictionary.metadata.get_separator().ok().map(|separator| separator as u8)
+    }
 
+    /// Węzeł po przejściu `word` i separatora albo `None`, jeśli słowa nie ma w słowniku.
+    ///
+    /// Jak w DictionaryLookup.java łuk separatora nie może być końcowy: za nim zaczyna
+    /// się zakodowana forma podstawowa.
+    fn find_values_node<F: Fsa + ?Sized>(&self, fsa: &F, word: &[u8], separator: u8) -> Option<Node> {
         let mut current_node = fsa.get_root_node();
-        let mut word_path_exists = true;
-        for &byte in word.iter() {
-            match fsa.get_arc(current_node, byte) {
-                Ok(arc) => {
-                    match fsa.get_end_node(arc) {
-                        Ok(next_node) => current_node = next_node,
-                        Err(_) => { 
-                            word_path_exists = false;
-                            break;
-                        }
-                    }
-                }
-                Err(_) => { 
-                    word_path_exists = false;
-                    break;
-                }
-            }
+        for &byte in word {
+            let arc = fsa.get_arc(current_node, byte).ok()?;
+            current_node = fsa.get_end_node(arc).ok()?;
         }
-
-        if !word_path_exists {
-            return Ok(forms); 
+        let arc = fsa.get_arc(current_node, separator).ok()?;
+        if fsa.is_arc_final(arc).ok()? {
+            return None;
         }
+        fsa.get_end_node(arc).ok()
+    }
 
-        let value_iterator = ByteSequenceIterator::from_node(fsa, current_node);
-        let separator = metadata.get_separator()?; 
+    /// Dekoduje wszystkie formy zapisane za węzłem `values_node`.
+    fn collect_forms<F: Fsa + ?Sized>(
+        &self,
+        fsa: &F,
+        values_node: Node,
+        word: &[u8],
+        separator: u8,
+    ) -> StemmingResult<Vec<WordData>> {
+        let encoder = self.dictionary.encoder.as_ref();
+        let mut forms: Vec<WordData> = Vec::new();
+        let mut values = SequenceCursor::from_node(fsa, values_node);
 
-        for encoded_data_result in value_iterator {
+        while let Some(encoded_data_result) = values.advance() {
             let encoded_data_from_fsa = encoded_data_result.map_err(StemmingError::Fsa)?;
-            
-            let decoded_stem_plus_tag = encoder.decode(word, &encoded_data_from_fsa)?;
 
-            let (stem_bytes, tag_bytes) =
-                self.split_decoded_data_to_stem_and_tag(&decoded_stem_plus_tag, separator as u8);
+            // Zakodowana forma podstawowa kończy się na pierwszym separatorze, dalej jest tag.
+            let sep_pos = encoded_data_from_fsa.iter().position(|&b| b == separator).ok_or_else(|| {
+                StemmingError::UnexpectedDataFormat(format!(
+                    "wpis słownika bez separatora tagu: '{}'",
+                    String::from_utf8_lossy(encoded_data_from_fsa)
+                ))
+            })?;
+            let stem = encoder.decode(word, &encoded_data_from_fsa[..sep_pos])?;
+            let tag = &encoded_data_from_fsa[sep_pos + 1..];
 
             forms.push(WordData::new(
                 word.to_vec(), 
-                stem_bytes,    
-                tag_bytes,     
+                Some(stem).filter(|s| !s.is_empty()),
+                Some(tag.to_vec()).filter(|t| !t.is_empty()),
             ));
         }
 
         Ok(forms)
     }
+}
+
+impl Stemmer for DictionaryLookup {
+    fn lookup(&self, word: &[u8]) -> StemmingResult<Vec<WordData>> {
+        let separator = match self.separator {
+            Some(separator) => separator,
+            None => self.dictionary.metadata.get_separator()? as u8,
+        };
+
+        // Słowo z separatorem nie może być zapisane w słowniku.
+        if word.contains(&separator) {
+            return Ok(Vec::new());
+        }
+
+        // Sekwencje w automacie mają postać: słowo + separator + zakodowane dane.
+        if let Some(fsa) = self.validated.as_deref() {
+            let arc = fsa.walk(fsa.root(), word).and_then(|node| fsa.find_arc(node, separator));
+            return match arc.filter(|&arc| !fsa.is_final(arc)).map(|arc| fsa.target(arc)) {
+                Some(node) => self.collect_forms(fsa, node, word, separator),
+                None => Ok(Vec::new()),
+            };
+        }
+
+        let fsa = self.dictionary.fsa.as_ref();
+        match self.find_values_node(fsa, word, separator) {
+            Some(node) => self.collect_forms(fsa, node, word, separator),
+            None => Ok(Vec::new()),
+        }
+    }
 
     fn get_dictionary_metadata(&self) -> &DictionaryMetadata {
         &self.dictionary.metadata
@@ -104,41 +136,39 @@ mod tests {
     use super::*;
     use crate::dictionary::Dictionary;
     use crate::dictionary_metadata::{DictionaryMetadata, EncoderType};
-    use crate::encoders::NoEncoder; 
-    use morfologik_fsa::fsa_trait::Fsa; // Potrzebne dla Box<dyn Fsa>
-    use morfologik_fsa::fsa5::FSA5; 
-    use morfologik_fsa::fsa_header::{FSA_MAGIC, VERSION_FSA5, FsaFlags};
-    use std::io::Cursor; 
-
-    fn create_test_fsa_for_lookup(key: &[u8], value_as_single_sequence: &[u8]) -> FSA5 {
-        let mut arcs_data = Vec::new();
-        let combined_sequence: Vec<u8> = key.iter().chain(value_as_single_sequence.iter()).cloned().collect();
+    use crate::encoders::{AnyEncoder, NoEncoder};
+    use morfologik_fsa::any_fsa::AnyFsa;
+    use morfologik_fsa::fsa5::{FSA5, BIT_ARC_FINAL, BIT_ARC_LAST, BIT_ARC_TARGET_NEXT};
+    use morfologik_fsa::loader::FsaBytes;
+    use morfologik_fsa::fsa_header::{FSA_MAGIC, VERSION_FSA5};
+
+    fn create_test_fsa_for_lookup(key: &[u8], value_as_single_sequence: &[u8]) -> AnyFsa {
+        let combined_sequence: Vec<u8> = key.iter()
+            .chain(std::iter::once(&b'+'))
+            .chain(value_as_single_sequence.iter())
+            .cloned()
+            .collect();
         let total_len = combined_sequence.len();
 
+        // Pusty węzeł stanu końcowego i łuk epsilon do korzenia (zapisanego zaraz za nim).
+        let mut arcs_data = vec![0, 0, b'^', BIT_ARC_LAST | BIT_ARC_TARGET_NEXT];
         for (i, &byte_val) in combined_sequence.iter().enumerate() {
             arcs_data.push(byte_val); 
-            let mut arc_flags = 0u8;
-            if i == total_len - 1 { 
-                arc_flags |= morfologik_fsa::fsa5::BIT_ARC_FINAL;
-            }
-            if i == total_len - 1 { 
-                 arc_flags |= morfologik_fsa::fsa5::BIT_ARC_LAST;
-            }
+            // Każdy stan na ścieżce ma dokładnie jeden łuk wychodzący, a następny stan
+            // leży zaraz za nim; ostatni łuk prowadzi do stanu końcowego (adres 0).
+            let arc_flags = if i == total_len - 1 {
+                BIT_ARC_LAST | BIT_ARC_FINAL
+            } else {
+                BIT_ARC_LAST | BIT_ARC_TARGET_NEXT
+            };
             arcs_data.push(arc_flags);
-            arcs_data.push(if i < total_len - 1 { ((i + 1) * 3) as u8 } else { 0 }); 
         }
         
-        let mut header_data = Vec::new();
-        header_data.extend_from_slice(&FSA_MAGIC);
-        header_data.push(VERSION_FSA5);
-        header_data.push(1); 
-        header_data.push(1); 
-        header_data.extend_from_slice(&FsaFlags::empty().bits().to_le_bytes()); 
-
-        let mut fsa_file_bytes = header_data;
+        let mut fsa_file_bytes = FSA_MAGIC.to_vec();
+        fsa_file_bytes.extend_from_slice(&[VERSION_FSA5, b'_', b'+', 0x01]);
         fsa_file_bytes.extend_from_slice(&arcs_data);
         
-        FSA5::from_reader(&mut Cursor::new(fsa_file_bytes)).unwrap()
+        AnyFsa::from(FSA5::from_bytes(FsaBytes::new(fsa_file_bytes)).unwrap())
     }
 
     #[test]
@@ -153,7 +183,7 @@ mod tests {
         value_to_encode.extend_from_slice(tag_val); 
 
         let fsa_impl = create_test_fsa_for_lookup(key, &value_to_encode);
-        let fsa: Arc<Box<dyn Fsa + Send + Sync>> = Arc::new(Box::new(fsa_impl));
+        let fsa = Arc::new(fsa_impl);
 
 
         let mut metadata = DictionaryMetadata::new();
@@ -161,8 +191,7 @@ mod tests {
         metadata.set_attribute("fsa.dict.encoder".to_string(), EncoderType::None.as_str().to_string());
         let arc_metadata = Arc::new(metadata);
 
-        let encoder: Arc<Box<dyn crate::sequence_encoder_trait::SequenceEncoder + Send + Sync>> 
-            = Arc::new(Box::new(NoEncoder::new()));
+        let encoder = Arc::new(AnyEncoder::None(NoEncoder::new()));
         
         let dictionary = Arc::new(Dictionary {
             fsa,
@@ -186,15 +215,14 @@ mod tests {
         let key = b"jest";
         let value = b"BYC+VERB";
         let fsa_impl = create_test_fsa_for_lookup(key, value);
-        let fsa: Arc<Box<dyn Fsa + Send + Sync>> = Arc::new(Box::new(fsa_impl));
+        let fsa = Arc::new(fsa_impl);
         
         let mut metadata = DictionaryMetadata::new();
         metadata.set_attribute("fsa.dict.separator".to_string(), "+".to_string());
         metadata.set_attribute("fsa.dict.encoder".to_string(), EncoderType::None.as_str().to_string());
         let arc_metadata = Arc::new(metadata);
         
-        let encoder: Arc<Box<dyn crate::sequence_encoder_trait::SequenceEncoder + Send + Sync>> 
-            = Arc::new(Box::new(NoEncoder::new()));
+        let encoder = Arc::new(AnyEncoder::None(NoEncoder::new()));
         
         let dictionary = Arc::new(Dictionary { fsa, metadata: arc_metadata, encoder });
         let lookup_service = DictionaryLookup::new(dictionary);
@@ -208,15 +236,14 @@ mod tests {
         let key = b"a"; 
         let value = b"A+X";
         let fsa_impl = create_test_fsa_for_lookup(key, value);
-        let fsa: Arc<Box<dyn Fsa + Send + Sync>> = Arc::new(Box::new(fsa_impl));
+        let fsa = Arc::new(fsa_impl);
         
         let mut metadata = DictionaryMetadata::new();
         metadata.set_attribute("fsa.dict.separator".to_string(), "+".to_string());
         metadata.set_attribute("fsa.dict.encoder".to_string(), EncoderType::None.as_str().to_string());
         let arc_metadata = Arc::new(metadata);
         
-        let encoder: Arc<Box<dyn crate::sequence_encoder_trait::SequenceEncoder + Send + Sync>>
-            = Arc::new(Box::new(NoEncoder::new()));
+        let encoder = Arc::new(AnyEncoder::None(NoEncoder::new()));
         
         let dictionary = Arc::new(Dictionary { fsa, metadata: arc_metadata, encoder });
         let lookup_service = DictionaryLookup::new(dictionary);
@@ -224,4 +251,38 @@ mod tests {
         let results = lookup_service.lookup(b"").expect("Lookup for empty word failed");
         assert!(results.is_empty(), "Expected no results for an empty word if not in FSA");
     }
+
+    #[test]
+    fn test_invalid_separator_is_reported_by_lookup() {
+        let mut metadata = DictionaryMetadata::new();
+        metadata.set_attribute("fsa.dict.separator".to_string(), "++".to_string());
+        let dictionary = Arc::new(Dictionary {
+            fsa: Arc::new(create_test_fsa_for_lookup(b"kot", b"KOT+SUBST")),
+            metadata: Arc::new(metadata),
+            encoder: Arc::new(AnyEncoder::None(NoEncoder::new())),
+        });
+        let lookup_service = DictionaryLookup::new(dictionary);
+        assert!(matches!(lookup_service.lookup(b"kot"), Err(StemmingError::InvalidMetadataValue(_))));
+    }
+
+    #[test]
+    fn test_validated_lookup_matches_checked_lookup() -> StemmingResult<()> {
+        use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+        use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+        use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+
+        let entries: [&[u8]; 5] = [b"kot+kot+sg", b"kota+kot+gen", b"koty+kot+pl", b"koty+koty+pl", b"pies+pies+sg"];
+        let mut fsa_bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&FsaBuilder::build(entries.iter()).unwrap(), &mut fsa_bytes).unwrap();
+        let info = "fsa.dict.separator = +\nfsa.dict.encoder = NONE\nfsa.version = FSA5";
+        let dictionary = Arc::new(Dictionary::from_embedded(fsa_bytes, info.as_bytes())?);
+
+        let checked = DictionaryLookup::new(dictionary.clone());
+        let validated = DictionaryLookup::validated(dictionary)?;
+        for word in [&b"kot"[..], b"koty", b"pies", b"ko", b"psy", b""] {
+            assert_eq!(validated.lookup(word)?, checked.lookup(word)?);
+        }
+        assert_eq!(validated.lookup(b"koty")?.len(), 2);
+        Ok(())
+    }
 }
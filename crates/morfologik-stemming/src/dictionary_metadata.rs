@@ -181,6 +181,14 @@ impl DictionaryMetadata {
             })
     }
     
+    /// Wszystkie atrybuty posortowane według klucza.
+    pub fn attributes(&self) -> Vec<(&str, &str)> {
+        let mut attributes: Vec<(&str, &str)> =
+            self.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        attributes.sort_unstable();
+        attributes
+    }
+
     pub fn set_attribute(&mut self, key: String, value: String) {
         self.attributes.insert(key, value);
     }
@@ -223,6 +231,13 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_attributes_sorted() -> Result<()> {
+        let metadata = DictionaryMetadata::from_bytes(b"fsa.dict.separator = +\nfsa.dict.encoder = SUFFIX")?;
+        assert_eq!(metadata.attributes(), vec![("fsa.dict.encoder", "SUFFIX"), ("fsa.dict.separator", "+")]);
+        Ok(())
+    }
+
     #[test]
     fn test_from_bytes_basic() -> Result<()> {
         let info_content = "fsa.dict.separator = *\n\
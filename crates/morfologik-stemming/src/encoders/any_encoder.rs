@@ -0,0 +1,91 @@
+// Enkoder jednego z wbudowanych typów z wywołaniami bez tablicy metod wirtualnych
+
+use crate::dictionary_metadata::EncoderType;
+use crate::error::Result as StemmingResult;
+use crate::sequence_encoder_trait::SequenceEncoder;
+
+use super::{NoEncoder, TrimInfixAndSuffixEncoder, TrimPrefixAndSuffixEncoder, TrimSuffixEncoder};
+
+/// Jeden z wbudowanych enkoderów albo dowolna implementacja [`SequenceEncoder`].
+///
+/// Dekodowanie w [`DictionaryLookup`](crate::dictionary_lookup::DictionaryLookup)
+/// wywołuje enkoder dla każdej formy; wariant wbudowanego enkodera nie wymaga przy tym
+/// wywołania wirtualnego. Własne enkodery trafiają do [`AnyEncoder::Other`].
+#[derive(Debug)]
+pub enum AnyEncoder {
+    None(NoEncoder),
+    Suffix(TrimSuffixEncoder),
+    /// Enkoder typu `PREFIX`; jak w Javie obcina prefiks i sufiks formy fleksyjnej.
+    Prefix(TrimPrefixAndSuffixEncoder),
+    Infix(TrimInfixAndSuffixEncoder),
+    Other(Box<dyn SequenceEncoder + Send + Sync>),
+}
+
+/// Wywołuje metodę na enkoderze z aktywnego wariantu.
+macro_rules! dispatch {
+    ($any:expr, $encoder:ident => $call:expr) => {
+        match $any {
+            AnyEncoder::None($encoder) => $call,
+            AnyEncoder::Suffix($encoder) => $call,
+            AnyEncoder::Prefix($encoder) => $call,
+            AnyEncoder::Infix($encoder) => $call,
+            AnyEncoder::Other($encoder) => $call,
+        }
+    };
+}
+
+impl AnyEncoder {
+    /// Tworzy enkoder danego typu (jak `EncoderType.get()` w Javie).
+    pub fn new(encoder_type: &EncoderType, separator: u8) -> Self {
+        match encoder_type {
+            EncoderType::None => AnyEncoder::None(NoEncoder::new()),
+            EncoderType::Suffix => AnyEncoder::Suffix(TrimSuffixEncoder::new(separator)),
+            EncoderType::Prefix => AnyEncoder::Prefix(TrimPrefixAndSuffixEncoder::new(separator)),
+            EncoderType::Infix => AnyEncoder::Infix(TrimInfixAndSuffixEncoder::new(separator)),
+        }
+    }
+}
+
+impl SequenceEncoder for AnyEncoder {
+    #[inline]
+    fn encode<'a>(&self, input: &'a [u8], data: &'a [u8]) -> StemmingResult<Vec<u8>> {
+        dispatch!(self, encoder => encoder.encode(input, data))
+    }
+
+    #[inline]
+    fn decode<'a>(&self, input: &'a [u8], encoded_data: &'a [u8]) -> StemmingResult<Vec<u8>> {
+        dispatch!(self, encoder => encoder.decode(input, encoded_data))
+    }
+
+    fn get_separator(&self) -> Option<u8> {
+        dispatch!(self, encoder => encoder.get_separator())
+    }
+
+    fn get_encoder_type(&self) -> EncoderType {
+        dispatch!(self, encoder => encoder.get_encoder_type())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_wrapped_encoder() {
+        use crate::encoders::TrimPrefixEncoder;
+
+        let encoders = [
+            (AnyEncoder::new(&EncoderType::Suffix, b'+'), TrimSuffixEncoder::new(b'+').encode(b"kotami", b"kot")),
+            (AnyEncoder::new(&EncoderType::Prefix, b'+'), TrimPrefixAndSuffixEncoder::new(b'+').encode(b"kotami", b"kot")),
+            (AnyEncoder::Other(Box::new(TrimPrefixEncoder::new(b'+'))), TrimPrefixEncoder::new(b'+').encode(b"kotami", b"kot")),
+        ];
+        for (encoder, expected) in encoders {
+            let encoded = encoder.encode(b"kotami", b"kot").unwrap();
+            assert_eq!(encoded, expected.unwrap());
+            assert_eq!(encoder.decode(b"kotami", &encoded).unwrap(), b"kot");
+        }
+        for encoder_type in [EncoderType::None, EncoderType::Prefix, EncoderType::Infix, EncoderType::Suffix] {
+            assert_eq!(AnyEncoder::new(&encoder_type, b'+').get_encoder_type(), encoder_type);
+        }
+    }
+}
@@ -5,10 +5,32 @@ pub mod trim_prefix_encoder;
 pub mod trim_suffix_encoder;
 pub mod trim_prefix_suffix_encoder;
 pub mod trim_infix_suffix_encoder; 
+pub mod any_encoder;
 
 // Re-eksportujemy, aby były łatwiej dostępne
 pub use no_encoder::NoEncoder;
 pub use trim_prefix_encoder::TrimPrefixEncoder;
 pub use trim_suffix_encoder::TrimSuffixEncoder;
 pub use trim_prefix_suffix_encoder::TrimPrefixAndSuffixEncoder; // Reeksportujemy
-pub use trim_infix_suffix_encoder::TrimInfixAndSuffixEncoder;
\ No newline at end of file
+pub use trim_infix_suffix_encoder::TrimInfixAndSuffixEncoder;
+pub use any_encoder::AnyEncoder;
+
+use crate::dictionary_metadata::EncoderType;
+
+/// Kod oznaczający usunięcie całego wejścia (jak `REMOVE_EVERYTHING` w Javie).
+pub(crate) const REMOVE_EVERYTHING: usize = 255;
+
+/// Zapisuje liczbę bajtów do usunięcia jako kod `'A' + n` (modulo 256).
+pub(crate) fn length_code(n: usize) -> u8 {
+    (n as u8).wrapping_add(b'A')
+}
+
+/// Odczytuje liczbę bajtów zapisaną przez [`length_code`].
+pub(crate) fn code_length(code: u8) -> usize {
+    code.wrapping_sub(b'A') as usize
+}
+
+/// Tworzy enkoder danego typu (jak `EncoderType.get()` w Javie); patrz [`AnyEncoder::new`].
+pub fn create_encoder(encoder_type: &EncoderType, separator: u8) -> AnyEncoder {
+    AnyEncoder::new(encoder_type, separator)
+}
@@ -4,6 +4,8 @@ use crate::sequence_encoder_trait::SequenceEncoder;
 use crate::error::{Result as StemmingResult, StemmingError};
 use crate::dictionary_metadata::EncoderType; // Import EncoderType
 
+use super::{code_length, length_code, REMOVE_EVERYTHING};
+
 #[derive(Debug, Clone)]
 pub struct TrimInfixAndSuffixEncoder {
     separator: u8,
@@ -18,86 +20,98 @@ impl TrimInfixAndSuffixEncoder {
         s1.iter().zip(s2.iter()).take_while(|&(&a, &b)| a == b).count()
     }
 
-    fn common_suffix_len(s1: &[u8], s2: &[u8]) -> usize {
-        s1.iter().rev().zip(s2.iter().rev()).take_while(|&(&a, &b)| a == b).count()
+    /// Długość wspólnego prefiksu `stem` i `input` z usuniętym fragmentem
+    /// `input[index..index + len]`.
+    fn shared_prefix_without_infix(input: &[u8], index: usize, len: usize, stem: &[u8]) -> usize {
+        input[..index]
+            .iter()
+            .chain(input[index + len..].iter())
+            .zip(stem.iter())
+            .take_while(|&(&a, &b)| a == b)
+            .count()
     }
 }
 
 impl SequenceEncoder for TrimInfixAndSuffixEncoder {
+    /// Koduje formę podstawową `data` względem `input` jak TrimInfixAndSuffixEncoder.java:
+    /// kody `'A' + n` położenia i długości usuwanego wrostka oraz długości odcinanego
+    /// sufiksu, a po nich reszta formy podstawowej.
     fn encode<'a>(&self, input: &'a [u8], data: &'a [u8]) -> StemmingResult<Vec<u8>> {
-        let sep_pos = data.iter().position(|&b| b == self.separator);
-
-        let (stem_part, tag_part) = match sep_pos {
-            Some(pos) => (&data[..pos], &data[pos + 1..]),
-            None => (data, Default::default()),
-        };
-
-        let p = Self::common_prefix_len(input, stem_part); 
-
-        let input_suffix_part = &input[p..];
-        let stem_suffix_part = &stem_part[p..];
-
-        let s = Self::common_suffix_len(input_suffix_part, stem_suffix_part); 
-
-        if input_suffix_part.len() < s {
-             return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimInfixAndSuffixEncoder: Common suffix length ({}) is greater than input suffix part length ({}) for input '{}', stem '{}'",
-                s, input_suffix_part.len(), String::from_utf8_lossy(input), String::from_utf8_lossy(stem_part)
-            )));
+        // Wrostek do usunięcia może zaczynać się albo na początku wejścia, albo tuż za
+        // najdłuższym wspólnym prefiksem.
+        let mut shared_len = Self::common_prefix_len(input, data);
+        let mut infix_index = 0;
+        let mut infix_len = 0;
+        for i in [0, shared_len] {
+            for j in 1..=(input.len() - i) {
+                let len = Self::shared_prefix_without_infix(input, i, j, data);
+                if len > 0 && len > shared_len && i < REMOVE_EVERYTHING && j < REMOVE_EVERYTHING {
+                    shared_len = len;
+                    infix_index = i;
+                    infix_len = j;
+                }
+            }
         }
-        let infix_to_remove_from_input_len = input_suffix_part.len() - s;
 
-        if stem_suffix_part.len() < s {
-            return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimInfixAndSuffixEncoder: Common suffix length ({}) is greater than stem suffix part length ({}) for input '{}', stem '{}'",
-                s, stem_suffix_part.len(), String::from_utf8_lossy(input), String::from_utf8_lossy(stem_part)
-            )));
-        }
-        let infix_to_insert_for_stem = &stem_suffix_part[..stem_suffix_part.len() - s];
+        let mut suffix_to_remove = input.len() - (infix_len + shared_len);
 
+        // Wrostek sięgający końca wejścia to w istocie obcięcie sufiksu.
+        if suffix_to_remove == 0 && infix_index + infix_len == input.len() {
+            suffix_to_remove = infix_len;
+            infix_index = 0;
+            infix_len = 0;
+        }
 
-        if p > 255 || infix_to_remove_from_input_len > 255 {
-            return Err(StemmingError::SequenceDecodingError(
-                "TrimInfixAndSuffixEncoder: Prefix or infix_to_remove length exceeds 255".to_string(),
-            ));
+        if infix_index >= REMOVE_EVERYTHING || infix_len >= REMOVE_EVERYTHING || suffix_to_remove >= REMOVE_EVERYTHING {
+            infix_index = 0;
+            shared_len = 0;
+            infix_len = REMOVE_EVERYTHING;
+            suffix_to_remove = REMOVE_EVERYTHING;
         }
 
-        let mut result = Vec::with_capacity(2 + infix_to_insert_for_stem.len() + tag_part.len());
-        result.push(p as u8);
-        result.push(infix_to_remove_from_input_len as u8);
-        result.extend_from_slice(infix_to_insert_for_stem);
-        result.extend_from_slice(tag_part);
+        let appended = &data[shared_len..];
+        let mut result = Vec::with_capacity(3 + appended.len());
+        result.push(length_code(infix_index));
+        result.push(length_code(infix_len));
+        result.push(length_code(suffix_to_remove));
+        result.extend_from_slice(appended);
 
         Ok(result)
     }
 
     fn decode<'a>(&self, input: &'a [u8], encoded_data: &'a [u8]) -> StemmingResult<Vec<u8>> {
-        if encoded_data.len() < 2 {
+        if encoded_data.len() < 3 {
             return Err(StemmingError::SequenceDecodingError(
-                "TrimInfixAndSuffixEncoder: Encoded data too short (minimum 2 bytes for lengths).".to_string(),
+                "TrimInfixAndSuffixEncoder: Encoded data too short (minimum 3 bytes for lengths).".to_string(),
             ));
         }
 
-        let p_len = encoded_data[0] as usize; 
-        let i_len = encoded_data[1] as usize; 
+        let mut infix_index = code_length(encoded_data[0]);
+        let mut infix_len = code_length(encoded_data[1]);
+        let mut suffix_len = code_length(encoded_data[2]);
+        if infix_len == REMOVE_EVERYTHING || suffix_len == REMOVE_EVERYTHING {
+            infix_index = 0;
+            infix_len = input.len();
+            suffix_len = 0;
+        }
 
-        if p_len > input.len() || p_len.checked_add(i_len).map_or(true, |sum| sum > input.len()) {
+        if infix_index + infix_len + suffix_len > input.len() {
              return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimInfixAndSuffixEncoder: Invalid lengths p ({}) or i ({}) for input length {} ('{}')",
-                p_len, i_len, input.len(), String::from_utf8_lossy(input)
+                "TrimInfixAndSuffixEncoder: Invalid lengths (infix {} at {}, suffix {}) for input length {} ('{}')",
+                infix_len, infix_index, suffix_len, input.len(), String::from_utf8_lossy(input)
             )));
         }
 
-        let prefix_from_input = &input[..p_len];
-        let suffix_from_input = &input[p_len + i_len ..];
-        let stem_infix_and_tag_from_fsa = &encoded_data[2..];
+        let prefix_from_input = &input[..infix_index];
+        let rest_from_input = &input[infix_index + infix_len .. input.len() - suffix_len];
+        let appended_from_fsa = &encoded_data[3..];
 
         let mut result = Vec::with_capacity(
-            prefix_from_input.len() + stem_infix_and_tag_from_fsa.len() + suffix_from_input.len()
+            prefix_from_input.len() + rest_from_input.len() + appended_from_fsa.len()
         );
         result.extend_from_slice(prefix_from_input);
-        result.extend_from_slice(stem_infix_and_tag_from_fsa);
-        result.extend_from_slice(suffix_from_input);
+        result.extend_from_slice(rest_from_input);
+        result.extend_from_slice(appended_from_fsa);
         
         Ok(result)
     }
@@ -120,20 +134,20 @@ mod tests {
     fn test_trim_infix_suffix_encoder_basic() {
         let encoder = TrimInfixAndSuffixEncoder::new(b'+');
         let input1 = b"niebieski"; 
-        let data1 = b"niesk+ADJ";   
+        let data1 = b"niesk";   
         let encoded1 = encoder.encode(input1, data1).unwrap();
-        assert_eq!(encoded1, vec![1, 4, b'A', b'D', b'J']);
+        assert_eq!(encoded1, b"DDB");
 
         let decoded1 = encoder.decode(input1, &encoded1).unwrap();
-        assert_eq!(decoded1, b"nADJeski");
+        assert_eq!(decoded1, b"niesk");
 
         let input2 = b"najpiekniejszy";
-        let data2 = b"piekny+ADJ";
+        let data2 = b"piekny";
         let encoded2 = encoder.encode(input2, data2).unwrap();
-        assert_eq!(encoded2, vec![0, 15, b'p',b'i',b'e',b'k',b'n', b'A',b'D',b'J']);
+        assert_eq!(encoded2, b"ADGy");
         
         let decoded2 = encoder.decode(input2, &encoded2).unwrap();
-        assert_eq!(decoded2, b"pieknADJy"); 
+        assert_eq!(decoded2, b"piekny"); 
     }
 
 
@@ -141,19 +155,43 @@ mod tests {
     fn test_no_infix_no_suffix_just_prefix() {
         let encoder = TrimInfixAndSuffixEncoder::new(b'+');
         let input = b"przedrostek";
-        let data = b"rostek+X";
+        let data = b"rostek";
         let encoded = encoder.encode(input, data).unwrap();
-        assert_eq!(encoded, vec![0, 5, b'X']);
+        assert_eq!(encoded, b"AFA");
 
         let decoded = encoder.decode(input, &encoded).unwrap();
-        assert_eq!(decoded, b"Xrostek"); 
+        assert_eq!(decoded, b"rostek"); 
+    }
+
+    #[test]
+    fn test_suffix_only() {
+        let encoder = TrimInfixAndSuffixEncoder::new(b'+');
+        let input = b"kotami";
+        let data = b"kot";
+        let encoded = encoder.encode(input, data).unwrap();
+        assert_eq!(encoded, b"AAD");
+
+        let decoded = encoder.decode(input, &encoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_remove_everything() {
+        let encoder = TrimInfixAndSuffixEncoder::new(b'+');
+        let input = vec![b'a'; 300];
+        let encoded = encoder.encode(&input, b"b").unwrap();
+        let remove_everything = b'A'.wrapping_add(255);
+        assert_eq!(encoded, vec![b'A', remove_everything, remove_everything, b'b']);
+
+        let decoded = encoder.decode(&input, &encoded).unwrap();
+        assert_eq!(decoded, b"b");
     }
 
     #[test]
     fn test_decode_encoded_too_short() {
         let encoder = TrimInfixAndSuffixEncoder::new(b'+');
         let input = b"word";
-        let encoded_data_short = &[1];
+        let encoded_data_short = b"BC";
         let result = encoder.decode(input, encoded_data_short);
         assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
     }
@@ -162,7 +200,7 @@ mod tests {
     fn test_decode_invalid_lengths() {
         let encoder = TrimInfixAndSuffixEncoder::new(b'+');
         let input = b"word"; 
-        let encoded_data = &[3, 2, b'T']; 
+        let encoded_data = b"DCAT"; 
         let result = encoder.decode(input, encoded_data);
         assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
     }
@@ -4,6 +4,8 @@ use crate::sequence_encoder_trait::SequenceEncoder;
 use crate::error::{Result as StemmingResult, StemmingError};
 use crate::dictionary_metadata::EncoderType; // Import EncoderType
 
+use super::{code_length, length_code, REMOVE_EVERYTHING};
+
 #[derive(Debug, Default, Clone)]
 pub struct TrimPrefixEncoder {
     separator: u8,
@@ -14,40 +16,33 @@ impl TrimPrefixEncoder {
         TrimPrefixEncoder { separator }
     }
 
-    fn common_suffix_len(s1: &[u8], s2: &[u8]) -> usize {
-        s1.iter().rev().zip(s2.iter().rev()).take_while(|&(&a, &b)| a == b).count()
+    /// Zwraca najmniejszą liczbę bajtów, które trzeba odciąć z początku `input`,
+    /// aby pozostała część była prefiksem `stem`.
+    fn prefix_to_remove_len(input: &[u8], stem: &[u8]) -> usize {
+        (0..input.len())
+            .find(|&k| stem.starts_with(&input[k..]))
+            .unwrap_or(input.len())
     }
 }
 
 impl SequenceEncoder for TrimPrefixEncoder {
+    /// Koduje formę podstawową `data` względem `input`: kod `'A' + n` liczby bajtów
+    /// odcinanych z początku `input` i reszta formy podstawowej (kody jak w enkoderach
+    /// Javy).
     fn encode<'a>(&self, input: &'a [u8], data: &'a [u8]) -> StemmingResult<Vec<u8>> {
-        let sep_pos = data.iter().position(|&b| b == self.separator);
-
-        let (stem_part, tag_part) = match sep_pos {
-            Some(pos) => (&data[..pos], &data[pos + 1..]),
-            None => (data, Default::default()), 
-        };
-
-        let suffix_len = Self::common_suffix_len(input, stem_part);
-
-        if suffix_len == 0 && !input.is_empty() && !stem_part.is_empty() && input != stem_part {
-            return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimPrefixEncoder: No common suffix between input ('{}') and stem ('{}')",
-                String::from_utf8_lossy(input), String::from_utf8_lossy(stem_part)
-            )));
+        let mut bytes_to_remove_from_input_prefix = Self::prefix_to_remove_len(input, data);
+        let mut kept_len = input.len() - bytes_to_remove_from_input_prefix;
+        if bytes_to_remove_from_input_prefix >= REMOVE_EVERYTHING {
+            bytes_to_remove_from_input_prefix = REMOVE_EVERYTHING;
+            kept_len = 0;
         }
-        
-        let bytes_to_remove_from_input_prefix = input.len() - suffix_len;
 
-        if bytes_to_remove_from_input_prefix > 255 {
-            return Err(StemmingError::SequenceDecodingError(
-                "TrimPrefixEncoder: Too many bytes to remove from input prefix (max 255)".to_string(),
-            ));
-        }
-
-        let mut result = Vec::with_capacity(1 + tag_part.len());
-        result.push(bytes_to_remove_from_input_prefix as u8);
-        result.extend_from_slice(tag_part);
+        // Pozostała część wejścia jest prefiksem formy podstawowej, więc
+        // zapisujemy tylko końcówkę formy.
+        let appended = &data[kept_len..];
+        let mut result = Vec::with_capacity(1 + appended.len());
+        result.push(length_code(bytes_to_remove_from_input_prefix));
+        result.extend_from_slice(appended);
 
         Ok(result)
     }
@@ -59,7 +54,10 @@ impl SequenceEncoder for TrimPrefixEncoder {
             ));
         }
 
-        let bytes_to_remove_from_input_prefix = encoded_data[0] as usize;
+        let mut bytes_to_remove_from_input_prefix = code_length(encoded_data[0]);
+        if bytes_to_remove_from_input_prefix == REMOVE_EVERYTHING {
+            bytes_to_remove_from_input_prefix = input.len();
+        }
 
         if bytes_to_remove_from_input_prefix > input.len() {
             return Err(StemmingError::SequenceDecodingError(format!(
@@ -69,11 +67,11 @@ impl SequenceEncoder for TrimPrefixEncoder {
         }
 
         let stem_part = &input[bytes_to_remove_from_input_prefix..];
-        let tag_part_from_fsa = &encoded_data[1..];
+        let appended_from_fsa = &encoded_data[1..];
 
-        let mut result = Vec::with_capacity(stem_part.len() + tag_part_from_fsa.len());
+        let mut result = Vec::with_capacity(stem_part.len() + appended_from_fsa.len());
         result.extend_from_slice(stem_part);
-        result.extend_from_slice(tag_part_from_fsa);
+        result.extend_from_slice(appended_from_fsa);
         
         Ok(result)
     }
@@ -95,57 +93,41 @@ mod tests {
     fn test_trim_prefix_encoder_basic() {
         let encoder = TrimPrefixEncoder::new(b'+');
         let input1 = b"przedimek";
-        let data1 = b"dimek+N";
+        let data1 = b"dimek";
         let encoded1 = encoder.encode(input1, data1).unwrap();
-        assert_eq!(encoded1, vec![3, b'N']);
+        assert_eq!(encoded1, b"E");
 
         let decoded1 = encoder.decode(input1, &encoded1).unwrap();
-        assert_eq!(decoded1, b"dimekN");
+        assert_eq!(decoded1, b"dimek");
 
         let input2 = b"kotami";
-        let data2 = b"ami+X";
+        let data2 = b"ami";
         let encoded2 = encoder.encode(input2, data2).unwrap();
-        assert_eq!(encoded2, vec![3, b'X']);
+        assert_eq!(encoded2, b"D");
 
         let decoded2 = encoder.decode(input2, &encoded2).unwrap();
-        assert_eq!(decoded2, b"amiX");
-    }
+        assert_eq!(decoded2, b"ami");
 
-    #[test]
-    fn test_trim_prefix_encoder_no_tag() {
-        let encoder = TrimPrefixEncoder::new(b'+');
-        let input = b"przeddom";
-        let data_no_tag = b"dom";
-        let encoded = encoder.encode(input, data_no_tag).unwrap();
-        assert_eq!(encoded, vec![5]);
+        let input3 = b"najlepszy";
+        let data3 = b"lepsi";
+        let encoded3 = encoder.encode(input3, data3).unwrap();
+        assert_eq!(encoded3, b"Jlepsi");
 
-        let decoded = encoder.decode(input, &encoded).unwrap();
-        assert_eq!(decoded, b"dom");
+        let decoded3 = encoder.decode(input3, &encoded3).unwrap();
+        assert_eq!(decoded3, data3);
     }
 
     #[test]
-    fn test_trim_prefix_encoder_stem_equals_word_no_tag() {
+    fn test_trim_prefix_encoder_stem_equals_word() {
         let encoder = TrimPrefixEncoder::new(b'+');
         let input = b"kot";
         let data_stem_equals_word = b"kot";
         let encoded = encoder.encode(input, data_stem_equals_word).unwrap();
-        assert_eq!(encoded, vec![0]);
+        assert_eq!(encoded, b"A");
 
         let decoded = encoder.decode(input, &encoded).unwrap();
         assert_eq!(decoded, b"kot");
     }
-
-    #[test]
-    fn test_trim_prefix_encoder_stem_equals_word_with_tag() {
-        let encoder = TrimPrefixEncoder::new(b'+');
-        let input = b"kot";
-        let data = b"kot+N";
-        let encoded = encoder.encode(input, data).unwrap();
-        assert_eq!(encoded, vec![0, b'N']);
-
-        let decoded = encoder.decode(input, &encoded).unwrap();
-        assert_eq!(decoded, b"kotN");
-    }
     
     #[test]
     fn test_trim_prefix_encoder_decode_empty_encoded_data() {
@@ -160,7 +142,7 @@ mod tests {
     fn test_trim_prefix_encoder_decode_bytes_to_remove_too_large() {
         let encoder = TrimPrefixEncoder::new(b'+');
         let input = b"word"; 
-        let encoded_data = &[5, b't', b'a', b'g']; 
+        let encoded_data = b"Ftag"; 
         let result = encoder.decode(input, encoded_data);
         assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
     }
@@ -169,9 +151,17 @@ mod tests {
     fn test_trim_prefix_encoder_no_common_suffix() {
         let encoder = TrimPrefixEncoder::new(b'+');
         let input = b"abcdef";
-        let data = b"xyz+tag"; 
-        let result = encoder.encode(input, data);
-        assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
+        let data = b"xyz"; 
+        let encoded = encoder.encode(input, data).unwrap();
+        assert_eq!(encoded, b"Gxyz");
+
+        let decoded = encoder.decode(input, &encoded).unwrap();
+        assert_eq!(decoded, data);
+
+        let long_input = vec![b'a'; 300];
+        let encoded = encoder.encode(&long_input, data).unwrap();
+        assert_eq!(encoded[0], b'A'.wrapping_add(255));
+        assert_eq!(encoder.decode(&long_input, &encoded).unwrap(), data);
     }
 
     #[test]
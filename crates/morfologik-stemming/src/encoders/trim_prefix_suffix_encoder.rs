@@ -4,6 +4,8 @@ use crate::sequence_encoder_trait::SequenceEncoder;
 use crate::error::{Result as StemmingResult, StemmingError};
 use crate::dictionary_metadata::EncoderType; // Import EncoderType
 
+use super::{code_length, length_code, REMOVE_EVERYTHING};
+
 #[derive(Debug, Default, Clone)]
 pub struct TrimPrefixAndSuffixEncoder {
     separator: u8,
@@ -17,56 +19,37 @@ impl TrimPrefixAndSuffixEncoder {
     fn common_prefix_len(s1: &[u8], s2: &[u8]) -> usize {
         s1.iter().zip(s2.iter()).take_while(|&(&a, &b)| a == b).count()
     }
-
-    fn common_suffix_len(s1: &[u8], s2: &[u8]) -> usize {
-        s1.iter().rev().zip(s2.iter().rev()).take_while(|&(&a, &b)| a == b).count()
-    }
 }
 
 impl SequenceEncoder for TrimPrefixAndSuffixEncoder {
+    /// Koduje formę podstawową `data` względem `input` jak TrimPrefixAndSuffixEncoder.java:
+    /// kody `'A' + n` bajtów odcinanych z początku i z końca `input` oraz reszta formy.
     fn encode<'a>(&self, input: &'a [u8], data: &'a [u8]) -> StemmingResult<Vec<u8>> {
-        let sep_pos = data.iter().position(|&b| b == self.separator);
-
-        let (stem_part, tag_part) = match sep_pos {
-            Some(pos) => (&data[..pos], &data[pos + 1..]),
-            None => (data, Default::default()), 
-        };
-
-        let p = Self::common_prefix_len(input, stem_part);
-        let s = Self::common_suffix_len(input, stem_part);
-
-        if p + s > input.len() || p + s > stem_part.len() {
-             return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimPrefixAndSuffixEncoder: Prefix and suffix overlap for input '{}' and stem '{}' (p={}, s={})",
-                String::from_utf8_lossy(input), String::from_utf8_lossy(stem_part), p, s
-            )));
+        // Szukamy fragmentu wejścia (po odcięciu prefiksu), który jest
+        // najdłuższym prefiksem formy podstawowej.
+        let mut shared_len = 0;
+        let mut shared_index = 0;
+        for i in 0..input.len() {
+            let len = Self::common_prefix_len(&input[i..], data);
+            if len > shared_len && i < REMOVE_EVERYTHING && input.len() - (i + len) < REMOVE_EVERYTHING {
+                shared_len = len;
+                shared_index = i;
+            }
         }
 
-        let input_core = &input[p .. input.len() - s];
-        let stem_part_core = &stem_part[p .. stem_part.len() - s];
-
-        if input_core != stem_part_core {
-            return Err(StemmingError::SequenceDecodingError(format!(
-                "TrimPrefixAndSuffixEncoder: Core mismatch for input '{}' (core '{}') and stem '{}' (core '{}')",
-                String::from_utf8_lossy(input), String::from_utf8_lossy(input_core),
-                String::from_utf8_lossy(stem_part), String::from_utf8_lossy(stem_part_core)
-            )));
+        let mut bytes_prefix_to_remove = shared_index;
+        let mut bytes_suffix_to_remove = input.len() - (shared_index + shared_len);
+        if bytes_prefix_to_remove >= REMOVE_EVERYTHING || bytes_suffix_to_remove >= REMOVE_EVERYTHING {
+            shared_len = 0;
+            bytes_prefix_to_remove = REMOVE_EVERYTHING;
+            bytes_suffix_to_remove = REMOVE_EVERYTHING;
         }
 
-        let bytes_prefix_to_remove = p;
-        let bytes_suffix_to_remove = s;
-
-
-        if bytes_prefix_to_remove > 255 || bytes_suffix_to_remove > 255 {
-            return Err(StemmingError::SequenceDecodingError(
-                "TrimPrefixAndSuffixEncoder: Too many bytes for prefix/suffix to remove (max 255)".to_string(),
-            ));
-        }
-
-        let mut result = Vec::with_capacity(2 + tag_part.len());
-        result.push(bytes_prefix_to_remove as u8);
-        result.push(bytes_suffix_to_remove as u8);
-        result.extend_from_slice(tag_part);
+        let appended = &data[shared_len..];
+        let mut result = Vec::with_capacity(2 + appended.len());
+        result.push(length_code(bytes_prefix_to_remove));
+        result.push(length_code(bytes_suffix_to_remove));
+        result.extend_from_slice(appended);
 
         Ok(result)
     }
@@ -78,8 +61,12 @@ impl SequenceEncoder for TrimPrefixAndSuffixEncoder {
             ));
         }
 
-        let p = encoded_data[0] as usize; 
-        let s = encoded_data[1] as usize; 
+        let mut p = code_length(encoded_data[0]);
+        let mut s = code_length(encoded_data[1]);
+        if p == REMOVE_EVERYTHING || s == REMOVE_EVERYTHING {
+            p = input.len();
+            s = 0;
+        }
 
         if p + s > input.len() {
             return Err(StemmingError::SequenceDecodingError(format!(
@@ -89,11 +76,11 @@ impl SequenceEncoder for TrimPrefixAndSuffixEncoder {
         }
 
         let stem_part = &input[p .. input.len() - s];
-        let tag_part_from_fsa = &encoded_data[2..];
+        let appended_from_fsa = &encoded_data[2..];
 
-        let mut result = Vec::with_capacity(stem_part.len() + tag_part_from_fsa.len());
+        let mut result = Vec::with_capacity(stem_part.len() + appended_from_fsa.len());
         result.extend_from_slice(stem_part);
-        result.extend_from_slice(tag_part_from_fsa);
+        result.extend_from_slice(appended_from_fsa);
         
         Ok(result)
     }
@@ -103,10 +90,8 @@ impl SequenceEncoder for TrimPrefixAndSuffixEncoder {
     }
     
     fn get_encoder_type(&self) -> EncoderType {
-        // W Morfologiku Java, InfixEncoder często jest aliasem lub implementuje to samo co TrimPrefixAndSuffix.
-        // Jeśli mamy osobny EncoderType::Infix, to ten powinien zwracać coś innego,
-        // np. EncoderType::PrefixSuffix. Na razie zakładamy, że Infix to ten enkoder.
-        EncoderType::Infix // Lub dedykowany typ, jeśli dodamy do EncoderType
+        // W Morfologiku Java typ PREFIX oznacza właśnie ten enkoder.
+        EncoderType::Prefix
     }
 }
 
@@ -118,70 +103,89 @@ mod tests {
     fn test_trim_prefix_suffix_encoder_basic() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
         let input1 = b"niebieski";
-        let data1 = b"biesk+ADJ";
+        let data1 = b"biesk";
         let encoded1 = encoder.encode(input1, data1).unwrap();
-        assert_eq!(encoded1, vec![3, 1, b'A', b'D', b'J']);
+        assert_eq!(encoded1, b"DB");
 
         let decoded1 = encoder.decode(input1, &encoded1).unwrap();
-        assert_eq!(decoded1, b"bieskADJ");
+        assert_eq!(decoded1, b"biesk");
 
         let input2 = b"kot";
-        let data2 = b"kot+N";
+        let data2 = b"kot";
         let encoded2 = encoder.encode(input2, data2).unwrap();
-        assert_eq!(encoded2, vec![0, 0, b'N']);
+        assert_eq!(encoded2, b"AA");
         let decoded2 = encoder.decode(input2, &encoded2).unwrap();
-        assert_eq!(decoded2, b"kotN");
+        assert_eq!(decoded2, b"kot");
 
         let input3 = b"przedrostek";
-        let data3 = b"rostek+X";
+        let data3 = b"rostek";
         let encoded3 = encoder.encode(input3, data3).unwrap();
-        assert_eq!(encoded3, vec![5, 0, b'X']);
+        assert_eq!(encoded3, b"FA");
         let decoded3 = encoder.decode(input3, &encoded3).unwrap();
-        assert_eq!(decoded3, b"rostekX");
+        assert_eq!(decoded3, b"rostek");
 
         let input4 = b"rosteksufiks";
-        let data4 = b"rostek+Y";
+        let data4 = b"rostek";
         let encoded4 = encoder.encode(input4, data4).unwrap();
-        assert_eq!(encoded4, vec![0, 6, b'Y']);
+        assert_eq!(encoded4, b"AG");
         let decoded4 = encoder.decode(input4, &encoded4).unwrap();
-        assert_eq!(decoded4, b"rostekY");
+        assert_eq!(decoded4, b"rostek");
     }
 
     #[test]
-    fn test_trim_prefix_suffix_encoder_no_tag() {
+    fn test_encode_core_mismatch() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
-        let input = b"niebieski";
-        let data_no_tag = b"biesk";
-        let encoded = encoder.encode(input, data_no_tag).unwrap();
-        assert_eq!(encoded, vec![3, 1]);
+        let input = b"abXYcd";
+        let data = b"abZZcd";
+        let encoded = encoder.encode(input, data).unwrap();
+        assert_eq!(encoded, b"AEZZcd");
 
         let decoded = encoder.decode(input, &encoded).unwrap();
-        assert_eq!(decoded, b"biesk");
+        assert_eq!(decoded, data);
     }
-    
+
     #[test]
-    fn test_encode_core_mismatch() {
+    fn test_encode_overlap() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
-        let input = b"abXYcd";
-        let data = b"abZZcd+T";
-        let result = encoder.encode(input, data);
-        assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
+        let input = b"abc";
+        let data = b"axc";
+        let encoded = encoder.encode(input, data).unwrap();
+        assert_eq!(encoded, b"ACxc");
+
+        let decoded = encoder.decode(input, &encoded).unwrap();
+        assert_eq!(decoded, data);
     }
 
     #[test]
-    fn test_encode_overlap() {
+    fn test_encode_no_shared_fragment() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
-        let input = b"abc";
-        let data = b"axc+T";
-        let result = encoder.encode(input, data);
-        assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
+        let input = b"jest";
+        let data = "być".as_bytes();
+        let encoded = encoder.encode(input, data).unwrap();
+        assert_eq!(encoded, "AEbyć".as_bytes());
+
+        let decoded = encoder.decode(input, &encoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_encode_remove_everything() {
+        let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
+        let mut input = vec![b'x'; 300];
+        input.extend_from_slice(b"kot");
+        let encoded = encoder.encode(&input, b"kot").unwrap();
+        let remove_everything = b'A'.wrapping_add(255);
+        assert_eq!(encoded, vec![remove_everything, remove_everything, b'k', b'o', b't']);
+
+        let decoded = encoder.decode(&input, &encoded).unwrap();
+        assert_eq!(decoded, b"kot");
     }
 
     #[test]
     fn test_decode_encoded_too_short() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
         let input = b"word";
-        let encoded_data_short = &[1]; 
+        let encoded_data_short = b"B"; 
         let result = encoder.decode(input, encoded_data_short);
         assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
     }
@@ -190,7 +194,7 @@ mod tests {
     fn test_decode_ps_greater_than_input() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
         let input = b"word"; 
-        let encoded_data = &[3, 2, b'T']; 
+        let encoded_data = b"DCT"; 
         let result = encoder.decode(input, encoded_data);
         assert!(matches!(result, Err(StemmingError::SequenceDecodingError(_))));
     }
@@ -198,6 +202,6 @@ mod tests {
     #[test]
     fn test_trim_prefix_suffix_encoder_get_type() {
         let encoder = TrimPrefixAndSuffixEncoder::new(b'+');
-        assert_eq!(encoder.get_encoder_type(), EncoderType::Infix); // Zakładając, że Infix to ten
+        assert_eq!(encoder.get_encoder_type(), EncoderType::Prefix);
     }
 }
@@ -10,7 +10,7 @@ pub trait Stemmer {
     ///
     /// # Argumenty
     /// * `word` - Słowo (forma fleksyjna) jako sekwencja bajtów.
-    ///           Kodowanie znaków powinno być zgodne z kodowaniem słownika.
+    ///   Kodowanie znaków powinno być zgodne z kodowaniem słownika.
     ///
     /// # Zwraca
     /// Wektor struktur `WordData`, gdzie każda struktura zawiera
@@ -51,6 +51,5 @@ mod tests {
     #[test]
     fn it_compiles() {
         // Ten test tylko sprawdza, czy kod się kompiluje.
-        assert!(true);
     }
 }
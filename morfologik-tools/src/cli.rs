@@ -1,21 +1,118 @@
-// Definicje argumentów CLI (np. z clap)
-// use clap::{Parser, Subcommand};
-
-// #[derive(Parser, Debug)]
-// #[clap(author, version, about, long_about = None)]
-// pub struct Cli {
-//     #[clap(subcommand)]
-//     pub command: Commands,
-// }
-
-// #[derive(Subcommand, Debug)]
-// pub enum Commands {
-//     /// Buduje automat FSA z pliku tekstowego
-//     FsaBuild {
-//         #[clap(short, long, value_parser)]
-//         input: std::path::PathBuf,
-//         #[clap(short, long, value_parser)]
-//         output: std::path::PathBuf,
-//     },
-//     // Dodaj inne komendy
-// }
+// Definicje argumentów CLI (clap)
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::commands::dict_apply::DictApplyArgs;
+use crate::commands::dict_compile::DictCompileArgs;
+use crate::commands::dict_decompile::DictDecompileArgs;
+use crate::commands::fsa_build::FsaBuildArgs;
+use crate::commands::fsa_compile::FsaCompileArgs;
+use crate::commands::fsa_decompile::FsaDecompileArgs;
+use crate::commands::fsa_dump::FsaDumpArgs;
+use crate::commands::fsa_info_cmd::FsaInfoArgs;
+use crate::commands::fsa_set::FsaSetArgs;
+
+/// Narzędzia Morfologik: budowanie i analiza automatów oraz słowników morfologicznych.
+///
+/// Pliki wejściowe i wyjściowe, które można pominąć, domyślnie oznaczają standardowe
+/// wejście/wyjście; to samo oznacza ścieżka `-`.
+#[derive(Parser, Debug)]
+#[command(name = "morfologik-cli", version, about, long_about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Buduje automat z listy sekwencji (jedna w wierszu), czytając ze standardowego wejścia.
+    #[command(alias = "fsa_build")]
+    FsaBuild(FsaBuildArgs),
+
+    /// Kompiluje listę sekwencji (jedna w wierszu) z pliku do pliku automatu.
+    #[command(alias = "fsa_compile")]
+    FsaCompile(FsaCompileArgs),
+
+    /// Wypisuje wszystkie sekwencje zapisane w automacie.
+    #[command(alias = "fsa_decompile")]
+    FsaDecompile(FsaDecompileArgs),
+
+    /// Wypisuje strukturę automatu (węzły i łuki).
+    #[command(alias = "fsa_dump")]
+    FsaDump(FsaDumpArgs),
+
+    /// Wypisuje informacje o automacie.
+    #[command(alias = "fsa_info")]
+    FsaInfo(FsaInfoArgs),
+
+    /// Buduje minimalny automat z sekwencji należących do któregokolwiek z dwóch automatów.
+    #[command(alias = "fsa_union")]
+    FsaUnion(FsaSetArgs),
+
+    /// Buduje minimalny automat z sekwencji należących do obu automatów.
+    #[command(alias = "fsa_intersect")]
+    FsaIntersect(FsaSetArgs),
+
+    /// Buduje minimalny automat z sekwencji pierwszego automatu, których nie ma w drugim.
+    #[command(alias = "fsa_diff")]
+    FsaDiff(FsaSetArgs),
+
+    /// Kompiluje słownik morfologiczny (.dict) z wierszy `forma<TAB>lemat<TAB>tag`.
+    #[command(alias = "dict_compile")]
+    DictCompile(DictCompileArgs),
+
+    /// Odtwarza źródłowe wiersze `forma<TAB>lemat<TAB>tag` ze słownika (.dict).
+    #[command(alias = "dict_decompile")]
+    DictDecompile(DictDecompileArgs),
+
+    /// Wyszukuje formy podstawowe i tagi słów z wejścia w słowniku.
+    #[command(alias = "dict_apply")]
+    DictApply(DictApplyArgs),
+}
+
+/// Format zapisu automatu.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsaFormat {
+    #[default]
+    Fsa5,
+    Cfsa2,
+}
+
+/// Format wyniku `fsa-dump`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DumpFormat {
+    /// Tabela łuków rozdzielona tabulatorami.
+    #[default]
+    Table,
+    /// Graf w języku DOT (Graphviz).
+    Dot,
+}
+
+/// Format wierszy wynikowych `dict-apply`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RowFormat {
+    /// Wartości rozdzielone tabulatorami.
+    #[default]
+    Tsv,
+    /// Jeden obiekt JSON w wierszu (JSON Lines).
+    Jsonl,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_java_style_aliases() {
+        let cli = Cli::try_parse_from(["morfologik-cli", "fsa_info", "-i", "a.fsa"]).unwrap();
+        assert!(matches!(cli.command, Commands::FsaInfo(_)));
+        let cli = Cli::try_parse_from(["morfologik-cli", "fsa_diff", "-a", "a.fsa", "-b", "b.fsa"]).unwrap();
+        assert!(matches!(cli.command, Commands::FsaDiff(_)));
+    }
+}
@@ -1,4 +1,212 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy dict_apply
 
-// Logika komendy dict_apply
\ No newline at end of file
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Args;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::dictionary_lookup::DictionaryLookup;
+use morfologik_stemming::stemmer_trait::Stemmer;
+
+use crate::cli::RowFormat;
+use crate::error::Result;
+use crate::utils::{open_input, open_output, trim_line_ending};
+
+/// Argumenty komendy `dict-apply`.
+#[derive(Args, Debug)]
+pub struct DictApplyArgs {
+    /// Plik słownika (.dict) z plikiem `.info` obok.
+    #[arg(short, long = "dict", visible_alias = "dictionary")]
+    pub dictionary: PathBuf,
+
+    /// Pliki wejściowe (można podać wielokrotnie; domyślnie standardowe wejście).
+    #[arg(short, long)]
+    pub input: Vec<PathBuf>,
+
+    /// Plik wynikowy (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Dzieli wiersze na słowa zamiast traktować każdy wiersz jako jedno słowo.
+    #[arg(short, long)]
+    pub tokenize: bool,
+
+    /// Wypisuje także słowa, których nie ma w słowniku (bez lematu i tagu).
+    #[arg(short, long)]
+    pub unknown: bool,
+
+    /// Format wierszy wynikowych.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: RowFormat,
+}
+
+/// Wypisuje wiersz `słowo, lemat, tag` dla każdej interpretacji każdego słowa z wejścia.
+///
+/// Wejście przetwarzane jest wiersz po wierszu, więc zużycie pamięci nie zależy od jego
+/// rozmiaru. W formacie TSV brak lematu lub tagu oznaczany jest przez `-`, a w JSON przez `null`.
+pub fn run(args: &DictApplyArgs) -> Result<()> {
+    // `validated` odrzuca uszkodzony automat i wyszukuje w jego zdekodowanej kopii.
+    let lookup = DictionaryLookup::validated(Arc::new(Dictionary::from_file(&args.dictionary)?))?;
+    let mut output = open_output(args.output.as_ref())?;
+    if args.input.is_empty() {
+        apply(&lookup, &mut open_input(None)?, &mut output, args)?;
+    }
+    for path in &args.input {
+        apply(&lookup, &mut open_input(Some(path))?, &mut output, args)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn apply(lookup: &dyn Stemmer, reader: &mut dyn BufRead, output: &mut dyn Write, args: &DictApplyArgs) -> Result<()> {
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line = trim_line_ending(&buffer);
+        let words = if args.tokenize { tokenize(line) } else { vec![line] };
+        for word in words.into_iter().filter(|w| !w.is_empty()) {
+            let forms = lookup.lookup(word)?;
+            if forms.is_empty() && args.unknown {
+                write_row(output, args.format, word, None, None)?;
+            }
+            for form in &forms {
+                write_row(output, args.format, word, form.stem(), form.tag())?;
+            }
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// Dzieli wiersz na słowa: w tekście UTF-8 są to ciągi znaków alfanumerycznych, w innych
+/// kodowaniach ciągi bajtów niebędących białymi znakami ani interpunkcją ASCII.
+fn tokenize(line: &[u8]) -> Vec<&[u8]> {
+    let tokens: Vec<&[u8]> = match std::str::from_utf8(line) {
+        Ok(text) => text.split(|c: char| !c.is_alphanumeric()).map(str::as_bytes).collect(),
+        Err(_) => line.split(|b| b.is_ascii_whitespace() || b.is_ascii_punctuation()).collect(),
+    };
+    tokens.into_iter().filter(|t| !t.is_empty()).collect()
+}
+
+fn write_row(
+    output: &mut dyn Write,
+    format: RowFormat,
+    word: &[u8],
+    stem: Option<&[u8]>,
+    tag: Option<&[u8]>,
+) -> Result<()> {
+    match format {
+        RowFormat::Tsv => {
+            output.write_all(word)?;
+            output.write_all(b"\t")?;
+            output.write_all(stem.unwrap_or(b"-"))?;
+            output.write_all(b"\t")?;
+            output.write_all(tag.unwrap_or(b"-"))?;
+        }
+        RowFormat::Jsonl => {
+            output.write_all(b"{\"word\":")?;
+            write_json_string(output, Some(word))?;
+            output.write_all(b",\"stem\":")?;
+            write_json_string(output, stem)?;
+            output.write_all(b",\"tag\":")?;
+            write_json_string(output, tag)?;
+            output.write_all(b"}")?;
+        }
+    }
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Zapisuje napis JSON (lub `null`); bajty spoza UTF-8 zastępowane są znakiem U+FFFD.
+fn write_json_string(output: &mut dyn Write, value: Option<&[u8]>) -> Result<()> {
+    let value = match value {
+        Some(value) => String::from_utf8_lossy(value),
+        None => return Ok(output.write_all(b"null")?),
+    };
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    output.write_all(escaped.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::FsaFormat;
+    use crate::commands::dict_compile::{self, DictCompileArgs};
+
+    fn compile_dictionary(dir: &std::path::Path) -> PathBuf {
+        let input = dir.join("test.txt");
+        std::fs::write(&input, "kotem\tkot\tsubst:sg:inst\nkoty\tkot\tsubst:pl:nom\nkoty\tkota\tsubst:pl:nom:f\n").unwrap();
+        std::fs::write(dir.join("test.info"), "fsa.dict.separator=+\n").unwrap();
+        let output = dir.join("test.dict");
+        dict_compile::run(&DictCompileArgs {
+            input,
+            output: Some(output.clone()),
+            format: FsaFormat::Fsa5,
+            ignore_duplicates: false,
+        })
+        .unwrap();
+        output
+    }
+
+    fn apply_to(text: &str, tokenize: bool, unknown: bool, format: RowFormat) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let dictionary = compile_dictionary(dir.path());
+        let input = dir.path().join("input.txt");
+        let output = dir.path().join("output.txt");
+        std::fs::write(&input, text).unwrap();
+        run(&DictApplyArgs {
+            dictionary,
+            input: vec![input],
+            output: Some(output.clone()),
+            tokenize,
+            unknown,
+            format,
+        })
+        .unwrap();
+        std::fs::read_to_string(output).unwrap()
+    }
+
+    #[test]
+    fn test_tsv_skips_unknown_by_default() {
+        let output = apply_to("koty\npies\nkotem\n", false, false, RowFormat::Tsv);
+        assert_eq!(output, "koty\tkot\tsubst:pl:nom\nkoty\tkota\tsubst:pl:nom:f\nkotem\tkot\tsubst:sg:inst\n");
+    }
+
+    #[test]
+    fn test_tokenized_jsonl_with_unknown() {
+        let output = apply_to("Pies, kotem!\n", true, true, RowFormat::Jsonl);
+        assert_eq!(
+            output,
+            "{\"word\":\"Pies\",\"stem\":null,\"tag\":null}\n\
+             {\"word\":\"kotem\",\"stem\":\"kot\",\"tag\":\"subst:sg:inst\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Zażółć gęślą-jaźń.".as_bytes()), vec!["Zażółć".as_bytes(), "gęślą".as_bytes(), "jaźń".as_bytes()]);
+        // ISO-8859-2: "żółw" nie jest poprawnym UTF-8.
+        assert_eq!(tokenize(b"\xbf\xf3\xb3w, kot"), vec![&b"\xbf\xf3\xb3w"[..], b"kot"]);
+    }
+
+    #[test]
+    fn test_json_escaping() {
+        let mut out = Vec::new();
+        write_json_string(&mut out, Some(b"a\"b\\c\td\x01")).unwrap();
+        assert_eq!(out, b"\"a\\\"b\\\\c\\td\\u0001\"");
+    }
+}
@@ -1,4 +1,307 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy dict_compile
 
-// Logika komendy dict_compile
\ No newline at end of file
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use morfologik_fsa::iterator::for_each_sequence;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::dictionary_metadata::DictionaryMetadata;
+use morfologik_stemming::encoders::create_encoder;
+use morfologik_stemming::error::StemmingError;
+use morfologik_stemming::sequence_encoder_trait::SequenceEncoder;
+
+use crate::cli::FsaFormat;
+use crate::error::{Result, ToolsError};
+use crate::utils::{open_input, open_output, read_numbered_lines, write_fsa};
+
+/// Maksymalna liczba problemów wypisywanych na standardowe wyjście błędów.
+const MAX_REPORTED_PROBLEMS: usize = 50;
+
+/// Argumenty komendy `dict-compile`.
+#[derive(Args, Debug)]
+pub struct DictCompileArgs {
+    /// Plik źródłowy z wierszami `forma<TAB>lemat<TAB>tag`. Obok musi leżeć plik `.info`
+    /// o tej samej nazwie (np. `slownik.txt` i `slownik.info`).
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy słownika (domyślnie plik wejściowy z rozszerzeniem `.dict`).
+    /// Plik `.info` jest kopiowany obok niego.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Format zapisu automatu.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: FsaFormat,
+
+    /// Pomija powtórzone wpisy (wypisując ostrzeżenia) zamiast przerywać kompilację.
+    #[arg(long)]
+    pub ignore_duplicates: bool,
+}
+
+/// Zakodowane wpisy pliku źródłowego oraz znalezione w nim problemy.
+#[derive(Debug, Default)]
+struct Source {
+    /// Posortowane sekwencje `forma + separator + zakodowany lemat i tag`, bez powtórzeń.
+    sequences: Vec<Vec<u8>>,
+    /// Wiersze, których nie udało się zakodować (numer wiersza, opis).
+    problems: Vec<(usize, String)>,
+    /// Powtórzone wpisy (numer wiersza, opis).
+    duplicates: Vec<(usize, String)>,
+}
+
+pub fn run(args: &DictCompileArgs) -> Result<()> {
+    let metadata = DictionaryMetadata::from_dictionary_path(&args.input)?;
+    let separator = metadata.get_separator()?;
+    if !separator.is_ascii() {
+        return Err(StemmingError::InvalidMetadataValue(format!(
+            "Separator musi być znakiem jednobajtowym, podano '{}'",
+            separator
+        ))
+        .into());
+    }
+    let encoder = create_encoder(&metadata.get_encoder()?, separator as u8);
+    let check_utf8 = matches!(metadata.get_encoding().to_ascii_lowercase().as_str(), "utf-8" | "utf8");
+
+    let lines = read_numbered_lines(&mut open_input(Some(&args.input))?)?;
+    let mut source = read_source(lines, separator as u8, &encoder, check_utf8);
+    if args.ignore_duplicates {
+        for (line, message) in &source.duplicates {
+            eprintln!("Ostrzeżenie: wiersz {}: {}", line, message);
+        }
+    } else {
+        source.problems.append(&mut source.duplicates);
+    }
+    if !source.problems.is_empty() {
+        source.problems.sort();
+        for (line, message) in source.problems.iter().take(MAX_REPORTED_PROBLEMS) {
+            eprintln!("Wiersz {}: {}", line, message);
+        }
+        if source.problems.len() > MAX_REPORTED_PROBLEMS {
+            eprintln!("... (i {} kolejnych)", source.problems.len() - MAX_REPORTED_PROBLEMS);
+        }
+        return Err(ToolsError::InvalidInput(format!(
+            "nieprawidłowe wiersze w {}: {}",
+            args.input.display(),
+            source.problems.len()
+        )));
+    }
+
+    let output_path = args.output.clone().unwrap_or_else(|| args.input.with_extension("dict"));
+    let mut output = open_output(Some(&output_path))?;
+    write_fsa(&mut source.sequences, args.format, &mut output)?;
+    output.flush()?;
+    drop(output);
+
+    let info_source = Dictionary::get_info_file_path(&args.input);
+    let info_target = Dictionary::get_info_file_path(&output_path);
+    if info_source != info_target {
+        std::fs::copy(info_source, info_target)?;
+    }
+
+    validate(&output_path, &source.sequences)
+}
+
+/// Koduje wiersze źródłowe i wyszukuje powtórzone wpisy.
+fn read_source(
+    lines: Vec<(usize, Vec<u8>)>,
+    separator: u8,
+    encoder: &dyn SequenceEncoder,
+    check_utf8: bool,
+) -> Source {
+    let mut source = Source::default();
+    let mut entries = Vec::with_capacity(lines.len());
+    for (line, content) in lines {
+        match encode_line(&content, separator, encoder, check_utf8) {
+            Ok(sequence) => entries.push((sequence, line)),
+            Err(message) => source.problems.push((line, message)),
+        }
+    }
+
+    // Kodowanie jest bezstratne, więc te same sekwencje oznaczają te same wpisy.
+    entries.sort_unstable();
+    let mut first = 0;
+    for i in 1..entries.len() {
+        if entries[i].0 == entries[first].0 {
+            let message = format!("powtórzony wpis (pierwszy raz w wierszu {})", entries[first].1);
+            source.duplicates.push((entries[i].1, message));
+        } else {
+            first = i;
+        }
+    }
+    entries.dedup_by(|a, b| a.0 == b.0);
+    source.sequences = entries.into_iter().map(|(sequence, _)| sequence).collect();
+    source
+}
+
+/// Zamienia wiersz `forma<TAB>lemat<TAB>tag` na sekwencję zapisywaną w automacie.
+fn encode_line(
+    line: &[u8],
+    separator: u8,
+    encoder: &dyn SequenceEncoder,
+    check_utf8: bool,
+) -> std::result::Result<Vec<u8>, String> {
+    if check_utf8 && std::str::from_utf8(line).is_err() {
+        return Err("nieprawidłowe UTF-8 (fsa.dict.encoding to UTF-8)".to_string());
+    }
+    let fields: Vec<&[u8]> = line.splitn(3, |&b| b == b'\t').collect();
+    let (word, base, tag) = match fields[..] {
+        [word, base] => (word, base, &[][..]),
+        [word, base, tag] => (word, base, tag),
+        _ => return Err("oczekiwano formy fleksyjnej, formy podstawowej i tagu rozdzielonych tabulatorami".to_string()),
+    };
+    if word.is_empty() || base.is_empty() {
+        return Err("pusta forma fleksyjna lub podstawowa".to_string());
+    }
+    for (name, field) in [("forma fleksyjna", word), ("forma podstawowa", base), ("tag", tag)] {
+        if field.contains(&separator) {
+            return Err(format!("{} zawiera separator słownika {:?}", name, separator as char));
+        }
+    }
+
+    // Jak w DictCompile.java: forma, separator, zakodowana forma podstawowa, separator, tag.
+    let encoded = encoder.encode(word, base).map_err(|e| e.to_string())?;
+    if encoded.contains(&separator) {
+        return Err(format!("zakodowana forma podstawowa zawiera separator słownika {:?}", separator as char));
+    }
+
+    let mut sequence = Vec::with_capacity(word.len() + encoded.len() + tag.len() + 2);
+    sequence.extend_from_slice(word);
+    sequence.push(separator);
+    sequence.extend_from_slice(&encoded);
+    sequence.push(separator);
+    sequence.extend_from_slice(tag);
+    Ok(sequence)
+}
+
+/// Wczytuje zapisany słownik przez [`Dictionary::from_file`] i sprawdza, czy zawiera
+/// dokładnie skompilowane sekwencje.
+fn validate(path: &Path, expected: &[Vec<u8>]) -> Result<()> {
+    let dictionary = Dictionary::from_file(path)?;
+    let mut count = 0;
+    for_each_sequence(dictionary.fsa.as_ref(), |sequence| {
+        if expected.get(count).map(Vec::as_slice) != Some(sequence) {
+            return Err(ToolsError::ValidationFailed(format!(
+                "nieoczekiwany wpis '{}' w {}",
+                String::from_utf8_lossy(sequence),
+                path.display()
+            )));
+        }
+        count += 1;
+        Ok(())
+    })?;
+    if count != expected.len() {
+        return Err(ToolsError::ValidationFailed(format!(
+            "{} zawiera {} z {} wpisów",
+            path.display(),
+            count,
+            expected.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use morfologik_stemming::dictionary_lookup::DictionaryLookup;
+    use morfologik_stemming::encoders::TrimSuffixEncoder;
+    use morfologik_stemming::stemmer_trait::Stemmer;
+    use std::sync::Arc;
+
+    fn numbered(lines: &[&[u8]]) -> Vec<(usize, Vec<u8>)> {
+        lines.iter().enumerate().map(|(i, l)| (i + 1, l.to_vec())).collect()
+    }
+
+    fn args(input: PathBuf, output: Option<PathBuf>, format: FsaFormat) -> DictCompileArgs {
+        DictCompileArgs { input, output, format, ignore_duplicates: false }
+    }
+
+    #[test]
+    fn test_compiled_dictionary_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("test.txt");
+        std::fs::write(&input, "kotem\tkot\tsubst:sg:inst\nkoty\tkot\tsubst:pl:nom\npsa\tpies\tsubst:sg:gen\n").unwrap();
+        std::fs::write(dir.path().join("test.info"), "fsa.dict.separator=+\nfsa.dict.encoder=SUFFIX\n").unwrap();
+
+        for format in [FsaFormat::Fsa5, FsaFormat::Cfsa2] {
+            let output = dir.path().join("out.dict");
+            run(&args(input.clone(), Some(output.clone()), format)).unwrap();
+            assert!(dir.path().join("out.info").exists());
+
+            let lookup = DictionaryLookup::new(Arc::new(Dictionary::from_file(&output).unwrap()));
+            let forms = lookup.lookup(b"psa").unwrap();
+            assert_eq!(forms.len(), 1);
+            assert_eq!(forms[0].stem(), Some(&b"pies"[..]));
+            assert_eq!(forms[0].tag(), Some(&b"subst:sg:gen"[..]));
+            assert!(lookup.lookup(b"pies").unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_problems_reported_with_line_numbers() {
+        let encoder = TrimSuffixEncoder::new(b'+');
+        let lines = numbered(&[b"kotem\tkot\tsubst", b"koty", b"a+b\tab\tx", b"psa\tpies\tsubst+gen", b"\xff\tx\ty"]);
+        let source = read_source(lines, b'+', &encoder, true);
+        let lines: Vec<usize> = source.problems.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5]);
+        assert!(source.problems[1].1.contains("forma fleksyjna zawiera separator słownika"));
+        assert!(source.problems[2].1.contains("tag zawiera"));
+        assert!(source.problems[3].1.contains("UTF-8"));
+        assert_eq!(source.sequences.len(), 1);
+    }
+
+    #[test]
+    fn test_encoding_error() {
+        // Kod 'A' + 234 to '+', więc zakodowana forma zawierałaby separator.
+        let encoder = TrimSuffixEncoder::new(b'+');
+        let mut line = vec![b'a'; 234];
+        line.extend_from_slice(b"\tb\tx");
+        let source = read_source(vec![(7, line)], b'+', &encoder, true);
+        assert_eq!(source.problems.len(), 1);
+        assert_eq!(source.problems[0].0, 7);
+        assert!(source.problems[0].1.contains("zakodowana forma podstawowa"));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let encoder = TrimSuffixEncoder::new(b'+');
+        let lines = numbered(&[b"koty\tkot\tx", b"psa\tpies\ty", b"koty\tkot\tx", b"koty\tkot\tx"]);
+        let source = read_source(lines, b'+', &encoder, true);
+        assert!(source.problems.is_empty());
+        assert_eq!(
+            source.duplicates,
+            vec![
+                (3, "powtórzony wpis (pierwszy raz w wierszu 1)".to_string()),
+                (4, "powtórzony wpis (pierwszy raz w wierszu 1)".to_string())
+            ]
+        );
+        assert_eq!(source.sequences.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicates_fail_unless_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("test.txt");
+        std::fs::write(&input, "koty\tkot\tx\nkoty\tkot\tx\n").unwrap();
+        std::fs::write(dir.path().join("test.info"), "fsa.dict.separator=+\n").unwrap();
+
+        let result = run(&args(input.clone(), None, FsaFormat::Fsa5));
+        assert!(matches!(result, Err(ToolsError::InvalidInput(msg)) if msg.starts_with("nieprawidłowe wiersze") && msg.ends_with(": 1")));
+
+        let mut ignoring = args(input.clone(), None, FsaFormat::Fsa5);
+        ignoring.ignore_duplicates = true;
+        run(&ignoring).unwrap();
+        assert!(input.with_extension("dict").exists());
+    }
+
+    #[test]
+    fn test_missing_info_is_configuration_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("test.txt");
+        std::fs::write(&input, "koty\tkot\tx\n").unwrap();
+        let error = run(&args(input, None, FsaFormat::Fsa5)).unwrap_err();
+        assert_eq!(error.exit_status(), crate::error::ExitStatus::ErrorConfiguration);
+    }
+}
@@ -1,4 +1,141 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy dict_decompile
 
-// Logika komendy dict_decompile
\ No newline at end of file
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use morfologik_fsa::iterator::for_each_sequence;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::sequence_encoder_trait::SequenceEncoder;
+
+use crate::error::{Result, ToolsError};
+use crate::utils::open_output;
+
+/// Argumenty komendy `dict-decompile`.
+#[derive(Args, Debug)]
+pub struct DictDecompileArgs {
+    /// Plik słownika (.dict) z plikiem `.info` obok.
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy (domyślnie standardowe wyjście). Obok niego zapisywana jest kopia
+    /// pliku `.info`, dzięki czemu wynik można od razu przekazać do `dict-compile`.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Wypisuje wiersze `forma<TAB>lemat<TAB>tag`, czyli w postaci przyjmowanej przez `dict-compile`.
+pub fn run(args: &DictDecompileArgs) -> Result<()> {
+    let dictionary = Dictionary::from_file_validated(&args.input)?;
+    let mut output = open_output(args.output.as_ref())?;
+    decompile(&dictionary, &mut output)?;
+    output.flush()?;
+
+    if let Some(path) = args.output.as_ref().filter(|p| p.as_os_str() != "-") {
+        let info_source = Dictionary::get_info_file_path(&args.input);
+        let info_target = Dictionary::get_info_file_path(path);
+        if info_source != info_target {
+            std::fs::copy(info_source, info_target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Dekoduje wszystkie wpisy słownika do postaci źródłowej.
+fn decompile(dictionary: &Dictionary, output: &mut dyn Write) -> Result<()> {
+    let separator = dictionary.metadata.get_separator()? as u8;
+    for_each_sequence(dictionary.fsa.as_ref(), |sequence| {
+        // Forma fleksyjna nie zawiera separatora, więc pierwszy separator kończy formę.
+        let split = sequence.iter().position(|&b| b == separator).ok_or_else(|| {
+            ToolsError::InvalidInput(format!(
+                "wpis słownika bez separatora: {}",
+                String::from_utf8_lossy(sequence)
+            ))
+        })?;
+        let word = &sequence[..split];
+        let data = &sequence[split + 1..];
+        // Zakodowana forma podstawowa kończy się na kolejnym separatorze, dalej jest tag.
+        let (base, tag) = match data.iter().position(|&b| b == separator) {
+            Some(split) => (&data[..split], Some(&data[split + 1..])),
+            None => (data, None),
+        };
+        let base = dictionary.encoder.decode(word, base)?;
+
+        output.write_all(word)?;
+        output.write_all(b"\t")?;
+        output.write_all(&base)?;
+        if let Some(tag) = tag {
+            output.write_all(b"\t")?;
+            output.write_all(tag)?;
+        }
+        output.write_all(b"\n")?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::FsaFormat;
+    use crate::commands::dict_compile::{self, DictCompileArgs};
+
+    const SOURCE: &str = "kotem\tkot\tsubst:sg:inst\nkoty\tkot\tsubst:pl:nom\npsa\tpies\tsubst:sg:gen\nzapsa\tpies\t\n";
+
+    #[test]
+    fn test_decompiled_output_recompiles_to_same_automaton() {
+        // Brak separatora w `.info` oznacza domyślny tabulator.
+        for (encoder, separator) in [("SUFFIX", Some("+")), ("PREFIX", Some(";")), ("INFIX", None), ("NONE", Some(","))] {
+            let dir = tempfile::tempdir().unwrap();
+            let input = dir.path().join("source.txt");
+            std::fs::write(&input, SOURCE).unwrap();
+            let mut info = format!("fsa.dict.encoder={}\n", encoder);
+            if let Some(separator) = separator {
+                info.push_str(&format!("fsa.dict.separator={}\n", separator));
+            }
+            std::fs::write(dir.path().join("source.info"), info).unwrap();
+
+            let compile = |input: PathBuf, output: PathBuf| {
+                let format = FsaFormat::Cfsa2;
+                dict_compile::run(&DictCompileArgs { input, output: Some(output), format, ignore_duplicates: false })
+                    .unwrap();
+            };
+            let first = dir.path().join("first.dict");
+            compile(input, first.clone());
+
+            let text = dir.path().join("decompiled.txt");
+            run(&DictDecompileArgs { input: first.clone(), output: Some(text.clone()) }).unwrap();
+            let mut lines: Vec<String> = std::fs::read_to_string(&text).unwrap().lines().map(String::from).collect();
+            lines.sort();
+            let mut expected: Vec<&str> = SOURCE.lines().collect();
+            expected.sort();
+            assert_eq!(lines, expected, "encoder {}", encoder);
+
+            let second = dir.path().join("second.dict");
+            compile(text, second.clone());
+            assert_eq!(std::fs::read(first).unwrap(), std::fs::read(second).unwrap());
+        }
+    }
+
+    /// Dekompiluje dołączony `polish.dict` i kompiluje go z powrotem do CFSA2; wynik musi
+    /// być identyczny z oryginałem.
+    ///
+    /// Trwa długo w trybie debug; uruchamiać przez
+    /// `cargo test --release -p morfologik-tools -- --ignored polish_dict`.
+    #[test]
+    #[ignore]
+    fn test_polish_dict_decompiles_and_recompiles_to_same_bytes() {
+        let resources = concat!(env!("CARGO_MANIFEST_DIR"), "/../crates/morfologik-polish/resources/morfologik/stemming/polish");
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("polish.dict");
+        std::fs::copy(format!("{}/polish.dict", resources), &original).unwrap();
+        std::fs::copy(format!("{}/polish.info", resources), dir.path().join("polish.info")).unwrap();
+
+        let text = dir.path().join("polish.txt");
+        run(&DictDecompileArgs { input: original.clone(), output: Some(text.clone()) }).unwrap();
+
+        let rebuilt = dir.path().join("rebuilt.dict");
+        let args = DictCompileArgs { input: text, output: Some(rebuilt.clone()), format: FsaFormat::Cfsa2, ignore_duplicates: false };
+        dict_compile::run(&args).unwrap();
+        assert!(std::fs::read(original).unwrap() == std::fs::read(rebuilt).unwrap());
+    }
+}
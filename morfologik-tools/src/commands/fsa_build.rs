@@ -1,4 +1,144 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy fsa_build
 
-// Logika komendy fsa_build
\ No newline at end of file
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+
+use crate::cli::FsaFormat;
+use crate::error::Result;
+use crate::utils::{open_input, open_output, serializer_for, trim_line_ending};
+
+/// Argumenty komendy `fsa-build`.
+#[derive(Args, Debug)]
+pub struct FsaBuildArgs {
+    /// Plik z sekwencjami (domyślnie standardowe wejście).
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+
+    /// Plik wynikowy automatu (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub options: BuildOptions,
+}
+
+/// Opcje budowania automatu wspólne dla `fsa-build` i `fsa-compile`.
+#[derive(Args, Debug, Default)]
+pub struct BuildOptions {
+    /// Format zapisu automatu.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: FsaFormat,
+
+    /// Zapisuje w węzłach liczby sekwencji (flaga NUMBERS), potrzebne do haszowania doskonałego.
+    #[arg(short, long)]
+    pub numbers: bool,
+
+    /// Sekwencje rozdzielone bajtem 0 zamiast końców wiersza.
+    #[arg(short = 'z', long)]
+    pub null_separated: bool,
+
+    /// Wejście jest już posortowane: sekwencje trafiają od razu do budowniczego, bez
+    /// wczytywania całości do pamięci. Nieposortowane wejście kończy się błędem.
+    #[arg(short, long)]
+    pub sorted: bool,
+}
+
+pub fn run(args: &FsaBuildArgs) -> Result<()> {
+    let mut output = open_output(args.output.as_ref())?;
+    build(&mut open_input(args.input.as_ref())?, &args.options, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Buduje minimalny automat z sekwencji wejścia i zapisuje go zgodnie z opcjami.
+///
+/// Puste sekwencje są pomijane, a powtórzenia usuwane. Przy separatorze końca wiersza
+/// usuwane jest też końcowe `\r`.
+pub(crate) fn build(reader: &mut dyn BufRead, options: &BuildOptions, writer: &mut dyn Write) -> Result<()> {
+    let separator = if options.null_separated { 0 } else { b'\n' };
+    let fsa = if options.sorted {
+        let mut builder = FsaBuilder::new();
+        for_each_sequence(reader, separator, |sequence| Ok(builder.add(sequence)?))?;
+        builder.complete()
+    } else {
+        let mut sequences = Vec::new();
+        for_each_sequence(reader, separator, |sequence| {
+            sequences.push(sequence.to_vec());
+            Ok(())
+        })?;
+        sequences.sort_unstable();
+        sequences.dedup();
+        FsaBuilder::build(&sequences)?
+    };
+    serializer_for(options.format, options.numbers).serialize(&fsa, writer)?;
+    Ok(())
+}
+
+fn for_each_sequence(
+    reader: &mut dyn BufRead,
+    separator: u8,
+    mut f: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let mut buffer = Vec::new();
+    while reader.read_until(separator, &mut buffer)? > 0 {
+        let sequence = match separator {
+            b'\n' => trim_line_ending(&buffer),
+            _ => buffer.strip_suffix(&[separator]).unwrap_or(&buffer),
+        };
+        if !sequence.is_empty() {
+            f(sequence)?;
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ToolsError;
+    use morfologik_fsa::fsa5::FSA5;
+    use morfologik_fsa::fsa_header::FsaFlags;
+    use morfologik_fsa::fsa_trait::Fsa;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+    use morfologik_fsa_builders::error::BuilderError;
+    use std::io::Cursor;
+
+    fn build_fsa5(input: &[u8], options: &BuildOptions) -> Result<FSA5> {
+        let mut bytes = Vec::new();
+        build(&mut Cursor::new(input), options, &mut bytes)?;
+        Ok(FSA5::from_reader(&mut Cursor::new(bytes)).unwrap())
+    }
+
+    fn sequences(fsa: &FSA5) -> Vec<Vec<u8>> {
+        ByteSequenceIterator::new(fsa).map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_null_separated_input_keeps_newlines() {
+        let options = BuildOptions { null_separated: true, ..Default::default() };
+        let fsa = build_fsa5(b"b\nc\0a\0\0b\nc\0", &options).unwrap();
+        assert_eq!(sequences(&fsa), vec![b"a".to_vec(), b"b\nc".to_vec()]);
+    }
+
+    #[test]
+    fn test_sorted_input_is_streamed() {
+        let options = BuildOptions { sorted: true, ..Default::default() };
+        let fsa = build_fsa5(b"ala\nala\nkot\r\n", &options).unwrap();
+        assert_eq!(sequences(&fsa), vec![b"ala".to_vec(), b"kot".to_vec()]);
+
+        let result = build_fsa5(b"kot\nala\n", &options);
+        assert!(matches!(result, Err(ToolsError::Builder(BuilderError::UnsortedInput { .. }))));
+    }
+
+    #[test]
+    fn test_numbers_flag() {
+        let options = BuildOptions { numbers: true, ..Default::default() };
+        let fsa = build_fsa5(b"kot\nala\npies\n", &options).unwrap();
+        assert!(fsa.get_flags().contains(FsaFlags::NUMBERS));
+        assert_eq!(fsa.get_right_language_count(fsa.get_root_node()).unwrap(), 3);
+    }
+}
@@ -1,4 +1,57 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy fsa_compile
 
-// Logika komendy fsa_compile
\ No newline at end of file
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::commands::fsa_build::{build, BuildOptions};
+use crate::error::Result;
+use crate::utils::{open_input, open_output};
+
+/// Argumenty komendy `fsa-compile`.
+#[derive(Args, Debug)]
+pub struct FsaCompileArgs {
+    /// Plik z sekwencjami.
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy automatu.
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    #[command(flatten)]
+    pub options: BuildOptions,
+}
+
+pub fn run(args: &FsaCompileArgs) -> Result<()> {
+    let mut input = open_input(Some(&args.input))?;
+    let mut output = open_output(Some(&args.output))?;
+    build(&mut input, &args.options, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::FsaFormat;
+    use crate::utils::read_fsa;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+
+    #[test]
+    fn test_compile_sorts_and_deduplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("words.txt");
+        let output = dir.path().join("words.fsa");
+        std::fs::write(&input, "kot\nala\nkot\n\npies\n").unwrap();
+
+        for format in [FsaFormat::Fsa5, FsaFormat::Cfsa2] {
+            let options = BuildOptions { format, ..Default::default() };
+            run(&FsaCompileArgs { input: input.clone(), output: output.clone(), options }).unwrap();
+            let fsa = read_fsa(&output).unwrap();
+            let sequences: Vec<Vec<u8>> = ByteSequenceIterator::new(fsa.as_ref()).map(|s| s.unwrap()).collect();
+            assert_eq!(sequences, vec![b"ala".to_vec(), b"kot".to_vec(), b"pies".to_vec()]);
+        }
+    }
+}
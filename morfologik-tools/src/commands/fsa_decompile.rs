@@ -1,4 +1,61 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy fsa_decompile
 
-// Logika komendy fsa_decompile
\ No newline at end of file
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use morfologik_fsa::iterator::for_each_sequence;
+
+use crate::error::Result;
+use crate::utils::{open_output, read_fsa};
+
+/// Argumenty komendy `fsa-decompile`.
+#[derive(Args, Debug)]
+pub struct FsaDecompileArgs {
+    /// Plik automatu (FSA5, CFSA lub CFSA2).
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Kończy sekwencje bajtem 0 zamiast końca wiersza.
+    #[arg(short = 'z', long)]
+    pub null_separated: bool,
+}
+
+/// Wypisuje sekwencje w porządku leksykograficznym, w postaci przyjmowanej przez `fsa-build`.
+pub fn run(args: &FsaDecompileArgs) -> Result<()> {
+    let fsa = read_fsa(&args.input)?;
+    let mut output = open_output(args.output.as_ref())?;
+    let terminator: &[u8] = if args.null_separated { b"\0" } else { b"\n" };
+    for_each_sequence(fsa.as_ref(), |sequence| -> Result<()> {
+        output.write_all(sequence)?;
+        output.write_all(terminator)?;
+        Ok(())
+    })?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::FsaFormat;
+    use crate::commands::fsa_build::{build, BuildOptions};
+    use std::fs::File;
+
+    #[test]
+    fn test_round_trip_null_separated() {
+        let dir = tempfile::tempdir().unwrap();
+        let fsa = dir.path().join("set.fsa");
+        let output = dir.path().join("set.txt");
+        let input: &[u8] = b"zeta\0alfa\nbeta\0";
+        let options = BuildOptions { format: FsaFormat::Cfsa2, null_separated: true, ..Default::default() };
+        build(&mut &input[..], &options, &mut File::create(&fsa).unwrap()).unwrap();
+
+        run(&FsaDecompileArgs { input: fsa, output: Some(output.clone()), null_separated: true }).unwrap();
+        assert_eq!(std::fs::read(output).unwrap(), b"alfa\nbeta\0zeta\0");
+    }
+}
@@ -1,4 +1,255 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy fsa_dump
 
-// Logika komendy fsa_dump
\ No newline at end of file
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use morfologik_fsa::error::FsaError;
+use morfologik_fsa::fsa_trait::{ArcOffset, Fsa, Node};
+use morfologik_fsa::traversal::{FsaTraversal, StateVisitor};
+
+use crate::cli::DumpFormat;
+use crate::error::Result;
+use crate::utils::{label_text, open_output, read_fsa};
+
+/// Argumenty komendy `fsa-dump`.
+#[derive(Args, Debug)]
+pub struct FsaDumpArgs {
+    /// Plik automatu (FSA5, CFSA lub CFSA2).
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Format wyniku.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: DumpFormat,
+
+    /// Maksymalna liczba wypisywanych stanów (pozostałe są pomijane).
+    #[arg(short, long)]
+    pub max_states: Option<usize>,
+}
+
+/// Łuk odczytany z automatu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArcRow {
+    arc: ArcOffset,
+    label: u8,
+    is_final: bool,
+    is_last: bool,
+    target: Node,
+}
+
+/// Stan odwiedzony podczas przejścia; stany bez łuków mają pustą listę.
+#[derive(Debug)]
+struct StateRow {
+    node: Node,
+    arcs: Vec<ArcRow>,
+}
+
+/// Zbiera stany i łuki w kolejności przejścia w głąb, odwiedzając każdy stan raz.
+#[derive(Debug, Default)]
+struct DumpVisitor {
+    max_states: Option<usize>,
+    visited: HashSet<Node>,
+    states: Vec<StateRow>,
+    truncated: bool,
+    error: Option<FsaError>,
+}
+
+impl DumpVisitor {
+    fn read_arcs<F: Fsa + ?Sized>(fsa: &F, node: Node) -> std::result::Result<Vec<ArcRow>, FsaError> {
+        let mut arcs = Vec::new();
+        let Some(mut arc) = fsa.first_arc_opt(node)? else {
+            return Ok(arcs);
+        };
+        loop {
+            let is_last = fsa.is_arc_terminal(arc)?;
+            arcs.push(ArcRow {
+                arc,
+                label: fsa.get_arc_label(arc)?,
+                is_final: fsa.is_arc_final(arc)?,
+                is_last,
+                target: fsa.get_end_node(arc)?,
+            });
+            if is_last {
+                return Ok(arcs);
+            }
+            arc = fsa.get_next_arc(arc)?;
+        }
+    }
+}
+
+impl<F: Fsa + ?Sized> StateVisitor<F> for DumpVisitor {
+    fn visit_state(&mut self, fsa: &F, node: Node) -> bool {
+        if self.error.is_some() || self.visited.contains(&node) {
+            return false;
+        }
+        if self.max_states.is_some_and(|max| self.states.len() >= max) {
+            self.truncated = true;
+            return false;
+        }
+        self.visited.insert(node);
+        match Self::read_arcs(fsa, node) {
+            Ok(arcs) => {
+                self.states.push(StateRow { node, arcs });
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    fn accept_arc(&mut self, _fsa: &F, _arc: ArcOffset) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Wypisuje strukturę automatu jako tabelę łuków lub graf DOT.
+///
+/// Węzły identyfikowane są offsetami w danych automatu, etykiety zapisywane przez
+/// [`label_text`]. Flaga `F` oznacza łuk końcowy, a `L` ostatni łuk węzła.
+pub fn run(args: &FsaDumpArgs) -> Result<()> {
+    let fsa = read_fsa(&args.input)?;
+    let mut output = open_output(args.output.as_ref())?;
+    dump(fsa.as_ref(), args.format, args.max_states, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn dump<F: Fsa + ?Sized>(fsa: &F, format: DumpFormat, max_states: Option<usize>, output: &mut dyn Write) -> Result<()> {
+    let mut visitor = DumpVisitor { max_states, ..Default::default() };
+    FsaTraversal::new(fsa).visit_dfs(fsa.get_root_node(), &mut Vec::new(), &mut visitor)?;
+    if let Some(e) = visitor.error {
+        return Err(e.into());
+    }
+    match format {
+        DumpFormat::Table => write_table(&visitor, output),
+        DumpFormat::Dot => write_dot(fsa.get_root_node(), &visitor, output),
+    }
+}
+
+fn write_table(visitor: &DumpVisitor, output: &mut dyn Write) -> Result<()> {
+    writeln!(output, "węzeł\tłuk\tetykieta\tflagi\tcel")?;
+    for state in &visitor.states {
+        for arc in &state.arcs {
+            writeln!(
+                output,
+                "{}\t{}\t{}\t{}\t{}",
+                state.node,
+                arc.arc,
+                label_text(arc.label),
+                flags_text(arc),
+                arc.target
+            )?;
+        }
+    }
+    if visitor.truncated {
+        writeln!(output, "# ucięto, wypisane stany: {}", visitor.states.len())?;
+    }
+    Ok(())
+}
+
+fn write_dot(root: Node, visitor: &DumpVisitor, output: &mut dyn Write) -> Result<()> {
+    writeln!(output, "digraph fsa {{")?;
+    writeln!(output, "  rankdir=LR;")?;
+    writeln!(output, "  node [shape=circle];")?;
+    for state in &visitor.states {
+        let mut attributes = format!("label=\"{}\"", state.node);
+        if state.node == root {
+            attributes.push_str(", style=bold");
+        }
+        if state.arcs.is_empty() {
+            attributes.push_str(", shape=doublecircle");
+        }
+        writeln!(output, "  n{} [{}];", state.node, attributes)?;
+    }
+    for state in &visitor.states {
+        for arc in &state.arcs {
+            let label = label_text(arc.label).replace('\\', "\\\\").replace('"', "\\\"");
+            let flags = flags_text(arc);
+            let label = if flags.is_empty() { label } else { format!("{} ({})", label, flags) };
+            writeln!(output, "  n{} -> n{} [label=\"{}\"];", state.node, arc.target, label)?;
+        }
+    }
+    if visitor.truncated {
+        writeln!(output, "  // ucięto, wypisane stany: {}", visitor.states.len())?;
+    }
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+fn flags_text(arc: &ArcRow) -> String {
+    let mut flags = String::new();
+    if arc.is_final {
+        flags.push('F');
+    }
+    if arc.is_last {
+        flags.push('L');
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use morfologik_fsa::fsa5::FSA5;
+    use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+    use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+    use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+    use std::io::Cursor;
+
+    fn fsa5(sequences: &[&[u8]]) -> FSA5 {
+        let fsa = FsaBuilder::build(sequences.iter()).unwrap();
+        let mut bytes = Vec::new();
+        Fsa5Serializer::new().serialize(&fsa, &mut bytes).unwrap();
+        FSA5::from_reader(&mut Cursor::new(bytes)).unwrap()
+    }
+
+    fn dump_to_string(fsa: &FSA5, format: DumpFormat, max_states: Option<usize>) -> String {
+        let mut out = Vec::new();
+        dump(fsa, format, max_states, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        let fsa = fsa5(&[b"a b", b"ac"]);
+        // Korzeń (4, za węzłem stanu końcowego i epsilon): 'a' -> 6; węzeł 6: ' ' -> 10,
+        // 'c' -> 0; węzeł 10: 'b' -> 0 (stan końcowy).
+        assert_eq!(
+            dump_to_string(&fsa, DumpFormat::Table, None),
+            "węzeł\tłuk\tetykieta\tflagi\tcel\n\
+             4\t4\ta\tL\t6\n\
+             6\t6\t0x20\t\t10\n\
+             6\t8\tc\tFL\t0\n\
+             10\t10\tb\tFL\t0\n"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let fsa = fsa5(&[b"\"", b"a"]);
+        let dot = dump_to_string(&fsa, DumpFormat::Dot, None);
+        assert!(dot.starts_with("digraph fsa {\n"));
+        assert!(dot.contains("  n4 [label=\"4\", style=bold];\n"));
+        assert!(dot.contains("  n0 [label=\"0\", shape=doublecircle];\n"));
+        assert!(dot.contains("  n4 -> n0 [label=\"\\\" (F)\"];\n"));
+        assert!(dot.contains("  n4 -> n0 [label=\"a (FL)\"];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_max_states() {
+        let fsa = fsa5(&[b"abc", b"abd", b"xyz"]);
+        let table = dump_to_string(&fsa, DumpFormat::Table, Some(2));
+        let nodes: HashSet<&str> = table.lines().skip(1).filter(|l| !l.starts_with('#')).map(|l| l.split('\t').next().unwrap()).collect();
+        assert_eq!(nodes.len(), 2);
+        assert!(table.ends_with("# ucięto, wypisane stany: 2\n"));
+    }
+}
@@ -1,4 +1,97 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Logika komendy fsa_info
 
-// Logika komendy fsa_info
\ No newline at end of file
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use morfologik_fsa::fsa_header::FsaHeader;
+use morfologik_fsa::loader::FsaRegistry;
+use morfologik_fsa_builders::fsa_info::FsaInfo;
+use morfologik_stemming::dictionary::Dictionary;
+use morfologik_stemming::dictionary_metadata::DictionaryMetadata;
+
+use crate::error::Result;
+use crate::utils::{label_text, open_output, read_fsa};
+
+/// Argumenty komendy `fsa-info`.
+#[derive(Args, Debug)]
+pub struct FsaInfoArgs {
+    /// Plik automatu (FSA5, CFSA lub CFSA2).
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Plik wynikowy (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Wypisuje statystyki automatu ([`FsaInfo`]) w postaci `klucz: wartość`, a po nich
+/// metadane z pliku `.info` leżącego obok, jeśli taki istnieje.
+pub fn run(args: &FsaInfoArgs) -> Result<()> {
+    let mut output = open_output(args.output.as_ref())?;
+    report(&args.input, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn report(path: &Path, output: &mut dyn Write) -> Result<()> {
+    let version = FsaHeader::read_any_version(&mut File::open(path)?)?.version;
+    let size = std::fs::metadata(path)?.len();
+    let fsa = read_fsa(path)?;
+    let info = FsaInfo::new(fsa.as_ref())?;
+
+    let format = FsaRegistry::new().format_name(version).unwrap_or("?");
+    writeln!(output, "Plik: {}", path.display())?;
+    writeln!(output, "Format: {} (0x{:02X})", format, version)?;
+    writeln!(output, "Rozmiar: {} B", size)?;
+    writeln!(output, "Flagi: {:?}", fsa.get_flags())?;
+    writeln!(output, "NUMBERS: {}", if info.has_numbers { "tak" } else { "nie" })?;
+    writeln!(output, "Stany: {}", info.states)?;
+    writeln!(output, "Łuki: {}", info.arcs)?;
+    writeln!(output, "Łuki końcowe: {}", info.final_arcs)?;
+    writeln!(output, "Sekwencje: {}", info.sequences)?;
+    writeln!(output, "Maksymalna głębokość: {}", info.max_depth)?;
+    writeln!(output, "Bajty na łuk: {:.2}", info.bytes_per_arc(size))?;
+    writeln!(output, "Etykiety:")?;
+    for (label, count) in info.labels_by_frequency() {
+        writeln!(output, "  {}\t{}", label_text(label), count)?;
+    }
+
+    let info_path = Dictionary::get_info_file_path(path);
+    if info_path.exists() {
+        writeln!(output, "Metadane ({}):", info_path.display())?;
+        for (key, value) in DictionaryMetadata::from_info_file(&info_path)?.attributes() {
+            writeln!(output, "  {} = {}", key, value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::FsaFormat;
+    use crate::utils::write_fsa;
+
+    #[test]
+    fn test_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("words.dict");
+        let mut sequences = vec![b"ala".to_vec(), b"kot".to_vec(), b"kota".to_vec()];
+        write_fsa(&mut sequences, FsaFormat::Cfsa2, &mut File::create(&path).unwrap()).unwrap();
+        std::fs::write(dir.path().join("words.info"), "fsa.dict.separator=+\n").unwrap();
+
+        let mut out = Vec::new();
+        report(&path, &mut out).unwrap();
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("Format: CFSA2 (0xC6)\n"));
+        assert!(report.contains("NUMBERS: nie\n"));
+        // "ala" i "kota" kończą się tym samym łukiem.
+        assert!(report.contains("Łuki końcowe: 2\n"));
+        assert!(report.contains("Sekwencje: 3\n"));
+        assert!(report.contains("Maksymalna głębokość: 4\n"));
+        assert!(report.contains("Etykiety:\n  a\t2\n"));
+        assert!(report.ends_with("  fsa.dict.separator = +\n"));
+    }
+}
@@ -0,0 +1,87 @@
+// Logika komend fsa_union, fsa_intersect i fsa_diff
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use morfologik_fsa_builders::set_operations::{apply, SetOperation};
+
+use crate::cli::FsaFormat;
+use crate::error::Result;
+use crate::utils::{open_output, read_fsa, serializer_for};
+
+/// Argumenty komend `fsa-union`, `fsa-intersect` i `fsa-diff`.
+#[derive(Args, Debug)]
+pub struct FsaSetArgs {
+    /// Pierwszy automat (FSA5, CFSA lub CFSA2).
+    #[arg(short = 'a', long)]
+    pub first: PathBuf,
+
+    /// Drugi automat (FSA5, CFSA lub CFSA2).
+    #[arg(short = 'b', long)]
+    pub second: PathBuf,
+
+    /// Plik wynikowy automatu (domyślnie standardowe wyjście).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Format zapisu automatu.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub format: FsaFormat,
+
+    /// Zapisuje w węzłach liczby sekwencji (flaga NUMBERS), potrzebne do haszowania doskonałego.
+    #[arg(short, long)]
+    pub numbers: bool,
+}
+
+pub fn run(operation: SetOperation, args: &FsaSetArgs) -> Result<()> {
+    let first = read_fsa(&args.first)?;
+    let second = read_fsa(&args.second)?;
+    let fsa = apply(operation, first.as_ref(), second.as_ref())?;
+    let mut output = open_output(args.output.as_ref())?;
+    serializer_for(args.format, args.numbers).serialize(&fsa, &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::fsa_build::{build, BuildOptions};
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+
+    #[test]
+    fn test_set_operations_on_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("lexicon.fsa");
+        let second = dir.path().join("blacklist.fsa");
+        let output = dir.path().join("result.fsa");
+        let mut bytes = Vec::new();
+        build(&mut &b"ala\nkot\npies\n"[..], &BuildOptions::default(), &mut bytes).unwrap();
+        std::fs::write(&first, &bytes).unwrap();
+        let options = BuildOptions { format: FsaFormat::Cfsa2, ..Default::default() };
+        let mut bytes = Vec::new();
+        build(&mut &b"kot\nmysz\n"[..], &options, &mut bytes).unwrap();
+        std::fs::write(&second, &bytes).unwrap();
+
+        let cases = [
+            (SetOperation::Union, vec!["ala", "kot", "mysz", "pies"]),
+            (SetOperation::Intersection, vec!["kot"]),
+            (SetOperation::Difference, vec!["ala", "pies"]),
+        ];
+        for (operation, expected) in cases {
+            let args = FsaSetArgs {
+                first: first.clone(),
+                second: second.clone(),
+                output: Some(output.clone()),
+                format: FsaFormat::Cfsa2,
+                numbers: true,
+            };
+            run(operation, &args).unwrap();
+            let fsa = read_fsa(&output).unwrap();
+            let sequences: Vec<String> =
+                ByteSequenceIterator::new(fsa.as_ref()).map(|s| String::from_utf8(s.unwrap()).unwrap()).collect();
+            assert_eq!(sequences, expected);
+        }
+    }
+}
@@ -4,6 +4,29 @@ pub mod fsa_compile;
 pub mod fsa_dump;
 pub mod fsa_info_cmd;
 pub mod fsa_decompile;
+pub mod fsa_set;
 pub mod dict_compile;
 pub mod dict_decompile;
 pub mod dict_apply;
+
+use morfologik_fsa_builders::set_operations::SetOperation;
+
+use crate::cli::Commands;
+use crate::error::Result;
+
+/// Uruchamia wybraną komendę.
+pub fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::FsaBuild(args) => fsa_build::run(&args),
+        Commands::FsaCompile(args) => fsa_compile::run(&args),
+        Commands::FsaDecompile(args) => fsa_decompile::run(&args),
+        Commands::FsaDump(args) => fsa_dump::run(&args),
+        Commands::FsaInfo(args) => fsa_info_cmd::run(&args),
+        Commands::FsaUnion(args) => fsa_set::run(SetOperation::Union, &args),
+        Commands::FsaIntersect(args) => fsa_set::run(SetOperation::Intersection, &args),
+        Commands::FsaDiff(args) => fsa_set::run(SetOperation::Difference, &args),
+        Commands::DictCompile(args) => dict_compile::run(&args),
+        Commands::DictDecompile(args) => dict_decompile::run(&args),
+        Commands::DictApply(args) => dict_apply::run(&args),
+    }
+}
@@ -1,2 +1,94 @@
 // Błędy specyficzne dla narzędzi, ExitStatus
-// Można zdefiniować enum dla ExitStatus
+
+use std::process::ExitCode;
+
+use morfologik_fsa::error::FsaError;
+use morfologik_fsa_builders::error::BuilderError;
+use morfologik_stemming::error::StemmingError;
+use thiserror::Error;
+
+/// Typ Result używany przez komendy.
+pub type Result<T> = std::result::Result<T, ToolsError>;
+
+/// Kody wyjścia narzędzi (jak `ExitStatus` w `morfologik-tools` w Javie).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success = 0,
+    UnknownError = 1,
+    /// Ten sam kod zwraca clap przy błędnych argumentach wiersza poleceń.
+    ErrorInvalidArguments = 2,
+    ErrorConfiguration = 3,
+    ErrorOther = 4,
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+/// Błędy zgłaszane przez komendy CLI.
+#[derive(Debug, Error)]
+pub enum ToolsError {
+    /// Błąd wejścia/wyjścia.
+    #[error("Błąd I/O: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Błąd odczytu lub obsługi automatu.
+    #[error("Błąd automatu: {0}")]
+    Fsa(#[from] FsaError),
+
+    /// Błąd budowania lub serializacji automatu.
+    #[error("Błąd budowania automatu: {0}")]
+    Builder(#[from] BuilderError),
+
+    /// Błąd słownika (metadane, kodowanie, wyszukiwanie).
+    #[error("Błąd słownika: {0}")]
+    Stemming(#[from] StemmingError),
+
+    /// Nieprawidłowe argumenty lub ich kombinacja.
+    #[error("Nieprawidłowe argumenty: {0}")]
+    InvalidArguments(String),
+
+    /// Nieprawidłowe dane wejściowe (np. błędny format wiersza).
+    #[error("Nieprawidłowe dane wejściowe: {0}")]
+    InvalidInput(String),
+
+    /// Zapisany wynik nie przeszedł weryfikacji po ponownym wczytaniu.
+    #[error("Weryfikacja wyniku nie powiodła się: {0}")]
+    ValidationFailed(String),
+}
+
+impl ToolsError {
+    /// Kod wyjścia odpowiadający błędowi.
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            ToolsError::InvalidArguments(_) => ExitStatus::ErrorInvalidArguments,
+            ToolsError::Stemming(StemmingError::MetadataNotFound(_))
+            | ToolsError::Stemming(StemmingError::InvalidMetadataValue(_)) => ExitStatus::ErrorConfiguration,
+            _ => ExitStatus::ErrorOther,
+        }
+    }
+
+    /// Czy błąd to zamknięty potok na wyjściu (np. `| head`), który nie jest traktowany jako porażka.
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(self, ToolsError::Io(e) if e.kind() == std::io::ErrorKind::BrokenPipe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_mapping() {
+        assert_eq!(ToolsError::InvalidArguments("x".into()).exit_status(), ExitStatus::ErrorInvalidArguments);
+        assert_eq!(
+            ToolsError::Stemming(StemmingError::MetadataNotFound("a.info".into())).exit_status(),
+            ExitStatus::ErrorConfiguration
+        );
+        assert_eq!(ToolsError::InvalidInput("x".into()).exit_status(), ExitStatus::ErrorOther);
+        let broken = ToolsError::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(broken.is_broken_pipe());
+    }
+}
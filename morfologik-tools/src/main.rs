@@ -1,16 +1,24 @@
 // Główny punkt wejścia dla narzędzi CLI
-// pub mod cli;
+pub mod cli;
 pub mod commands;
 pub mod utils;
 pub mod error;
 
-fn main() {
-    println!("Morfologik Tools CLI - W budowie!");
-    // Tutaj logika parsowania argumentów i wywoływania komend
-    // np. przy użyciu clap
-    // let cli_args = cli::Cli::parse();
-    // match cli_args.command {
-    //    cli::Commands::FsaBuild(args) => commands::fsa_build::run(args),
-    //    ...
-    // }
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use crate::error::ExitStatus;
+
+fn main() -> ExitCode {
+    let cli = cli::Cli::parse();
+    match commands::run(cli.command) {
+        Ok(()) => ExitStatus::Success.into(),
+        // Zamknięty potok (np. `| head`) nie jest błędem.
+        Err(e) if e.is_broken_pipe() => ExitStatus::Success.into(),
+        Err(e) => {
+            eprintln!("Błąd: {}", e);
+            e.exit_status().into()
+        }
+    }
 }
@@ -1,4 +1,146 @@
-// Plik wygenerowany automatycznie przez skrypt.
-// TODO: Dodać właściwą implementację.
+// Wspólne funkcje pomocnicze dla narzędzi
 
-// Wspólne funkcje pomocnicze dla narzędzi
\ No newline at end of file
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use morfologik_fsa::fsa_trait::Fsa;
+use morfologik_fsa::loader;
+use morfologik_fsa_builders::cfsa2_serializer::CFSA2Serializer;
+use morfologik_fsa_builders::fsa5_serializer::Fsa5Serializer;
+use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+use morfologik_fsa_builders::fsa_serializer_trait::FsaSerializer;
+
+use crate::cli::FsaFormat;
+use crate::error::Result;
+
+/// Ścieżka pliku lub `None`, jeśli należy użyć standardowego strumienia (brak ścieżki lub `-`).
+fn file_path(path: Option<&PathBuf>) -> Option<&Path> {
+    path.map(PathBuf::as_path).filter(|p| p.as_os_str() != "-")
+}
+
+/// Otwiera plik wejściowy lub standardowe wejście.
+pub fn open_input(path: Option<&PathBuf>) -> Result<Box<dyn BufRead>> {
+    Ok(match file_path(path) {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(BufReader::new(io::stdin().lock())),
+    })
+}
+
+/// Otwiera (buforowany) plik wyjściowy lub standardowe wyjście.
+pub fn open_output(path: Option<&PathBuf>) -> Result<Box<dyn Write>> {
+    Ok(match file_path(path) {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+/// Wczytuje automat, rozpoznając format po nagłówku, i odrzuca go, jeśli walidacja
+/// wykryje uszkodzenie (zamiast błędów lub pętli w trakcie przetwarzania).
+pub fn read_fsa(path: &Path) -> Result<Box<dyn Fsa>> {
+    Ok(loader::open_validated(path)?)
+}
+
+/// Zwraca serializator dla wybranego formatu, opcjonalnie zapisujący flagę NUMBERS.
+pub fn serializer_for(format: FsaFormat, numbers: bool) -> Box<dyn FsaSerializer> {
+    match (format, numbers) {
+        (FsaFormat::Fsa5, false) => Box::new(Fsa5Serializer::new()),
+        (FsaFormat::Fsa5, true) => Box::new(Fsa5Serializer::new().with_numbers()),
+        (FsaFormat::Cfsa2, false) => Box::new(CFSA2Serializer::new()),
+        (FsaFormat::Cfsa2, true) => Box::new(CFSA2Serializer::new().with_numbers()),
+    }
+}
+
+/// Sortuje sekwencje, usuwa powtórzenia, buduje z nich automat i zapisuje go w wybranym formacie.
+pub fn write_fsa(sequences: &mut Vec<Vec<u8>>, format: FsaFormat, writer: &mut dyn Write) -> Result<()> {
+    sequences.sort_unstable();
+    sequences.dedup();
+    let fsa = FsaBuilder::build(sequences.iter())?;
+    serializer_for(format, false).serialize(&fsa, writer)?;
+    Ok(())
+}
+
+/// Wczytuje niepuste wiersze razem z ich numerami w pliku (liczonymi od 1).
+pub fn read_numbered_lines(reader: &mut dyn BufRead) -> Result<Vec<(usize, Vec<u8>)>> {
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+    let mut number = 0;
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        number += 1;
+        let line = trim_line_ending(&buffer);
+        if !line.is_empty() {
+            lines.push((number, line.to_vec()));
+        }
+        buffer.clear();
+    }
+    Ok(lines)
+}
+
+/// Etykieta łuku do wypisania: drukowalny znak ASCII wprost, pozostałe bajty jako `0xNN`.
+pub fn label_text(label: u8) -> String {
+    if label.is_ascii_graphic() {
+        (label as char).to_string()
+    } else {
+        format!("0x{:02X}", label)
+    }
+}
+
+/// Usuwa końcowe `\n` (i ewentualne `\r`) z wczytanego wiersza.
+pub fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use morfologik_fsa::iterator::ByteSequenceIterator;
+    use morfologik_fsa_builders::fsa_builder::FsaBuilder;
+
+    #[test]
+    fn test_trim_line_ending() {
+        assert_eq!(trim_line_ending(b"kot\r\n"), b"kot");
+        assert_eq!(trim_line_ending(b"kot\n"), b"kot");
+        assert_eq!(trim_line_ending(b"kot"), b"kot");
+    }
+
+    #[test]
+    fn test_read_numbered_lines_skips_empty() {
+        let mut input = Cursor::new(b"kot\r\n\nala\npies".to_vec());
+        assert_eq!(
+            read_numbered_lines(&mut input).unwrap(),
+            vec![(1, b"kot".to_vec()), (3, b"ala".to_vec()), (4, b"pies".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_read_fsa_detects_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let fsa = FsaBuilder::build([&b"ala"[..], b"kot"]).unwrap();
+        for format in [FsaFormat::Fsa5, FsaFormat::Cfsa2] {
+            let path = dir.path().join("test.fsa");
+            let mut out = Vec::new();
+            serializer_for(format, false).serialize(&fsa, &mut out).unwrap();
+            std::fs::write(&path, out).unwrap();
+
+            let loaded = read_fsa(&path).unwrap();
+            let sequences: Vec<Vec<u8>> = ByteSequenceIterator::new(loaded.as_ref()).map(|s| s.unwrap()).collect();
+            assert_eq!(sequences, vec![b"ala".to_vec(), b"kot".to_vec()]);
+        }
+    }
+
+    #[test]
+    fn test_read_fsa_rejects_corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.fsa");
+        let mut out = Vec::new();
+        let fsa = FsaBuilder::build([&b"ala"[..], b"kot"]).unwrap();
+        serializer_for(FsaFormat::Fsa5, false).serialize(&fsa, &mut out).unwrap();
+        out.truncate(out.len() - 2);
+        std::fs::write(&path, out).unwrap();
+
+        let error = read_fsa(&path).err().unwrap();
+        assert!(matches!(error, crate::error::ToolsError::Fsa(morfologik_fsa::error::FsaError::CorruptedFsa(_))));
+    }
+}